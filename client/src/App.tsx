@@ -1,7 +1,7 @@
 import { useState, useEffect, useRef, useMemo, type FormEvent } from 'react'
-import { useTable, useReducer } from 'spacetimedb/react'
+import { useTable, useReducer, useSpacetimeDB } from 'spacetimedb/react'
 import { Identity } from 'spacetimedb'
-import { tables, reducers } from './module_bindings'
+import { tables, reducers, type DbConnection } from './module_bindings'
 import { useIdentity, toHex } from './hooks/useIdentity'
 import { useFormAction } from './hooks/useFormAction'
 import type { UserAccount, Company, CompanyMember, Notification, Project, ProjectMember, ProjectChat } from './module_bindings/types'
@@ -33,7 +33,7 @@ function AuthenticatedApp() {
   const [accounts] = useTable(tables.user_account)
   const myAccount = accounts.find(a => toHex(a.identity) === identityHex)
 
-  const [allCompanies] = useTable(tables.company)
+  const [allCompanies] = useTable(tables.visible_companies)
   const [allMembers] = useTable(tables.company_member)
 
   // Derive my memberships and companies
@@ -64,7 +64,7 @@ function AuthenticatedApp() {
   }, [myAccount?.activeCompanyId, myMemberships.length])
 
   // ── Notifications & toasts ──
-  const [allNotifications] = useTable(tables.notification)
+  const [allNotifications] = useTable(tables.my_notifications)
   const myNotifications = allNotifications.filter(n => toHex(n.recipientIdentity) === identityHex)
   const activeNotifications = activeCompanyId != null
     ? myNotifications.filter(n => n.companyId === activeCompanyId)
@@ -613,13 +613,15 @@ function ProfileSection({ account, membership }: { account: UserAccount; members
   const [editing, setEditing] = useState(false)
   const [fullName, setFullName] = useState(account.fullName)
   const [nickname, setNickname] = useState(account.nickname)
-  const [email, setEmail] = useState(account.email)
+  const [contacts] = useTable(tables.visible_user_contacts)
+  const accountEmail = contacts.find(c => toHex(c.identity) === toHex(account.identity))?.email ?? ''
+  const [email, setEmail] = useState(accountEmail)
 
   useEffect(() => {
     setFullName(account.fullName)
     setNickname(account.nickname)
-    setEmail(account.email)
-  }, [account.fullName, account.nickname, account.email])
+    setEmail(accountEmail)
+  }, [account.fullName, account.nickname, accountEmail])
 
   const handleSave = () => {
     run(async () => {
@@ -628,6 +630,22 @@ function ProfileSection({ account, membership }: { account: UserAccount; members
     }, 'Profile updated')
   }
 
+  // Download everything the platform stores about you as a JSON file
+  const { getConnection } = useSpacetimeDB()
+  const handleExport = () => {
+    run(async () => {
+      const conn = getConnection() as DbConnection | null
+      if (!conn) throw new Error('Not connected')
+      const json = await conn.procedures.exportMyData({})
+      const url = URL.createObjectURL(new Blob([json], { type: 'application/json' }))
+      const link = document.createElement('a')
+      link.href = url
+      link.download = `my-data-${new Date().toISOString().slice(0, 10)}.json`
+      link.click()
+      URL.revokeObjectURL(url)
+    }, 'Data exported')
+  }
+
   return (
     <div>
       <h2>Your Profile</h2>
@@ -636,8 +654,10 @@ function ProfileSection({ account, membership }: { account: UserAccount; members
         <div>
           <p>Name: {account.fullName}</p>
           <p>Nickname: {account.nickname}</p>
-          <p>Email: {account.email}</p>
+          <p>Email: {accountEmail}</p>
           <button onClick={() => setEditing(true)}>Edit Profile</button>
+          {' '}
+          <button onClick={handleExport} disabled={loading}>{loading ? 'Exporting...' : 'Download My Data'}</button>
         </div>
       ) : (
         <div>
@@ -706,7 +726,7 @@ function CompanySettingsSection({ company }: { company: Company }) {
 // ── Capabilities ───────────────────────────────────────────
 
 function CapabilitiesSection({ companyId }: { companyId: bigint }) {
-  const [allCapabilities] = useTable(tables.capability)
+  const [allCapabilities] = useTable(tables.visible_capabilities)
   const cap = allCapabilities.find(c => c.companyId === companyId)
   const updateCapabilities = useReducer(reducers.updateCapabilities)
   const { error, success, loading, run } = useFormAction()
@@ -752,6 +772,7 @@ function CapabilitiesSection({ companyId }: { companyId: bigint }) {
 
 function TeamSection({ company, myRole, isMe, allMembers }: { company: Company; myRole: string; isMe: (id: unknown) => boolean; allMembers: CompanyMember[] }) {
   const [allAccounts] = useTable(tables.user_account)
+  const [contacts] = useTable(tables.visible_user_contacts)
   const companyMembers = allMembers.filter(m => m.companyId === company.id)
   const [onlineUsers] = useTable(tables.online_user)
 
@@ -806,7 +827,7 @@ function TeamSection({ company, myRole, isMe, allMembers }: { company: Company;
       <tr key={toHex(mem.identity)}>
         <td>{isOnline(mem.identity) ? '🟢' : '⚪'}</td>
         <td>{displayName}{isSelf ? ' (you)' : ''}</td>
-        <td>{acct.email}</td>
+        <td>{contacts.find(c => toHex(c.identity) === toHex(mem.identity))?.email}</td>
         <td>
           {canManage && !isSelf && memberRoleTag !== 'Owner' && (
             memberRoleTag === 'Pending'
@@ -894,7 +915,7 @@ function TeamSection({ company, myRole, isMe, allMembers }: { company: Company;
 // ── Invite Codes ───────────────────────────────────────────
 
 function InviteCodesSection({ companyId }: { companyId: bigint }) {
-  const [allCodes] = useTable(tables.invite_code)
+  const [allCodes] = useTable(tables.my_invite_codes)
   const companyCodes = allCodes.filter(c => c.companyId === companyId)
   const generateCode = useReducer(reducers.generateInviteCode)
   const deleteCode = useReducer(reducers.deleteInviteCode)
@@ -925,7 +946,7 @@ function InviteCodesSection({ companyId }: { companyId: bigint }) {
           ))}
         </ul>
       )}
-      <button onClick={() => run(() => generateCode({ maxUses: 10 }), 'Invite code generated')} disabled={loading}>
+      <button onClick={() => run(() => generateCode({ maxUses: 10, expiresAt: undefined, role: undefined }), 'Invite code generated')} disabled={loading}>
         {loading ? 'Generating...' : 'Generate Invite Code'}
       </button>
       {error && <p style={{ color: 'red' }}>{error}</p>}
@@ -943,9 +964,9 @@ function ConnectionsSection({ company }: { company: Company }) {
   const connections = allConnections.filter(c => c.companyA === company.id || c.companyB === company.id)
   const [allMembers] = useTable(tables.company_member)
 
-  const [allCompanies] = useTable(tables.company)
+  const [allCompanies] = useTable(tables.visible_companies)
   const [allAccounts] = useTable(tables.user_account)
-  const [allChats] = useTable(tables.connection_chat)
+  const [allChats] = useTable(tables.my_connection_chat)
 
   const requestConnection = useReducer(reducers.requestConnection)
   const cancelRequest = useReducer(reducers.cancelRequest)
@@ -1057,7 +1078,7 @@ function ConnectionsSection({ company }: { company: Company }) {
 
   const handleSendChat = (connectionId: bigint) => {
     if (!chatInput.trim()) return
-    run(() => sendConnectionChat({ connectionId, text: chatInput.trim() }))
+    run(() => sendConnectionChat({ connectionId, text: chatInput.trim(), parentId: undefined, quotedId: undefined, attachmentIds: [] }))
     setChatInput('')
   }
 
@@ -1227,8 +1248,8 @@ function ProjectsSection({ company, canManage }: { company: Company; canManage:
 
   const [allProjects] = useTable(tables.project)
   const [allProjectMembers] = useTable(tables.project_member)
-  const [allProjectChats] = useTable(tables.project_chat)
-  const [allCompanies] = useTable(tables.company)
+  const [allProjectChats] = useTable(tables.my_project_chat)
+  const [allCompanies] = useTable(tables.visible_companies)
   const [allMembers] = useTable(tables.company_member)
   const [allAccounts] = useTable(tables.user_account)
   const [allConnections] = useTable(tables.company_connection)
@@ -1322,7 +1343,7 @@ function ProjectsSection({ company, canManage }: { company: Company; canManage:
     setChatInput('')
     run(async () => {
       try {
-        await sendProjectChat({ projectId: selectedProjectId, text })
+        await sendProjectChat({ projectId: selectedProjectId, text, parentId: undefined, quotedId: undefined, attachmentIds: [] })
       } catch (err) {
         // Restore message on failure so the user doesn't lose it
         setChatInput(text)
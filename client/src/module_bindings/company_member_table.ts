@@ -22,4 +22,5 @@ export default __t.row({
     return UserRole;
   },
   joinedAt: __t.timestamp().name("joined_at"),
+  customRoleId: __t.option(__t.u64()).name("custom_role_id"),
 });
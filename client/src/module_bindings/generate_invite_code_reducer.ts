@@ -10,6 +10,14 @@ import {
   type Infer as __Infer,
 } from "spacetimedb";
 
+import {
+  UserRole,
+} from "./types";
+
 export default {
   maxUses: __t.u32(),
+  expiresAt: __t.option(__t.timestamp()),
+  get role() {
+    return __t.option(UserRole);
+  },
 };
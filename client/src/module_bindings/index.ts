@@ -35,86 +35,149 @@ import {
 
 // Import all reducer arg schemas
 import AcceptConnectionReducer from "./accept_connection_reducer";
+import AcceptPersonalInvitationReducer from "./accept_personal_invitation_reducer";
 import AcceptProjectInviteReducer from "./accept_project_invite_reducer";
+import AddChannelMemberReducer from "./add_channel_member_reducer";
 import AddColleagueByIdentityReducer from "./add_colleague_by_identity_reducer";
+import AddPlatformModeratorReducer from "./add_platform_moderator_reducer";
+import AssignCompanyRoleReducer from "./assign_company_role_reducer";
+import AssignProjectParticipantReducer from "./assign_project_participant_reducer";
+import BeginAttachmentUploadReducer from "./begin_attachment_upload_reducer";
 import BlockCompanyReducer from "./block_company_reducer";
+import BlockUserReducer from "./block_user_reducer";
 import CancelRequestReducer from "./cancel_request_reducer";
 import ClearNotificationsReducer from "./clear_notifications_reducer";
+import CompleteAttachmentUploadReducer from "./complete_attachment_upload_reducer";
 import CreateAccountReducer from "./create_account_reducer";
+import CreateChannelReducer from "./create_channel_reducer";
 import CreateCompanyReducer from "./create_company_reducer";
+import CreateCompanyRoleReducer from "./create_company_role_reducer";
 import CreateProjectReducer from "./create_project_reducer";
 import DeclineConnectionReducer from "./decline_connection_reducer";
 import DeclineProjectInviteReducer from "./decline_project_invite_reducer";
+import DeleteAccountReducer from "./delete_account_reducer";
+import DeleteAttachmentReducer from "./delete_attachment_reducer";
+import DeleteChannelReducer from "./delete_channel_reducer";
+import DeleteChatMessageReducer from "./delete_chat_message_reducer";
 import DeleteCompanyReducer from "./delete_company_reducer";
+import DeleteCompanyRoleReducer from "./delete_company_role_reducer";
 import DeleteInviteCodeReducer from "./delete_invite_code_reducer";
 import DeleteProjectReducer from "./delete_project_reducer";
 import DisconnectCompanyReducer from "./disconnect_company_reducer";
+import EditChatMessageReducer from "./edit_chat_message_reducer";
 import GenerateInviteCodeReducer from "./generate_invite_code_reducer";
+import InviteByEmailReducer from "./invite_by_email_reducer";
 import InviteToProjectReducer from "./invite_to_project_reducer";
 import JoinCompanyReducer from "./join_company_reducer";
 import KickFromProjectReducer from "./kick_from_project_reducer";
 import LeaveCompanyReducer from "./leave_company_reducer";
 import LeaveProjectReducer from "./leave_project_reducer";
 import MarkAllNotificationsReadReducer from "./mark_all_notifications_read_reducer";
+import MarkConversationReadReducer from "./mark_conversation_read_reducer";
 import MarkNotificationReadReducer from "./mark_notification_read_reducer";
+import MuteConversationReducer from "./mute_conversation_reducer";
+import OpenDirectConversationReducer from "./open_direct_conversation_reducer";
+import PinChatMessageReducer from "./pin_chat_message_reducer";
+import PurgeCompanyReducer from "./purge_company_reducer";
+import PurgeExpiredInviteCodeReducer from "./purge_expired_invite_code_reducer";
+import RebuildChatSearchIndexReducer from "./rebuild_chat_search_index_reducer";
+import RemoveChannelMemberReducer from "./remove_channel_member_reducer";
 import RemoveColleagueReducer from "./remove_colleague_reducer";
+import RemovePlatformModeratorReducer from "./remove_platform_moderator_reducer";
+import RemoveProjectParticipantReducer from "./remove_project_participant_reducer";
+import ReportReducer from "./report_reducer";
 import RequestConnectionReducer from "./request_connection_reducer";
+import RestoreCompanyReducer from "./restore_company_reducer";
+import ReviewReportReducer from "./review_report_reducer";
+import RevokePersonalInvitationReducer from "./revoke_personal_invitation_reducer";
+import SendChannelChatReducer from "./send_channel_chat_reducer";
 import SendConnectionChatReducer from "./send_connection_chat_reducer";
 import SendProjectChatReducer from "./send_project_chat_reducer";
+import SetChatRetentionReducer from "./set_chat_retention_reducer";
+import SetCompanyDeletionGraceDaysReducer from "./set_company_deletion_grace_days_reducer";
+import SetMessageHiddenReducer from "./set_message_hidden_reducer";
+import SetNotificationPreferenceReducer from "./set_notification_preference_reducer";
+import SetPlatformOwnerReducer from "./set_platform_owner_reducer";
+import SetRateLimitsReducer from "./set_rate_limits_reducer";
+import SetReadReceiptsReducer from "./set_read_receipts_reducer";
+import SetStorageQuotaReducer from "./set_storage_quota_reducer";
+import SuspendCompanyReducer from "./suspend_company_reducer";
+import SweepChatRetentionReducer from "./sweep_chat_retention_reducer";
 import SwitchActiveCompanyReducer from "./switch_active_company_reducer";
 import TransferOwnershipReducer from "./transfer_ownership_reducer";
 import UnblockCompanyReducer from "./unblock_company_reducer";
+import UnblockUserReducer from "./unblock_user_reducer";
+import UnmuteConversationReducer from "./unmute_conversation_reducer";
+import UnpinChatMessageReducer from "./unpin_chat_message_reducer";
+import UnsuspendCompanyReducer from "./unsuspend_company_reducer";
 import UpdateCapabilitiesReducer from "./update_capabilities_reducer";
 import UpdateCompanyProfileReducer from "./update_company_profile_reducer";
+import UpdateCompanyRoleReducer from "./update_company_role_reducer";
 import UpdateProfileReducer from "./update_profile_reducer";
+import UpdateProjectReducer from "./update_project_reducer";
 import UpdateUserRoleReducer from "./update_user_role_reducer";
+import UploadAttachmentChunkReducer from "./upload_attachment_chunk_reducer";
 
 // Import all procedure arg schemas
+import * as DownloadAttachmentProcedure from "./download_attachment_procedure";
+import * as ExportMyDataProcedure from "./export_my_data_procedure";
+import * as SearchChatProcedure from "./search_chat_procedure";
 
 // Import all table schema definitions
-import CapabilityRow from "./capability_table";
-import CompanyRow from "./company_table";
+import ChatRetentionRow from "./chat_retention_table";
 import CompanyConnectionRow from "./company_connection_table";
 import CompanyMemberRow from "./company_member_table";
-import ConnectionChatRow from "./connection_chat_table";
-import InviteCodeRow from "./invite_code_table";
-import NotificationRow from "./notification_table";
+import CompanySuspensionRow from "./company_suspension_table";
+import MyChannelChatRow from "./my_channel_chat_table";
+import MyChannelMembersRow from "./my_channel_members_table";
+import MyChannelsRow from "./my_channels_table";
+import MyChatArchivesRow from "./my_chat_archives_table";
+import MyChatRevisionsRow from "./my_chat_revisions_table";
+import MyCompanyAuditEventsRow from "./my_company_audit_events_table";
+import MyCompanyRolesRow from "./my_company_roles_table";
+import MyCompanyStorageRow from "./my_company_storage_table";
+import MyConnectionChatRow from "./my_connection_chat_table";
+import MyConversationMutesRow from "./my_conversation_mutes_table";
+import MyInviteCodesRow from "./my_invite_codes_table";
+import MyNotificationPreferencesRow from "./my_notification_preferences_table";
+import MyNotificationsRow from "./my_notifications_table";
+import MyPersonalInvitationsRow from "./my_personal_invitations_table";
+import MyPinnedMessagesRow from "./my_pinned_messages_table";
+import MyProjectChatRow from "./my_project_chat_table";
+import MyProjectParticipantsRow from "./my_project_participants_table";
+import MyReadCursorsRow from "./my_read_cursors_table";
+import MyReadReceiptSharingRow from "./my_read_receipt_sharing_table";
+import MyUserBlocksRow from "./my_user_blocks_table";
 import OnlineUserRow from "./online_user_table";
+import PlatformAuditEventsRow from "./platform_audit_events_table";
+import PlatformConfigRow from "./platform_config_table";
+import PlatformModeratorRow from "./platform_moderator_table";
 import ProjectRow from "./project_table";
-import ProjectChatRow from "./project_chat_table";
 import ProjectMemberRow from "./project_member_table";
+import RateLimitConfigRow from "./rate_limit_config_table";
 import UserAccountRow from "./user_account_table";
+import VisibleAttachmentsRow from "./visible_attachments_table";
+import VisibleCapabilitiesRow from "./visible_capabilities_table";
+import VisibleCompaniesRow from "./visible_companies_table";
+import VisibleReadReceiptsRow from "./visible_read_receipts_table";
+import VisibleReportsRow from "./visible_reports_table";
+import VisibleUserContactsRow from "./visible_user_contacts_table";
 
 /** Type-only namespace exports for generated type groups. */
 
 /** The schema information for all tables in this module. This is defined the same was as the tables would have been defined in the server. */
 const tablesSchema = __schema({
-  capability: __table({
-    name: 'capability',
+  chat_retention: __table({
+    name: 'chat_retention',
     indexes: [
       { name: 'company_id', algorithm: 'btree', columns: [
         'companyId',
       ] },
     ],
     constraints: [
-      { name: 'capability_company_id_key', constraint: 'unique', columns: ['companyId'] },
+      { name: 'chat_retention_company_id_key', constraint: 'unique', columns: ['companyId'] },
     ],
-  }, CapabilityRow),
-  company: __table({
-    name: 'company',
-    indexes: [
-      { name: 'id', algorithm: 'btree', columns: [
-        'id',
-      ] },
-      { name: 'slug', algorithm: 'btree', columns: [
-        'slug',
-      ] },
-    ],
-    constraints: [
-      { name: 'company_id_key', constraint: 'unique', columns: ['id'] },
-      { name: 'company_slug_key', constraint: 'unique', columns: ['slug'] },
-    ],
-  }, CompanyRow),
+  }, ChatRetentionRow),
   company_connection: __table({
     name: 'company_connection',
     indexes: [
@@ -149,48 +212,157 @@ const tablesSchema = __schema({
       { name: 'company_member_id_key', constraint: 'unique', columns: ['id'] },
     ],
   }, CompanyMemberRow),
-  connection_chat: __table({
-    name: 'connection_chat',
+  company_suspension: __table({
+    name: 'company_suspension',
     indexes: [
-      { name: 'chat_by_connection', algorithm: 'btree', columns: [
-        'connectionId',
-      ] },
-      { name: 'id', algorithm: 'btree', columns: [
-        'id',
+      { name: 'company_id', algorithm: 'btree', columns: [
+        'companyId',
       ] },
     ],
     constraints: [
-      { name: 'connection_chat_id_key', constraint: 'unique', columns: ['id'] },
+      { name: 'company_suspension_company_id_key', constraint: 'unique', columns: ['companyId'] },
     ],
-  }, ConnectionChatRow),
-  invite_code: __table({
-    name: 'invite_code',
+  }, CompanySuspensionRow),
+  my_channel_chat: __table({
+    name: 'my_channel_chat',
     indexes: [
-      { name: 'code', algorithm: 'btree', columns: [
-        'code',
-      ] },
-      { name: 'invite_by_company', algorithm: 'btree', columns: [
-        'companyId',
-      ] },
     ],
     constraints: [
-      { name: 'invite_code_code_key', constraint: 'unique', columns: ['code'] },
     ],
-  }, InviteCodeRow),
-  notification: __table({
-    name: 'notification',
+  }, MyChannelChatRow),
+  my_channel_members: __table({
+    name: 'my_channel_members',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyChannelMembersRow),
+  my_channels: __table({
+    name: 'my_channels',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyChannelsRow),
+  my_chat_archives: __table({
+    name: 'my_chat_archives',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyChatArchivesRow),
+  my_chat_revisions: __table({
+    name: 'my_chat_revisions',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyChatRevisionsRow),
+  my_company_audit_events: __table({
+    name: 'my_company_audit_events',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyCompanyAuditEventsRow),
+  my_company_roles: __table({
+    name: 'my_company_roles',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyCompanyRolesRow),
+  my_company_storage: __table({
+    name: 'my_company_storage',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyCompanyStorageRow),
+  my_connection_chat: __table({
+    name: 'my_connection_chat',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyConnectionChatRow),
+  my_conversation_mutes: __table({
+    name: 'my_conversation_mutes',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyConversationMutesRow),
+  my_invite_codes: __table({
+    name: 'my_invite_codes',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyInviteCodesRow),
+  my_notification_preferences: __table({
+    name: 'my_notification_preferences',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyNotificationPreferencesRow),
+  my_notifications: __table({
+    name: 'my_notifications',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyNotificationsRow),
+  my_personal_invitations: __table({
+    name: 'my_personal_invitations',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyPersonalInvitationsRow),
+  my_pinned_messages: __table({
+    name: 'my_pinned_messages',
     indexes: [
-      { name: 'id', algorithm: 'btree', columns: [
-        'id',
-      ] },
-      { name: 'notif_by_recipient', algorithm: 'btree', columns: [
-        'recipientIdentity',
-      ] },
     ],
     constraints: [
-      { name: 'notification_id_key', constraint: 'unique', columns: ['id'] },
     ],
-  }, NotificationRow),
+  }, MyPinnedMessagesRow),
+  my_project_chat: __table({
+    name: 'my_project_chat',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyProjectChatRow),
+  my_project_participants: __table({
+    name: 'my_project_participants',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyProjectParticipantsRow),
+  my_read_cursors: __table({
+    name: 'my_read_cursors',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyReadCursorsRow),
+  my_read_receipt_sharing: __table({
+    name: 'my_read_receipt_sharing',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyReadReceiptSharingRow),
+  my_user_blocks: __table({
+    name: 'my_user_blocks',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, MyUserBlocksRow),
   online_user: __table({
     name: 'online_user',
     indexes: [
@@ -202,31 +374,46 @@ const tablesSchema = __schema({
       { name: 'online_user_identity_key', constraint: 'unique', columns: ['identity'] },
     ],
   }, OnlineUserRow),
-  project: __table({
-    name: 'project',
+  platform_audit_events: __table({
+    name: 'platform_audit_events',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, PlatformAuditEventsRow),
+  platform_config: __table({
+    name: 'platform_config',
     indexes: [
       { name: 'id', algorithm: 'btree', columns: [
         'id',
       ] },
     ],
     constraints: [
-      { name: 'project_id_key', constraint: 'unique', columns: ['id'] },
+      { name: 'platform_config_id_key', constraint: 'unique', columns: ['id'] },
     ],
-  }, ProjectRow),
-  project_chat: __table({
-    name: 'project_chat',
+  }, PlatformConfigRow),
+  platform_moderator: __table({
+    name: 'platform_moderator',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'platform_moderator_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, PlatformModeratorRow),
+  project: __table({
+    name: 'project',
     indexes: [
       { name: 'id', algorithm: 'btree', columns: [
         'id',
       ] },
-      { name: 'pchat_by_project', algorithm: 'btree', columns: [
-        'projectId',
-      ] },
     ],
     constraints: [
-      { name: 'project_chat_id_key', constraint: 'unique', columns: ['id'] },
+      { name: 'project_id_key', constraint: 'unique', columns: ['id'] },
     ],
-  }, ProjectChatRow),
+  }, ProjectRow),
   project_member: __table({
     name: 'project_member',
     indexes: [
@@ -244,6 +431,17 @@ const tablesSchema = __schema({
       { name: 'project_member_id_key', constraint: 'unique', columns: ['id'] },
     ],
   }, ProjectMemberRow),
+  rate_limit_config: __table({
+    name: 'rate_limit_config',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'rate_limit_config_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, RateLimitConfigRow),
   user_account: __table({
     name: 'user_account',
     indexes: [
@@ -255,48 +453,142 @@ const tablesSchema = __schema({
       { name: 'user_account_identity_key', constraint: 'unique', columns: ['identity'] },
     ],
   }, UserAccountRow),
+  visible_attachments: __table({
+    name: 'visible_attachments',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, VisibleAttachmentsRow),
+  visible_capabilities: __table({
+    name: 'visible_capabilities',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, VisibleCapabilitiesRow),
+  visible_companies: __table({
+    name: 'visible_companies',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, VisibleCompaniesRow),
+  visible_read_receipts: __table({
+    name: 'visible_read_receipts',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, VisibleReadReceiptsRow),
+  visible_reports: __table({
+    name: 'visible_reports',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, VisibleReportsRow),
+  visible_user_contacts: __table({
+    name: 'visible_user_contacts',
+    indexes: [
+    ],
+    constraints: [
+    ],
+  }, VisibleUserContactsRow),
 });
 
 /** The schema information for all reducers in this module. This is defined the same way as the reducers would have been defined in the server, except the body of the reducer is omitted in code generation. */
 const reducersSchema = __reducers(
   __reducerSchema("accept_connection", AcceptConnectionReducer),
+  __reducerSchema("accept_personal_invitation", AcceptPersonalInvitationReducer),
   __reducerSchema("accept_project_invite", AcceptProjectInviteReducer),
+  __reducerSchema("add_channel_member", AddChannelMemberReducer),
   __reducerSchema("add_colleague_by_identity", AddColleagueByIdentityReducer),
+  __reducerSchema("add_platform_moderator", AddPlatformModeratorReducer),
+  __reducerSchema("assign_company_role", AssignCompanyRoleReducer),
+  __reducerSchema("assign_project_participant", AssignProjectParticipantReducer),
+  __reducerSchema("begin_attachment_upload", BeginAttachmentUploadReducer),
   __reducerSchema("block_company", BlockCompanyReducer),
+  __reducerSchema("block_user", BlockUserReducer),
   __reducerSchema("cancel_request", CancelRequestReducer),
   __reducerSchema("clear_notifications", ClearNotificationsReducer),
+  __reducerSchema("complete_attachment_upload", CompleteAttachmentUploadReducer),
   __reducerSchema("create_account", CreateAccountReducer),
+  __reducerSchema("create_channel", CreateChannelReducer),
   __reducerSchema("create_company", CreateCompanyReducer),
+  __reducerSchema("create_company_role", CreateCompanyRoleReducer),
   __reducerSchema("create_project", CreateProjectReducer),
   __reducerSchema("decline_connection", DeclineConnectionReducer),
   __reducerSchema("decline_project_invite", DeclineProjectInviteReducer),
+  __reducerSchema("delete_account", DeleteAccountReducer),
+  __reducerSchema("delete_attachment", DeleteAttachmentReducer),
+  __reducerSchema("delete_channel", DeleteChannelReducer),
+  __reducerSchema("delete_chat_message", DeleteChatMessageReducer),
   __reducerSchema("delete_company", DeleteCompanyReducer),
+  __reducerSchema("delete_company_role", DeleteCompanyRoleReducer),
   __reducerSchema("delete_invite_code", DeleteInviteCodeReducer),
   __reducerSchema("delete_project", DeleteProjectReducer),
   __reducerSchema("disconnect_company", DisconnectCompanyReducer),
+  __reducerSchema("edit_chat_message", EditChatMessageReducer),
   __reducerSchema("generate_invite_code", GenerateInviteCodeReducer),
+  __reducerSchema("invite_by_email", InviteByEmailReducer),
   __reducerSchema("invite_to_project", InviteToProjectReducer),
   __reducerSchema("join_company", JoinCompanyReducer),
   __reducerSchema("kick_from_project", KickFromProjectReducer),
   __reducerSchema("leave_company", LeaveCompanyReducer),
   __reducerSchema("leave_project", LeaveProjectReducer),
   __reducerSchema("mark_all_notifications_read", MarkAllNotificationsReadReducer),
+  __reducerSchema("mark_conversation_read", MarkConversationReadReducer),
   __reducerSchema("mark_notification_read", MarkNotificationReadReducer),
+  __reducerSchema("mute_conversation", MuteConversationReducer),
+  __reducerSchema("open_direct_conversation", OpenDirectConversationReducer),
+  __reducerSchema("pin_chat_message", PinChatMessageReducer),
+  __reducerSchema("purge_company", PurgeCompanyReducer),
+  __reducerSchema("purge_expired_invite_code", PurgeExpiredInviteCodeReducer),
+  __reducerSchema("rebuild_chat_search_index", RebuildChatSearchIndexReducer),
+  __reducerSchema("remove_channel_member", RemoveChannelMemberReducer),
   __reducerSchema("remove_colleague", RemoveColleagueReducer),
+  __reducerSchema("remove_platform_moderator", RemovePlatformModeratorReducer),
+  __reducerSchema("remove_project_participant", RemoveProjectParticipantReducer),
+  __reducerSchema("report", ReportReducer),
   __reducerSchema("request_connection", RequestConnectionReducer),
+  __reducerSchema("restore_company", RestoreCompanyReducer),
+  __reducerSchema("review_report", ReviewReportReducer),
+  __reducerSchema("revoke_personal_invitation", RevokePersonalInvitationReducer),
+  __reducerSchema("send_channel_chat", SendChannelChatReducer),
   __reducerSchema("send_connection_chat", SendConnectionChatReducer),
   __reducerSchema("send_project_chat", SendProjectChatReducer),
+  __reducerSchema("set_chat_retention", SetChatRetentionReducer),
+  __reducerSchema("set_company_deletion_grace_days", SetCompanyDeletionGraceDaysReducer),
+  __reducerSchema("set_message_hidden", SetMessageHiddenReducer),
+  __reducerSchema("set_notification_preference", SetNotificationPreferenceReducer),
+  __reducerSchema("set_platform_owner", SetPlatformOwnerReducer),
+  __reducerSchema("set_rate_limits", SetRateLimitsReducer),
+  __reducerSchema("set_read_receipts", SetReadReceiptsReducer),
+  __reducerSchema("set_storage_quota", SetStorageQuotaReducer),
+  __reducerSchema("suspend_company", SuspendCompanyReducer),
+  __reducerSchema("sweep_chat_retention", SweepChatRetentionReducer),
   __reducerSchema("switch_active_company", SwitchActiveCompanyReducer),
   __reducerSchema("transfer_ownership", TransferOwnershipReducer),
   __reducerSchema("unblock_company", UnblockCompanyReducer),
+  __reducerSchema("unblock_user", UnblockUserReducer),
+  __reducerSchema("unmute_conversation", UnmuteConversationReducer),
+  __reducerSchema("unpin_chat_message", UnpinChatMessageReducer),
+  __reducerSchema("unsuspend_company", UnsuspendCompanyReducer),
   __reducerSchema("update_capabilities", UpdateCapabilitiesReducer),
   __reducerSchema("update_company_profile", UpdateCompanyProfileReducer),
+  __reducerSchema("update_company_role", UpdateCompanyRoleReducer),
   __reducerSchema("update_profile", UpdateProfileReducer),
+  __reducerSchema("update_project", UpdateProjectReducer),
   __reducerSchema("update_user_role", UpdateUserRoleReducer),
+  __reducerSchema("upload_attachment_chunk", UploadAttachmentChunkReducer),
 );
 
 /** The schema information for all procedures in this module. This is defined the same way as the procedures would have been defined in the server. */
 const proceduresSchema = __procedures(
+  __procedureSchema("download_attachment", DownloadAttachmentProcedure.params, DownloadAttachmentProcedure.returnType),
+  __procedureSchema("export_my_data", ExportMyDataProcedure.params, ExportMyDataProcedure.returnType),
+  __procedureSchema("search_chat", SearchChatProcedure.params, SearchChatProcedure.returnType),
 );
 
 /** The remote SpacetimeDB module schema, both runtime and type information. */
@@ -11,8 +11,10 @@ import {
 } from "spacetimedb";
 
 export default __t.row({
-  code: __t.string().primaryKey(),
+  id: __t.u64(),
   companyId: __t.u64().name("company_id"),
+  name: __t.string(),
+  isDirect: __t.bool().name("is_direct"),
   createdBy: __t.identity().name("created_by"),
-  usesRemaining: __t.u32().name("uses_remaining"),
+  createdAt: __t.timestamp().name("created_at"),
 });
@@ -11,9 +11,7 @@ import {
 } from "spacetimedb";
 
 export default __t.row({
-  id: __t.u64().primaryKey(),
-  connectionId: __t.u64().name("connection_id"),
-  sender: __t.identity(),
-  text: __t.string(),
-  createdAt: __t.timestamp().name("created_at"),
+  companyId: __t.u64().name("company_id"),
+  usedBytes: __t.u64().name("used_bytes"),
+  quotaBytes: __t.u64().name("quota_bytes"),
 });
@@ -10,19 +10,19 @@ import {
   type Infer as __Infer,
 } from "spacetimedb";
 import {
+  NotificationLevel,
   NotificationType,
 } from "./types";
 
 
 export default __t.row({
-  id: __t.u64().primaryKey(),
-  recipientIdentity: __t.identity().name("recipient_identity"),
+  id: __t.u64(),
+  identity: __t.identity(),
   companyId: __t.u64().name("company_id"),
   get notificationType() {
     return NotificationType.name("notification_type");
   },
-  title: __t.string(),
-  body: __t.string(),
-  isRead: __t.bool().name("is_read"),
-  createdAt: __t.timestamp().name("created_at"),
+  get level() {
+    return NotificationLevel;
+  },
 });
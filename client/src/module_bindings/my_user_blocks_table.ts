@@ -11,9 +11,8 @@ import {
 } from "spacetimedb";
 
 export default __t.row({
-  id: __t.u64().primaryKey(),
-  projectId: __t.u64().name("project_id"),
-  sender: __t.identity(),
-  text: __t.string(),
+  id: __t.u64(),
+  blocker: __t.identity(),
+  blocked: __t.identity(),
   createdAt: __t.timestamp().name("created_at"),
 });
@@ -13,4 +13,7 @@ import {
 export default {
   projectId: __t.u64(),
   text: __t.string(),
+  parentId: __t.option(__t.u64()),
+  quotedId: __t.option(__t.u64()),
+  attachmentIds: __t.array(__t.u64()),
 };
@@ -10,6 +10,107 @@ import {
   type Infer as __Infer,
 } from "spacetimedb";
 
+export const Attachment = __t.object("Attachment", {
+  id: __t.u64(),
+  get kind() {
+    return ChatKind;
+  },
+  conversationId: __t.u64(),
+  companyId: __t.u64(),
+  uploader: __t.identity(),
+  filename: __t.string(),
+  mimeType: __t.string(),
+  size: __t.u64(),
+  checksum: __t.string(),
+  get status() {
+    return AttachmentStatus;
+  },
+  receivedBytes: __t.u64(),
+  chunkCount: __t.u32(),
+  messageId: __t.u64(),
+  createdAt: __t.timestamp(),
+  completedAt: __t.option(__t.timestamp()),
+});
+export type Attachment = __Infer<typeof Attachment>;
+
+export const AttachmentChunk = __t.object("AttachmentChunk", {
+  id: __t.u64(),
+  attachmentId: __t.u64(),
+  seq: __t.u32(),
+  data: __t.array(__t.u8()),
+});
+export type AttachmentChunk = __Infer<typeof AttachmentChunk>;
+
+// The tagged union or sum type for the algebraic type `AttachmentStatus`.
+export const AttachmentStatus = __t.enum("AttachmentStatus", {
+  Uploading: __t.unit(),
+  Ready: __t.unit(),
+});
+export type AttachmentStatus = __Infer<typeof AttachmentStatus>;
+
+// The tagged union or sum type for the algebraic type `AuditAction`.
+export const AuditAction = __t.enum("AuditAction", {
+  ColleagueRemoved: __t.unit(),
+  RoleChanged: __t.unit(),
+  OwnershipTransferred: __t.unit(),
+  CompanyBlocked: __t.unit(),
+  CompanyUnblocked: __t.unit(),
+  AccountDeleted: __t.unit(),
+  CompanyDeletionScheduled: __t.unit(),
+  CompanyRestored: __t.unit(),
+  CompanyDeleted: __t.unit(),
+  ProjectCreated: __t.unit(),
+  ProjectInviteSent: __t.unit(),
+  ProjectInviteAccepted: __t.unit(),
+  ProjectInviteDeclined: __t.unit(),
+  ProjectLeft: __t.unit(),
+  ProjectAutoDeleted: __t.unit(),
+  ProjectKicked: __t.unit(),
+  ProjectDeleted: __t.unit(),
+  CompanyRoleCreated: __t.unit(),
+  CompanyRoleUpdated: __t.unit(),
+  CompanyRoleDeleted: __t.unit(),
+  CompanyRoleAssigned: __t.unit(),
+  ProjectParticipantAssigned: __t.unit(),
+  ProjectParticipantRemoved: __t.unit(),
+  CompanySuspended: __t.unit(),
+  CompanyUnsuspended: __t.unit(),
+  ChatMessageDeleted: __t.unit(),
+  ChatRetentionChanged: __t.unit(),
+  ChannelCreated: __t.unit(),
+  ChannelDeleted: __t.unit(),
+  ChannelMemberAdded: __t.unit(),
+  ChannelMemberRemoved: __t.unit(),
+  ProjectUpdated: __t.unit(),
+  MessageHidden: __t.unit(),
+  MessageUnhidden: __t.unit(),
+  ModeratorAppointed: __t.unit(),
+  ModeratorRemoved: __t.unit(),
+  RateLimitsChanged: __t.unit(),
+  CompanyDeletionGraceChanged: __t.unit(),
+  StorageQuotaChanged: __t.unit(),
+  ChatSearchIndexRebuilt: __t.unit(),
+  PlatformOwnerChanged: __t.unit(),
+});
+export type AuditAction = __Infer<typeof AuditAction>;
+
+export const AuditEvent = __t.object("AuditEvent", {
+  id: __t.u64(),
+  actor: __t.identity(),
+  companyId: __t.u64(),
+  get action() {
+    return AuditAction;
+  },
+  targetIdentity: __t.option(__t.identity()),
+  targetCompanyId: __t.option(__t.u64()),
+  targetProjectId: __t.option(__t.u64()),
+  oldValue: __t.string(),
+  newValue: __t.string(),
+  createdAt: __t.timestamp(),
+  targetUser: __t.identity(),
+});
+export type AuditEvent = __Infer<typeof AuditEvent>;
+
 export const Capability = __t.object("Capability", {
   companyId: __t.u64(),
   canInstall: __t.bool(),
@@ -19,6 +120,125 @@ export const Capability = __t.object("Capability", {
 });
 export type Capability = __Infer<typeof Capability>;
 
+export const ChannelChat = __t.object("ChannelChat", {
+  id: __t.u64(),
+  channelId: __t.u64(),
+  sender: __t.identity(),
+  text: __t.string(),
+  createdAt: __t.timestamp(),
+  hidden: __t.bool(),
+  editedAt: __t.option(__t.timestamp()),
+  deleted: __t.bool(),
+  parentId: __t.option(__t.u64()),
+  quotedId: __t.option(__t.u64()),
+  replyCount: __t.u32(),
+  mentions: __t.array(__t.identity()),
+  mentionedCompanyIds: __t.array(__t.u64()),
+  attachmentIds: __t.array(__t.u64()),
+  get messageKind() {
+    return MessageKind;
+  },
+});
+export type ChannelChat = __Infer<typeof ChannelChat>;
+
+export const ChannelMember = __t.object("ChannelMember", {
+  id: __t.u64(),
+  channelId: __t.u64(),
+  identity: __t.identity(),
+  addedBy: __t.identity(),
+  joinedAt: __t.timestamp(),
+});
+export type ChannelMember = __Infer<typeof ChannelMember>;
+
+export const ChatArchive = __t.object("ChatArchive", {
+  id: __t.u64(),
+  get kind() {
+    return ChatKind;
+  },
+  conversationId: __t.u64(),
+  title: __t.string(),
+  companyIds: __t.array(__t.u64()),
+  archivedAt: __t.timestamp(),
+});
+export type ChatArchive = __Infer<typeof ChatArchive>;
+
+export const ChatArchiveReader = __t.object("ChatArchiveReader", {
+  id: __t.u64(),
+  archiveId: __t.u64(),
+  identity: __t.identity(),
+  companyId: __t.u64(),
+});
+export type ChatArchiveReader = __Infer<typeof ChatArchiveReader>;
+
+// The tagged union or sum type for the algebraic type `ChatKind`.
+export const ChatKind = __t.enum("ChatKind", {
+  Connection: __t.unit(),
+  Project: __t.unit(),
+  Channel: __t.unit(),
+});
+export type ChatKind = __Infer<typeof ChatKind>;
+
+export const ChatRetention = __t.object("ChatRetention", {
+  companyId: __t.u64(),
+  retentionDays: __t.u32(),
+  updatedBy: __t.identity(),
+  updatedAt: __t.timestamp(),
+});
+export type ChatRetention = __Infer<typeof ChatRetention>;
+
+export const ChatRetentionSweep = __t.object("ChatRetentionSweep", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type ChatRetentionSweep = __Infer<typeof ChatRetentionSweep>;
+
+export const ChatRevision = __t.object("ChatRevision", {
+  id: __t.u64(),
+  get kind() {
+    return ChatKind;
+  },
+  messageId: __t.u64(),
+  text: __t.string(),
+  revisedBy: __t.identity(),
+  revisedAt: __t.timestamp(),
+});
+export type ChatRevision = __Infer<typeof ChatRevision>;
+
+export const ChatSearchFilter = __t.object("ChatSearchFilter", {
+  get kind() {
+    return __t.option(ChatKind);
+  },
+  conversationId: __t.option(__t.u64()),
+  sender: __t.option(__t.identity()),
+  sentAfter: __t.option(__t.timestamp()),
+  sentBefore: __t.option(__t.timestamp()),
+});
+export type ChatSearchFilter = __Infer<typeof ChatSearchFilter>;
+
+export const ChatSearchHit = __t.object("ChatSearchHit", {
+  get kind() {
+    return ChatKind;
+  },
+  conversationId: __t.u64(),
+  messageId: __t.u64(),
+  sender: __t.identity(),
+  createdAt: __t.timestamp(),
+  snippet: __t.string(),
+  score: __t.u32(),
+});
+export type ChatSearchHit = __Infer<typeof ChatSearchHit>;
+
+export const ChatSearchTerm = __t.object("ChatSearchTerm", {
+  id: __t.u64(),
+  term: __t.string(),
+  get kind() {
+    return ChatKind;
+  },
+  messageId: __t.u64(),
+  occurrences: __t.u32(),
+});
+export type ChatSearchTerm = __Infer<typeof ChatSearchTerm>;
+
 export const Company = __t.object("Company", {
   id: __t.u64(),
   ownerIdentity: __t.identity(),
@@ -28,9 +248,20 @@ export const Company = __t.object("Company", {
   bio: __t.string(),
   isPublic: __t.bool(),
   kvkNumber: __t.string(),
+  purgeAt: __t.option(__t.timestamp()),
 });
 export type Company = __Infer<typeof Company>;
 
+export const CompanyChannel = __t.object("CompanyChannel", {
+  id: __t.u64(),
+  companyId: __t.u64(),
+  name: __t.string(),
+  isDirect: __t.bool(),
+  createdBy: __t.identity(),
+  createdAt: __t.timestamp(),
+});
+export type CompanyChannel = __Infer<typeof CompanyChannel>;
+
 export const CompanyMember = __t.object("CompanyMember", {
   id: __t.u64(),
   identity: __t.identity(),
@@ -39,9 +270,46 @@ export const CompanyMember = __t.object("CompanyMember", {
     return UserRole;
   },
   joinedAt: __t.timestamp(),
+  customRoleId: __t.option(__t.u64()),
 });
 export type CompanyMember = __Infer<typeof CompanyMember>;
 
+export const CompanyPurge = __t.object("CompanyPurge", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+  companyId: __t.u64(),
+  requestedBy: __t.identity(),
+});
+export type CompanyPurge = __Infer<typeof CompanyPurge>;
+
+export const CompanyRole = __t.object("CompanyRole", {
+  id: __t.u64(),
+  companyId: __t.u64(),
+  name: __t.string(),
+  get tier() {
+    return __t.option(UserRole);
+  },
+  get permissions() {
+    return __t.array(Permission);
+  },
+});
+export type CompanyRole = __Infer<typeof CompanyRole>;
+
+export const CompanyStorage = __t.object("CompanyStorage", {
+  companyId: __t.u64(),
+  usedBytes: __t.u64(),
+  quotaBytes: __t.u64(),
+});
+export type CompanyStorage = __Infer<typeof CompanyStorage>;
+
+export const CompanySuspension = __t.object("CompanySuspension", {
+  companyId: __t.u64(),
+  reason: __t.string(),
+  suspendedBy: __t.identity(),
+  suspendedAt: __t.timestamp(),
+});
+export type CompanySuspension = __Infer<typeof CompanySuspension>;
+
 export const Connection = __t.object("Connection", {
   id: __t.u64(),
   companyA: __t.u64(),
@@ -62,6 +330,18 @@ export const ConnectionChat = __t.object("ConnectionChat", {
   sender: __t.identity(),
   text: __t.string(),
   createdAt: __t.timestamp(),
+  hidden: __t.bool(),
+  editedAt: __t.option(__t.timestamp()),
+  deleted: __t.bool(),
+  parentId: __t.option(__t.u64()),
+  quotedId: __t.option(__t.u64()),
+  replyCount: __t.u32(),
+  mentions: __t.option(__t.array(__t.identity())),
+  mentionedCompanyIds: __t.option(__t.array(__t.u64())),
+  attachmentIds: __t.option(__t.array(__t.u64())),
+  get messageKind() {
+    return MessageKind;
+  },
 });
 export type ConnectionChat = __Infer<typeof ConnectionChat>;
 
@@ -73,14 +353,74 @@ export const ConnectionStatus = __t.enum("ConnectionStatus", {
 });
 export type ConnectionStatus = __Infer<typeof ConnectionStatus>;
 
+export const ConversationMute = __t.object("ConversationMute", {
+  id: __t.u64(),
+  identity: __t.identity(),
+  get kind() {
+    return ChatKind;
+  },
+  conversationId: __t.u64(),
+  mutedUntil: __t.option(__t.timestamp()),
+});
+export type ConversationMute = __Infer<typeof ConversationMute>;
+
+export const ConversationStats = __t.object("ConversationStats", {
+  id: __t.u64(),
+  get kind() {
+    return ChatKind;
+  },
+  conversationId: __t.u64(),
+  messageCount: __t.u64(),
+  lastMessageId: __t.u64(),
+});
+export type ConversationStats = __Infer<typeof ConversationStats>;
+
 export const InviteCode = __t.object("InviteCode", {
   code: __t.string(),
   companyId: __t.u64(),
   createdBy: __t.identity(),
   usesRemaining: __t.u32(),
+  get role() {
+    return UserRole;
+  },
+  expiresAt: __t.option(__t.timestamp()),
 });
 export type InviteCode = __Infer<typeof InviteCode>;
 
+export const InviteCodeExpiry = __t.object("InviteCodeExpiry", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+  code: __t.string(),
+});
+export type InviteCodeExpiry = __Infer<typeof InviteCodeExpiry>;
+
+// The tagged union or sum type for the algebraic type `MessageKind`.
+export const MessageKind = __t.enum("MessageKind", {
+  User: __t.unit(),
+  get System() {
+    return SystemEvent;
+  },
+});
+export type MessageKind = __Infer<typeof MessageKind>;
+
+export const ModerationReport = __t.object("ModerationReport", {
+  id: __t.u64(),
+  reporter: __t.identity(),
+  get target() {
+    return ReportTarget;
+  },
+  reason: __t.string(),
+  excerpt: __t.string(),
+  get status() {
+    return ReportStatus;
+  },
+  createdAt: __t.timestamp(),
+  reviewedBy: __t.option(__t.identity()),
+  decision: __t.string(),
+  reviewedAt: __t.option(__t.timestamp()),
+});
+export type ModerationReport = __Infer<typeof ModerationReport>;
+
 export const Notification = __t.object("Notification", {
   id: __t.u64(),
   recipientIdentity: __t.identity(),
@@ -92,9 +432,47 @@ export const Notification = __t.object("Notification", {
   body: __t.string(),
   isRead: __t.bool(),
   createdAt: __t.timestamp(),
+  messageId: __t.u64(),
+  messageCount: __t.u32(),
+  projectId: __t.u64(),
+  connectionId: __t.u64(),
+  channelId: __t.u64(),
+  actorIdentity: __t.identity(),
+  targetCompanyId: __t.u64(),
+  get scope() {
+    return NotificationScope;
+  },
 });
 export type Notification = __Infer<typeof Notification>;
 
+// The tagged union or sum type for the algebraic type `NotificationLevel`.
+export const NotificationLevel = __t.enum("NotificationLevel", {
+  All: __t.unit(),
+  MentionsOnly: __t.unit(),
+  Off: __t.unit(),
+});
+export type NotificationLevel = __Infer<typeof NotificationLevel>;
+
+export const NotificationPreference = __t.object("NotificationPreference", {
+  id: __t.u64(),
+  identity: __t.identity(),
+  companyId: __t.u64(),
+  get notificationType() {
+    return NotificationType;
+  },
+  get level() {
+    return NotificationLevel;
+  },
+});
+export type NotificationPreference = __Infer<typeof NotificationPreference>;
+
+// The tagged union or sum type for the algebraic type `NotificationScope`.
+export const NotificationScope = __t.enum("NotificationScope", {
+  Company: __t.unit(),
+  Platform: __t.unit(),
+});
+export type NotificationScope = __Infer<typeof NotificationScope>;
+
 // The tagged union or sum type for the algebraic type `NotificationType`.
 export const NotificationType = __t.enum("NotificationType", {
   MemberJoined: __t.unit(),
@@ -110,6 +488,18 @@ export const NotificationType = __t.enum("NotificationType", {
   ProjectChat: __t.unit(),
   ProjectKicked: __t.unit(),
   ProjectLeft: __t.unit(),
+  ProjectAssignment: __t.unit(),
+  ReportSubmitted: __t.unit(),
+  ReportReviewed: __t.unit(),
+  CompanySuspension: __t.unit(),
+  ChatReply: __t.unit(),
+  ProjectChatReply: __t.unit(),
+  ChatMention: __t.unit(),
+  ProjectChatMention: __t.unit(),
+  ChannelMessage: __t.unit(),
+  ChannelReply: __t.unit(),
+  ChannelMention: __t.unit(),
+  ChannelAdded: __t.unit(),
 });
 export type NotificationType = __Infer<typeof NotificationType>;
 
@@ -119,6 +509,70 @@ export const OnlineUser = __t.object("OnlineUser", {
 });
 export type OnlineUser = __Infer<typeof OnlineUser>;
 
+// The tagged union or sum type for the algebraic type `Permission`.
+export const Permission = __t.enum("Permission", {
+  ManageMembers: __t.unit(),
+  ManageRoles: __t.unit(),
+  ManageConnections: __t.unit(),
+  ManageProjects: __t.unit(),
+  Chat: __t.unit(),
+  EditProfile: __t.unit(),
+  ViewAuditLog: __t.unit(),
+});
+export type Permission = __Infer<typeof Permission>;
+
+export const PersonalInvitation = __t.object("PersonalInvitation", {
+  id: __t.u64(),
+  companyId: __t.u64(),
+  email: __t.string(),
+  get role() {
+    return UserRole;
+  },
+  invitedBy: __t.identity(),
+  createdAt: __t.timestamp(),
+});
+export type PersonalInvitation = __Infer<typeof PersonalInvitation>;
+
+export const PinnedMessage = __t.object("PinnedMessage", {
+  id: __t.u64(),
+  get kind() {
+    return ChatKind;
+  },
+  conversationId: __t.u64(),
+  messageId: __t.u64(),
+  pinnedBy: __t.identity(),
+  pinnedAt: __t.timestamp(),
+});
+export type PinnedMessage = __Infer<typeof PinnedMessage>;
+
+export const PlatformAuditEvent = __t.object("PlatformAuditEvent", {
+  id: __t.u64(),
+  actor: __t.identity(),
+  get action() {
+    return AuditAction;
+  },
+  targetIdentity: __t.option(__t.identity()),
+  targetCompanyId: __t.option(__t.u64()),
+  oldValue: __t.string(),
+  newValue: __t.string(),
+  createdAt: __t.timestamp(),
+});
+export type PlatformAuditEvent = __Infer<typeof PlatformAuditEvent>;
+
+export const PlatformConfig = __t.object("PlatformConfig", {
+  id: __t.u32(),
+  companyDeletionGraceDays: __t.u32(),
+  ownerIdentity: __t.identity(),
+});
+export type PlatformConfig = __Infer<typeof PlatformConfig>;
+
+export const PlatformModerator = __t.object("PlatformModerator", {
+  identity: __t.identity(),
+  addedBy: __t.identity(),
+  addedAt: __t.timestamp(),
+});
+export type PlatformModerator = __Infer<typeof PlatformModerator>;
+
 export const Project = __t.object("Project", {
   id: __t.u64(),
   ownerCompanyId: __t.u64(),
@@ -135,6 +589,18 @@ export const ProjectChat = __t.object("ProjectChat", {
   sender: __t.identity(),
   text: __t.string(),
   createdAt: __t.timestamp(),
+  hidden: __t.bool(),
+  editedAt: __t.option(__t.timestamp()),
+  deleted: __t.bool(),
+  parentId: __t.option(__t.u64()),
+  quotedId: __t.option(__t.u64()),
+  replyCount: __t.u32(),
+  mentions: __t.option(__t.array(__t.identity())),
+  mentionedCompanyIds: __t.option(__t.array(__t.u64())),
+  attachmentIds: __t.option(__t.array(__t.u64())),
+  get messageKind() {
+    return MessageKind;
+  },
 });
 export type ProjectChat = __Infer<typeof ProjectChat>;
 
@@ -159,6 +625,124 @@ export const ProjectMemberStatus = __t.enum("ProjectMemberStatus", {
 });
 export type ProjectMemberStatus = __Infer<typeof ProjectMemberStatus>;
 
+export const ProjectParticipant = __t.object("ProjectParticipant", {
+  id: __t.u64(),
+  projectId: __t.u64(),
+  companyId: __t.u64(),
+  identity: __t.identity(),
+  get role() {
+    return ProjectRole;
+  },
+  assignedBy: __t.identity(),
+  assignedAt: __t.timestamp(),
+});
+export type ProjectParticipant = __Infer<typeof ProjectParticipant>;
+
+// The tagged union or sum type for the algebraic type `ProjectRole`.
+export const ProjectRole = __t.enum("ProjectRole", {
+  Lead: __t.unit(),
+  Contributor: __t.unit(),
+  Observer: __t.unit(),
+});
+export type ProjectRole = __Infer<typeof ProjectRole>;
+
+export const RateLimitConfig = __t.object("RateLimitConfig", {
+  id: __t.u32(),
+  chatMessagesPerMinuteUser: __t.u32(),
+  chatMessagesPerMinuteCompany: __t.u32(),
+  connectionRequestsPerHourUser: __t.u32(),
+  connectionRequestsPerDayCompany: __t.u32(),
+});
+export type RateLimitConfig = __Infer<typeof RateLimitConfig>;
+
+export const RateLimitEvent = __t.object("RateLimitEvent", {
+  id: __t.u64(),
+  get action() {
+    return RateLimitedAction;
+  },
+  identity: __t.identity(),
+  companyId: __t.u64(),
+  createdAt: __t.timestamp(),
+});
+export type RateLimitEvent = __Infer<typeof RateLimitEvent>;
+
+// The tagged union or sum type for the algebraic type `RateLimitedAction`.
+export const RateLimitedAction = __t.enum("RateLimitedAction", {
+  ConnectionRequest: __t.unit(),
+  ConnectionChat: __t.unit(),
+  ProjectChat: __t.unit(),
+  ChannelChat: __t.unit(),
+});
+export type RateLimitedAction = __Infer<typeof RateLimitedAction>;
+
+export const ReadCursor = __t.object("ReadCursor", {
+  id: __t.u64(),
+  identity: __t.identity(),
+  get kind() {
+    return ChatKind;
+  },
+  conversationId: __t.u64(),
+  lastReadMessageId: __t.u64(),
+  lastReadAt: __t.option(__t.timestamp()),
+  readCount: __t.u64(),
+  skippedCount: __t.u64(),
+});
+export type ReadCursor = __Infer<typeof ReadCursor>;
+
+export const ReadReceiptSharing = __t.object("ReadReceiptSharing", {
+  identity: __t.identity(),
+  enabledAt: __t.timestamp(),
+});
+export type ReadReceiptSharing = __Infer<typeof ReadReceiptSharing>;
+
+// The tagged union or sum type for the algebraic type `ReportStatus`.
+export const ReportStatus = __t.enum("ReportStatus", {
+  Open: __t.unit(),
+  Dismissed: __t.unit(),
+  ActionTaken: __t.unit(),
+});
+export type ReportStatus = __Infer<typeof ReportStatus>;
+
+// The tagged union or sum type for the algebraic type `ReportTarget`.
+export const ReportTarget = __t.enum("ReportTarget", {
+  ConnectionMessage: __t.u64(),
+  ProjectMessage: __t.u64(),
+  Company: __t.u64(),
+  User: __t.identity(),
+  ChannelMessage: __t.u64(),
+});
+export type ReportTarget = __Infer<typeof ReportTarget>;
+
+export const SchemaMigration = __t.object("SchemaMigration", {
+  name: __t.string(),
+  appliedAt: __t.timestamp(),
+});
+export type SchemaMigration = __Infer<typeof SchemaMigration>;
+
+// The tagged union or sum type for the algebraic type `SystemEvent`.
+export const SystemEvent = __t.enum("SystemEvent", {
+  ConnectionAccepted: __t.u64(),
+  ProjectInviteSent: __t.u64(),
+  ProjectInviteDeclined: __t.u64(),
+  ProjectJoined: __t.u64(),
+  ProjectLeft: __t.u64(),
+  ProjectKicked: __t.u64(),
+  ProjectRenamed: __t.string(),
+  ProjectDescriptionChanged: __t.unit(),
+});
+export type SystemEvent = __Infer<typeof SystemEvent>;
+
+export const UnreadCount = __t.object("UnreadCount", {
+  get kind() {
+    return ChatKind;
+  },
+  conversationId: __t.u64(),
+  lastReadMessageId: __t.u64(),
+  lastReadAt: __t.option(__t.timestamp()),
+  unreadCount: __t.u64(),
+});
+export type UnreadCount = __Infer<typeof UnreadCount>;
+
 export const UsedInviteCode = __t.object("UsedInviteCode", {
   id: __t.u64(),
   identity: __t.identity(),
@@ -177,6 +761,27 @@ export const UserAccount = __t.object("UserAccount", {
 });
 export type UserAccount = __Infer<typeof UserAccount>;
 
+export const UserBlock = __t.object("UserBlock", {
+  id: __t.u64(),
+  blocker: __t.identity(),
+  blocked: __t.identity(),
+  createdAt: __t.timestamp(),
+});
+export type UserBlock = __Infer<typeof UserBlock>;
+
+export const UserContact = __t.object("UserContact", {
+  identity: __t.identity(),
+  email: __t.string(),
+  emailVerified: __t.bool(),
+});
+export type UserContact = __Infer<typeof UserContact>;
+
+export const UserMentionHandle = __t.object("UserMentionHandle", {
+  identity: __t.identity(),
+  handle: __t.string(),
+});
+export type UserMentionHandle = __Infer<typeof UserMentionHandle>;
+
 // The tagged union or sum type for the algebraic type `UserRole`.
 export const UserRole = __t.enum("UserRole", {
   Owner: __t.unit(),
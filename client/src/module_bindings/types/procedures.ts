@@ -6,5 +6,14 @@
 import { type Infer as __Infer } from "spacetimedb";
 
 // Import all procedure arg schemas
+import * as DownloadAttachmentProcedure from "../download_attachment_procedure";
+import * as ExportMyDataProcedure from "../export_my_data_procedure";
+import * as SearchChatProcedure from "../search_chat_procedure";
 
+export type DownloadAttachmentArgs = __Infer<typeof DownloadAttachmentProcedure.params>;
+export type DownloadAttachmentResult = __Infer<typeof DownloadAttachmentProcedure.returnType>;
+export type ExportMyDataArgs = __Infer<typeof ExportMyDataProcedure.params>;
+export type ExportMyDataResult = __Infer<typeof ExportMyDataProcedure.returnType>;
+export type SearchChatArgs = __Infer<typeof SearchChatProcedure.params>;
+export type SearchChatResult = __Infer<typeof SearchChatProcedure.returnType>;
 
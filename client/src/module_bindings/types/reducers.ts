@@ -7,72 +7,170 @@ import { type Infer as __Infer } from "spacetimedb";
 
 // Import all reducer arg schemas
 import AcceptConnectionReducer from "../accept_connection_reducer";
+import AcceptPersonalInvitationReducer from "../accept_personal_invitation_reducer";
 import AcceptProjectInviteReducer from "../accept_project_invite_reducer";
+import AddChannelMemberReducer from "../add_channel_member_reducer";
 import AddColleagueByIdentityReducer from "../add_colleague_by_identity_reducer";
+import AddPlatformModeratorReducer from "../add_platform_moderator_reducer";
+import AssignCompanyRoleReducer from "../assign_company_role_reducer";
+import AssignProjectParticipantReducer from "../assign_project_participant_reducer";
+import BeginAttachmentUploadReducer from "../begin_attachment_upload_reducer";
 import BlockCompanyReducer from "../block_company_reducer";
+import BlockUserReducer from "../block_user_reducer";
 import CancelRequestReducer from "../cancel_request_reducer";
 import ClearNotificationsReducer from "../clear_notifications_reducer";
+import CompleteAttachmentUploadReducer from "../complete_attachment_upload_reducer";
 import CreateAccountReducer from "../create_account_reducer";
+import CreateChannelReducer from "../create_channel_reducer";
 import CreateCompanyReducer from "../create_company_reducer";
+import CreateCompanyRoleReducer from "../create_company_role_reducer";
 import CreateProjectReducer from "../create_project_reducer";
 import DeclineConnectionReducer from "../decline_connection_reducer";
 import DeclineProjectInviteReducer from "../decline_project_invite_reducer";
+import DeleteAccountReducer from "../delete_account_reducer";
+import DeleteAttachmentReducer from "../delete_attachment_reducer";
+import DeleteChannelReducer from "../delete_channel_reducer";
+import DeleteChatMessageReducer from "../delete_chat_message_reducer";
 import DeleteCompanyReducer from "../delete_company_reducer";
+import DeleteCompanyRoleReducer from "../delete_company_role_reducer";
 import DeleteInviteCodeReducer from "../delete_invite_code_reducer";
 import DeleteProjectReducer from "../delete_project_reducer";
 import DisconnectCompanyReducer from "../disconnect_company_reducer";
+import EditChatMessageReducer from "../edit_chat_message_reducer";
 import GenerateInviteCodeReducer from "../generate_invite_code_reducer";
+import InviteByEmailReducer from "../invite_by_email_reducer";
 import InviteToProjectReducer from "../invite_to_project_reducer";
 import JoinCompanyReducer from "../join_company_reducer";
 import KickFromProjectReducer from "../kick_from_project_reducer";
 import LeaveCompanyReducer from "../leave_company_reducer";
 import LeaveProjectReducer from "../leave_project_reducer";
 import MarkAllNotificationsReadReducer from "../mark_all_notifications_read_reducer";
+import MarkConversationReadReducer from "../mark_conversation_read_reducer";
 import MarkNotificationReadReducer from "../mark_notification_read_reducer";
+import MuteConversationReducer from "../mute_conversation_reducer";
+import OpenDirectConversationReducer from "../open_direct_conversation_reducer";
+import PinChatMessageReducer from "../pin_chat_message_reducer";
+import PurgeCompanyReducer from "../purge_company_reducer";
+import PurgeExpiredInviteCodeReducer from "../purge_expired_invite_code_reducer";
+import RebuildChatSearchIndexReducer from "../rebuild_chat_search_index_reducer";
+import RemoveChannelMemberReducer from "../remove_channel_member_reducer";
 import RemoveColleagueReducer from "../remove_colleague_reducer";
+import RemovePlatformModeratorReducer from "../remove_platform_moderator_reducer";
+import RemoveProjectParticipantReducer from "../remove_project_participant_reducer";
+import ReportReducer from "../report_reducer";
 import RequestConnectionReducer from "../request_connection_reducer";
+import RestoreCompanyReducer from "../restore_company_reducer";
+import ReviewReportReducer from "../review_report_reducer";
+import RevokePersonalInvitationReducer from "../revoke_personal_invitation_reducer";
+import SendChannelChatReducer from "../send_channel_chat_reducer";
 import SendConnectionChatReducer from "../send_connection_chat_reducer";
 import SendProjectChatReducer from "../send_project_chat_reducer";
+import SetChatRetentionReducer from "../set_chat_retention_reducer";
+import SetCompanyDeletionGraceDaysReducer from "../set_company_deletion_grace_days_reducer";
+import SetMessageHiddenReducer from "../set_message_hidden_reducer";
+import SetNotificationPreferenceReducer from "../set_notification_preference_reducer";
+import SetPlatformOwnerReducer from "../set_platform_owner_reducer";
+import SetRateLimitsReducer from "../set_rate_limits_reducer";
+import SetReadReceiptsReducer from "../set_read_receipts_reducer";
+import SetStorageQuotaReducer from "../set_storage_quota_reducer";
+import SuspendCompanyReducer from "../suspend_company_reducer";
+import SweepChatRetentionReducer from "../sweep_chat_retention_reducer";
 import SwitchActiveCompanyReducer from "../switch_active_company_reducer";
 import TransferOwnershipReducer from "../transfer_ownership_reducer";
 import UnblockCompanyReducer from "../unblock_company_reducer";
+import UnblockUserReducer from "../unblock_user_reducer";
+import UnmuteConversationReducer from "../unmute_conversation_reducer";
+import UnpinChatMessageReducer from "../unpin_chat_message_reducer";
+import UnsuspendCompanyReducer from "../unsuspend_company_reducer";
 import UpdateCapabilitiesReducer from "../update_capabilities_reducer";
 import UpdateCompanyProfileReducer from "../update_company_profile_reducer";
+import UpdateCompanyRoleReducer from "../update_company_role_reducer";
 import UpdateProfileReducer from "../update_profile_reducer";
+import UpdateProjectReducer from "../update_project_reducer";
 import UpdateUserRoleReducer from "../update_user_role_reducer";
+import UploadAttachmentChunkReducer from "../upload_attachment_chunk_reducer";
 
 export type AcceptConnectionParams = __Infer<typeof AcceptConnectionReducer>;
+export type AcceptPersonalInvitationParams = __Infer<typeof AcceptPersonalInvitationReducer>;
 export type AcceptProjectInviteParams = __Infer<typeof AcceptProjectInviteReducer>;
+export type AddChannelMemberParams = __Infer<typeof AddChannelMemberReducer>;
 export type AddColleagueByIdentityParams = __Infer<typeof AddColleagueByIdentityReducer>;
+export type AddPlatformModeratorParams = __Infer<typeof AddPlatformModeratorReducer>;
+export type AssignCompanyRoleParams = __Infer<typeof AssignCompanyRoleReducer>;
+export type AssignProjectParticipantParams = __Infer<typeof AssignProjectParticipantReducer>;
+export type BeginAttachmentUploadParams = __Infer<typeof BeginAttachmentUploadReducer>;
 export type BlockCompanyParams = __Infer<typeof BlockCompanyReducer>;
+export type BlockUserParams = __Infer<typeof BlockUserReducer>;
 export type CancelRequestParams = __Infer<typeof CancelRequestReducer>;
 export type ClearNotificationsParams = __Infer<typeof ClearNotificationsReducer>;
+export type CompleteAttachmentUploadParams = __Infer<typeof CompleteAttachmentUploadReducer>;
 export type CreateAccountParams = __Infer<typeof CreateAccountReducer>;
+export type CreateChannelParams = __Infer<typeof CreateChannelReducer>;
 export type CreateCompanyParams = __Infer<typeof CreateCompanyReducer>;
+export type CreateCompanyRoleParams = __Infer<typeof CreateCompanyRoleReducer>;
 export type CreateProjectParams = __Infer<typeof CreateProjectReducer>;
 export type DeclineConnectionParams = __Infer<typeof DeclineConnectionReducer>;
 export type DeclineProjectInviteParams = __Infer<typeof DeclineProjectInviteReducer>;
+export type DeleteAccountParams = __Infer<typeof DeleteAccountReducer>;
+export type DeleteAttachmentParams = __Infer<typeof DeleteAttachmentReducer>;
+export type DeleteChannelParams = __Infer<typeof DeleteChannelReducer>;
+export type DeleteChatMessageParams = __Infer<typeof DeleteChatMessageReducer>;
 export type DeleteCompanyParams = __Infer<typeof DeleteCompanyReducer>;
+export type DeleteCompanyRoleParams = __Infer<typeof DeleteCompanyRoleReducer>;
 export type DeleteInviteCodeParams = __Infer<typeof DeleteInviteCodeReducer>;
 export type DeleteProjectParams = __Infer<typeof DeleteProjectReducer>;
 export type DisconnectCompanyParams = __Infer<typeof DisconnectCompanyReducer>;
+export type EditChatMessageParams = __Infer<typeof EditChatMessageReducer>;
 export type GenerateInviteCodeParams = __Infer<typeof GenerateInviteCodeReducer>;
+export type InviteByEmailParams = __Infer<typeof InviteByEmailReducer>;
 export type InviteToProjectParams = __Infer<typeof InviteToProjectReducer>;
 export type JoinCompanyParams = __Infer<typeof JoinCompanyReducer>;
 export type KickFromProjectParams = __Infer<typeof KickFromProjectReducer>;
 export type LeaveCompanyParams = __Infer<typeof LeaveCompanyReducer>;
 export type LeaveProjectParams = __Infer<typeof LeaveProjectReducer>;
 export type MarkAllNotificationsReadParams = __Infer<typeof MarkAllNotificationsReadReducer>;
+export type MarkConversationReadParams = __Infer<typeof MarkConversationReadReducer>;
 export type MarkNotificationReadParams = __Infer<typeof MarkNotificationReadReducer>;
+export type MuteConversationParams = __Infer<typeof MuteConversationReducer>;
+export type OpenDirectConversationParams = __Infer<typeof OpenDirectConversationReducer>;
+export type PinChatMessageParams = __Infer<typeof PinChatMessageReducer>;
+export type PurgeCompanyParams = __Infer<typeof PurgeCompanyReducer>;
+export type PurgeExpiredInviteCodeParams = __Infer<typeof PurgeExpiredInviteCodeReducer>;
+export type RebuildChatSearchIndexParams = __Infer<typeof RebuildChatSearchIndexReducer>;
+export type RemoveChannelMemberParams = __Infer<typeof RemoveChannelMemberReducer>;
 export type RemoveColleagueParams = __Infer<typeof RemoveColleagueReducer>;
+export type RemovePlatformModeratorParams = __Infer<typeof RemovePlatformModeratorReducer>;
+export type RemoveProjectParticipantParams = __Infer<typeof RemoveProjectParticipantReducer>;
+export type ReportParams = __Infer<typeof ReportReducer>;
 export type RequestConnectionParams = __Infer<typeof RequestConnectionReducer>;
+export type RestoreCompanyParams = __Infer<typeof RestoreCompanyReducer>;
+export type ReviewReportParams = __Infer<typeof ReviewReportReducer>;
+export type RevokePersonalInvitationParams = __Infer<typeof RevokePersonalInvitationReducer>;
+export type SendChannelChatParams = __Infer<typeof SendChannelChatReducer>;
 export type SendConnectionChatParams = __Infer<typeof SendConnectionChatReducer>;
 export type SendProjectChatParams = __Infer<typeof SendProjectChatReducer>;
+export type SetChatRetentionParams = __Infer<typeof SetChatRetentionReducer>;
+export type SetCompanyDeletionGraceDaysParams = __Infer<typeof SetCompanyDeletionGraceDaysReducer>;
+export type SetMessageHiddenParams = __Infer<typeof SetMessageHiddenReducer>;
+export type SetNotificationPreferenceParams = __Infer<typeof SetNotificationPreferenceReducer>;
+export type SetPlatformOwnerParams = __Infer<typeof SetPlatformOwnerReducer>;
+export type SetRateLimitsParams = __Infer<typeof SetRateLimitsReducer>;
+export type SetReadReceiptsParams = __Infer<typeof SetReadReceiptsReducer>;
+export type SetStorageQuotaParams = __Infer<typeof SetStorageQuotaReducer>;
+export type SuspendCompanyParams = __Infer<typeof SuspendCompanyReducer>;
+export type SweepChatRetentionParams = __Infer<typeof SweepChatRetentionReducer>;
 export type SwitchActiveCompanyParams = __Infer<typeof SwitchActiveCompanyReducer>;
 export type TransferOwnershipParams = __Infer<typeof TransferOwnershipReducer>;
 export type UnblockCompanyParams = __Infer<typeof UnblockCompanyReducer>;
+export type UnblockUserParams = __Infer<typeof UnblockUserReducer>;
+export type UnmuteConversationParams = __Infer<typeof UnmuteConversationReducer>;
+export type UnpinChatMessageParams = __Infer<typeof UnpinChatMessageReducer>;
+export type UnsuspendCompanyParams = __Infer<typeof UnsuspendCompanyReducer>;
 export type UpdateCapabilitiesParams = __Infer<typeof UpdateCapabilitiesReducer>;
 export type UpdateCompanyProfileParams = __Infer<typeof UpdateCompanyProfileReducer>;
+export type UpdateCompanyRoleParams = __Infer<typeof UpdateCompanyRoleReducer>;
 export type UpdateProfileParams = __Infer<typeof UpdateProfileReducer>;
+export type UpdateProjectParams = __Infer<typeof UpdateProjectReducer>;
 export type UpdateUserRoleParams = __Infer<typeof UpdateUserRoleReducer>;
+export type UploadAttachmentChunkParams = __Infer<typeof UploadAttachmentChunkReducer>;
 
@@ -11,7 +11,7 @@ import {
 } from "spacetimedb";
 
 export default __t.row({
-  companyId: __t.u64().primaryKey().name("company_id"),
+  companyId: __t.u64().name("company_id"),
   canInstall: __t.bool().name("can_install"),
   hasCnc: __t.bool().name("has_cnc"),
   hasLargeFormat: __t.bool().name("has_large_format"),
@@ -11,7 +11,7 @@ import {
 } from "spacetimedb";
 
 export default __t.row({
-  id: __t.u64().primaryKey(),
+  id: __t.u64(),
   ownerIdentity: __t.identity().name("owner_identity"),
   name: __t.string(),
   slug: __t.string(),
@@ -19,4 +19,5 @@ export default __t.row({
   bio: __t.string(),
   isPublic: __t.bool().name("is_public"),
   kvkNumber: __t.string().name("kvk_number"),
+  purgeAt: __t.option(__t.timestamp()).name("purge_at"),
 });
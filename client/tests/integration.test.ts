@@ -9,6 +9,7 @@
  *   npx vitest run
  */
 import { describe, it, expect, beforeAll, afterAll } from 'vitest'
+import { Timestamp } from 'spacetimedb'
 import { createClient, waitFor, expectError, sleep, type TestClient } from './helpers'
 
 // ─── Shared state across all tests ──────────────────────────────────────────
@@ -20,6 +21,7 @@ let clientD: TestClient // extra client for permission checks (no account)
 // Company IDs (populated during company creation tests)
 let companyAId: bigint
 let companyBId: bigint
+let companyDId: bigint // clientD's company (created in the custom role tests)
 
 // ─── Setup & Teardown ───────────────────────────────────────────────────────
 beforeAll(async () => {
@@ -45,8 +47,12 @@ function getAccounts(client: TestClient) {
   return [...client.conn.db.user_account.iter()]
 }
 
+function getContacts(client: TestClient) {
+  return [...client.conn.db.visible_user_contacts.iter()]
+}
+
 function getCompanies(client: TestClient) {
-  return [...client.conn.db.company.iter()]
+  return [...client.conn.db.visible_companies.iter()]
 }
 
 function getConnections(client: TestClient) {
@@ -54,19 +60,19 @@ function getConnections(client: TestClient) {
 }
 
 function getChats(client: TestClient) {
-  return [...client.conn.db.connection_chat.iter()]
+  return [...client.conn.db.my_connection_chat.iter()]
 }
 
 function getInviteCodes(client: TestClient) {
-  return [...client.conn.db.invite_code.iter()]
+  return [...client.conn.db.my_invite_codes.iter()]
 }
 
 function getCapabilities(client: TestClient) {
-  return [...client.conn.db.capability.iter()]
+  return [...client.conn.db.visible_capabilities.iter()]
 }
 
 function getNotifications(client: TestClient) {
-  return [...client.conn.db.notification.iter()]
+  return [...client.conn.db.my_notifications.iter()]
 }
 
 function getProjects(client: TestClient) {
@@ -78,13 +84,37 @@ function getProjectMembers(client: TestClient) {
 }
 
 function getProjectChats(client: TestClient) {
-  return [...client.conn.db.project_chat.iter()]
+  return [...client.conn.db.my_project_chat.iter()]
 }
 
 function getMembers(client: TestClient) {
   return [...client.conn.db.company_member.iter()]
 }
 
+function getCompanyRoles(client: TestClient) {
+  return [...client.conn.db.my_company_roles.iter()]
+}
+
+function getChannels(client: TestClient) {
+  return [...client.conn.db.my_channels.iter()]
+}
+
+function getUserBlocks(client: TestClient) {
+  return [...client.conn.db.my_user_blocks.iter()]
+}
+
+function connectionBetween(client: TestClient, a: bigint, b: bigint) {
+  return getConnections(client).find(
+    c => (c.companyA === a && c.companyB === b) || (c.companyA === b && c.companyB === a)
+  )
+}
+
+function myEmail(client: TestClient) {
+  return getContacts(client).find(
+    c => c.identity.toHexString() === client.identity.toHexString()
+  )?.email
+}
+
 function myAccount(client: TestClient) {
   return getAccounts(client).find(
     a => a.identity.toHexString() === client.identity.toHexString()
@@ -119,7 +149,8 @@ describe('1. Account Creation', () => {
     const acct = myAccount(clientA)!
     expect(acct.fullName).toBe('Alice van Dijk')
     expect(acct.nickname).toBe('Alice')
-    expect(acct.email).toBe('alice@alpha.test')
+    await waitFor(() => myEmail(clientA) !== undefined)
+    expect(myEmail(clientA)).toBe('alice@alpha.test')
     expect(acct.activeCompanyId).toBeUndefined()
   })
 
@@ -297,8 +328,10 @@ describe('2. Company Creation', () => {
     await clientA.conn.reducers.switchActiveCompany({ companyId: secondCo.id })
     await waitFor(() => myAccount(clientA)?.activeCompanyId === secondCo.id)
     await clientA.conn.reducers.deleteCompany({})
-    await waitFor(() => getCompanies(clientA).find(c => c.slug === 'second-co') == null)
+    // Deletion is only scheduled: members still see the company until it is purged
+    await waitFor(() => getCompanies(clientA).find(c => c.slug === 'second-co')?.purgeAt != null)
     // Switch back
+    await clientA.conn.reducers.switchActiveCompany({ companyId: companyAId })
     await waitFor(() => myAccount(clientA)?.activeCompanyId === companyAId)
   })
 })
@@ -311,7 +344,7 @@ describe('3. Invite Code System', () => {
 
   it('clientA generates an invite code', async () => {
     const codesBefore = getInviteCodes(clientA).length
-    await clientA.conn.reducers.generateInviteCode({ maxUses: 5 })
+    await clientA.conn.reducers.generateInviteCode({ maxUses: 5, expiresAt: undefined, role: undefined })
     await waitFor(() => getInviteCodes(clientA).length > codesBefore)
     const codes = getInviteCodes(clientA).filter(
       c => c.companyId === companyAId
@@ -323,7 +356,7 @@ describe('3. Invite Code System', () => {
 
   it('rejects no-company user generating invite code', async () => {
     await expectError(
-      () => clientC.conn.reducers.generateInviteCode({ maxUses: 1 }),
+      () => clientC.conn.reducers.generateInviteCode({ maxUses: 1, expiresAt: undefined, role: undefined }),
       'Not permitted'
     )
   })
@@ -464,7 +497,7 @@ describe('5. Profile & Company Settings', () => {
       email: 'alice-new@alpha.test',
     })
     await waitFor(() => myAccount(clientA)?.nickname === 'AliceUpdated')
-    expect(myAccount(clientA)!.email).toBe('alice-new@alpha.test')
+    await waitFor(() => myEmail(clientA) === 'alice-new@alpha.test')
   })
 
   it('rejects empty nickname on update', async () => {
@@ -724,6 +757,9 @@ describe('7. Chat System', () => {
     await clientB.conn.reducers.sendConnectionChat({
       connectionId,
       text: 'What dimensions?',
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
     })
     await waitFor(() => getChats(clientB).length > chatsBefore)
     const chat = getChats(clientB).find(c => c.connectionId === connectionId)!
@@ -737,6 +773,9 @@ describe('7. Chat System', () => {
     await clientA.conn.reducers.sendConnectionChat({
       connectionId,
       text: '3m x 2m vinyl',
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
     })
     await waitFor(
       () =>
@@ -762,6 +801,9 @@ describe('7. Chat System', () => {
         clientA.conn.reducers.sendConnectionChat({
           connectionId,
           text: '',
+          parentId: undefined,
+          quotedId: undefined,
+          attachmentIds: [],
         }),
       'cannot be empty'
     )
@@ -773,6 +815,9 @@ describe('7. Chat System', () => {
         clientA.conn.reducers.sendConnectionChat({
           connectionId,
           text: 'X'.repeat(501),
+          parentId: undefined,
+          quotedId: undefined,
+          attachmentIds: [],
         }),
       'too long'
     )
@@ -799,6 +844,9 @@ describe('7. Chat System', () => {
     await clientA.conn.reducers.sendConnectionChat({
       connectionId,
       text: 'Great, sending specs',
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
     })
     await waitFor(
       () =>
@@ -975,6 +1023,9 @@ describe('8. Ghosting & Block', () => {
         clientB.conn.reducers.sendConnectionChat({
           connectionId: conn.id,
           text: 'Should fail',
+          parentId: undefined,
+          quotedId: undefined,
+          attachmentIds: [],
         }),
       'blocked connection'
     )
@@ -1152,6 +1203,9 @@ describe('9. Permission Checks', () => {
         clientC.conn.reducers.sendConnectionChat({
           connectionId: conn.id,
           text: 'Intruder',
+          parentId: undefined,
+          quotedId: undefined,
+          attachmentIds: [],
         }),
       'Not permitted'
     )
@@ -1242,6 +1296,9 @@ describe('10. Notifications', () => {
     await clientA.conn.reducers.sendConnectionChat({
       connectionId: conn.id,
       text: 'Notification test msg',
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
     })
     await waitFor(() => getNotifications(clientB).some(n => !n.isRead))
     const bNotif = getNotifications(clientB).find(n => !n.isRead)!
@@ -1371,6 +1428,9 @@ describe('11. Project Lifecycle', () => {
     await clientA.conn.reducers.sendProjectChat({
       projectId,
       text: 'The permits are approved',
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
     })
     await waitFor(() =>
       getProjectChats(clientA).filter(c => c.projectId === projectId).length > chatsBefore
@@ -1393,6 +1453,9 @@ describe('11. Project Lifecycle', () => {
     await clientB.conn.reducers.sendProjectChat({
       projectId,
       text: 'Great, we will start production',
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
     })
     await waitFor(() =>
       getProjectChats(clientB).some(
@@ -1411,14 +1474,14 @@ describe('11. Project Lifecycle', () => {
 
   it('rejects empty project chat', async () => {
     await expectError(
-      () => clientA.conn.reducers.sendProjectChat({ projectId, text: '' }),
+      () => clientA.conn.reducers.sendProjectChat({ projectId, text: '', parentId: undefined, quotedId: undefined, attachmentIds: [] }),
       'cannot be empty'
     )
   })
 
   it('rejects project chat too long', async () => {
     await expectError(
-      () => clientA.conn.reducers.sendProjectChat({ projectId, text: 'X'.repeat(501) }),
+      () => clientA.conn.reducers.sendProjectChat({ projectId, text: 'X'.repeat(501), parentId: undefined, quotedId: undefined, attachmentIds: [] }),
       'too long'
     )
   })
@@ -1610,6 +1673,9 @@ describe('12. Project Security', () => {
       () => clientB.conn.reducers.sendProjectChat({
         projectId,
         text: 'Intruder message',
+        parentId: undefined,
+        quotedId: undefined,
+        attachmentIds: [],
       }),
       'not a member'
     )
@@ -1683,3 +1749,531 @@ describe('12. Project Security', () => {
     )
   })
 })
+
+// ═════════════════════════════════════════════════════════════════════════════
+// 13. INVITE EXPIRY & ROLE PRESETS
+// ═════════════════════════════════════════════════════════════════════════════
+describe('13. Invite Expiry & Role Presets', () => {
+  let presetCode: string
+
+  it('rejects an expiry in the past', async () => {
+    await expectError(
+      () =>
+        clientA.conn.reducers.generateInviteCode({
+          maxUses: 1,
+          expiresAt: Timestamp.fromDate(new Date(Date.now() - 60_000)),
+          role: undefined,
+        }),
+      'Expiry must be in the future'
+    )
+  })
+
+  it('rejects presetting a role at or above your own level', async () => {
+    await expectError(
+      () =>
+        clientA.conn.reducers.generateInviteCode({
+          maxUses: 1,
+          expiresAt: undefined,
+          role: { tag: 'Owner' },
+        }),
+      'Cannot preset a role at or above your own level'
+    )
+  })
+
+  it('clientA generates a Member invite code that expires in an hour', async () => {
+    const codesBefore = new Set(getInviteCodes(clientA).map(c => c.code))
+    await clientA.conn.reducers.generateInviteCode({
+      maxUses: 1,
+      expiresAt: Timestamp.fromDate(new Date(Date.now() + 60 * 60 * 1000)),
+      role: { tag: 'Member' },
+    })
+    await waitFor(() => getInviteCodes(clientA).some(c => !codesBefore.has(c.code)))
+    const code = getInviteCodes(clientA).find(c => !codesBefore.has(c.code))!
+    expect(code.role.tag).toBe('Member')
+    expect(code.expiresAt).toBeDefined()
+    presetCode = code.code
+  })
+
+  it('clientC joins with the preset role instead of Pending', async () => {
+    await clientC.conn.reducers.joinCompany({ code: presetCode })
+    await waitFor(() => myMembership(clientC, companyAId) !== undefined)
+    expect(myMembership(clientC, companyAId)!.role.tag).toBe('Member')
+    expect(myAccount(clientC)!.activeCompanyId).toBe(companyAId)
+  })
+
+  it('expired invite code is purged', async () => {
+    const codesBefore = new Set(getInviteCodes(clientA).map(c => c.code))
+    await clientA.conn.reducers.generateInviteCode({
+      maxUses: 1,
+      expiresAt: Timestamp.fromDate(new Date(Date.now() + 2_000)),
+      role: undefined,
+    })
+    await waitFor(() => getInviteCodes(clientA).some(c => !codesBefore.has(c.code)))
+    const expiring = getInviteCodes(clientA).find(c => !codesBefore.has(c.code))!.code
+
+    await waitFor(
+      () => getInviteCodes(clientA).find(c => c.code === expiring) === undefined,
+      10_000
+    )
+    await expectError(
+      () => clientD.conn.reducers.joinCompany({ code: expiring }),
+      'Invalid invite code'
+    )
+  })
+})
+
+// ═════════════════════════════════════════════════════════════════════════════
+// 14. CUSTOM ROLES
+// ═════════════════════════════════════════════════════════════════════════════
+describe('14. Custom Roles', () => {
+  let salesRoleId: bigint
+  let supportRoleId: bigint
+
+  it('setup: clientD creates Delta Displays', async () => {
+    await clientD.conn.reducers.createCompany({
+      name: 'Delta Displays',
+      slug: 'delta-displays',
+      location: 'Utrecht, NL',
+    })
+    await waitFor(() => getCompanies(clientD).some(c => c.slug === 'delta-displays'))
+    companyDId = getCompanies(clientD).find(c => c.slug === 'delta-displays')!.id
+  })
+
+  it('clientA creates a Sales role with ManageConnections and a Support role without', async () => {
+    await clientA.conn.reducers.createCompanyRole({
+      name: 'Sales',
+      permissions: [{ tag: 'Chat' }, { tag: 'ManageConnections' }],
+    })
+    await clientA.conn.reducers.createCompanyRole({
+      name: 'Support',
+      permissions: [{ tag: 'Chat' }],
+    })
+    const roleNamed = (name: string) =>
+      getCompanyRoles(clientA).find(r => r.companyId === companyAId && r.name === name)
+    await waitFor(() => roleNamed('Sales') !== undefined && roleNamed('Support') !== undefined)
+    expect(roleNamed('Sales')!.tier).toBeUndefined()
+    salesRoleId = roleNamed('Sales')!.id
+    supportRoleId = roleNamed('Support')!.id
+  })
+
+  it('rejects a duplicate role name', async () => {
+    await expectError(
+      () => clientA.conn.reducers.createCompanyRole({ name: 'sales', permissions: [] }),
+      'A role with this name already exists'
+    )
+  })
+
+  it('rejects a Member creating roles', async () => {
+    await expectError(
+      () => clientC.conn.reducers.createCompanyRole({ name: 'Rogue', permissions: [] }),
+      'You do not have permission to manage roles'
+    )
+  })
+
+  it('clientC with the Support role cannot request connections', async () => {
+    await clientA.conn.reducers.assignCompanyRole({
+      targetIdentity: clientC.identity,
+      roleId: supportRoleId,
+    })
+    await waitFor(() =>
+      membershipOf(clientA, clientC.identity.toHexString(), companyAId)?.customRoleId === supportRoleId
+    )
+    await expectError(
+      () =>
+        clientC.conn.reducers.requestConnection({
+          targetCompanyId: companyDId,
+          message: '',
+        }),
+      'You do not have permission to manage connections'
+    )
+  })
+
+  it('clientC with the Sales role can request connections', async () => {
+    await clientA.conn.reducers.assignCompanyRole({
+      targetIdentity: clientC.identity,
+      roleId: salesRoleId,
+    })
+    await waitFor(() =>
+      membershipOf(clientA, clientC.identity.toHexString(), companyAId)?.customRoleId === salesRoleId
+    )
+    await clientC.conn.reducers.requestConnection({
+      targetCompanyId: companyDId,
+      message: 'Hello from Alpha sales',
+    })
+    await waitFor(
+      () => connectionBetween(clientD, companyAId, companyDId)?.status.tag === 'Pending'
+    )
+    // Clean up
+    await clientC.conn.reducers.cancelRequest({ targetCompanyId: companyDId })
+    await waitFor(() => connectionBetween(clientD, companyAId, companyDId) === undefined)
+  })
+
+  it('clearing the custom role falls back to the tier permissions', async () => {
+    await clientA.conn.reducers.assignCompanyRole({
+      targetIdentity: clientC.identity,
+      roleId: undefined,
+    })
+    await waitFor(() =>
+      membershipOf(clientA, clientC.identity.toHexString(), companyAId)?.customRoleId === undefined
+    )
+    await expectError(
+      () =>
+        clientC.conn.reducers.requestConnection({
+          targetCompanyId: companyDId,
+          message: '',
+        }),
+      'You do not have permission to manage connections'
+    )
+  })
+})
+
+// ═════════════════════════════════════════════════════════════════════════════
+// 15. RATE LIMITS
+// ═════════════════════════════════════════════════════════════════════════════
+describe('15. Rate Limits', () => {
+  it('a user hits the per-minute message limit', async () => {
+    // Fresh client so the limit does not affect the shared clients
+    const eve = await createClient()
+    try {
+      await eve.conn.reducers.createAccount({
+        fullName: 'Eva de Vries',
+        nickname: 'Eva',
+        email: 'eva@echo.test',
+      })
+      await waitFor(() => myAccount(eve) !== undefined)
+      await eve.conn.reducers.createCompany({
+        name: 'Echo Graphics',
+        slug: 'echo-graphics',
+        location: 'Leiden, NL',
+      })
+      await waitFor(() => myAccount(eve)?.activeCompanyId != null)
+      await eve.conn.reducers.createChannel({ name: 'rate-test', members: [] })
+      await waitFor(() => getChannels(eve).some(c => c.name === 'rate-test'))
+      const channelId = getChannels(eve).find(c => c.name === 'rate-test')!.id
+
+      // The defaults apply until a moderator stores a config row
+      const limit =
+        [...eve.conn.db.rate_limit_config.iter()][0]?.chatMessagesPerMinuteUser ?? 20
+      for (let i = 1; i <= limit; i++) {
+        await eve.conn.reducers.sendChannelChat({
+          channelId,
+          text: `Message ${i}`,
+          parentId: undefined,
+          quotedId: undefined,
+          attachmentIds: [],
+        })
+      }
+      await expectError(
+        () =>
+          eve.conn.reducers.sendChannelChat({
+            channelId,
+            text: 'One message too many',
+            parentId: undefined,
+            quotedId: undefined,
+            attachmentIds: [],
+          }),
+        'Too many messages'
+      )
+    } finally {
+      eve.disconnect()
+    }
+  }, 30_000)
+})
+
+// ═════════════════════════════════════════════════════════════════════════════
+// 16. USER BLOCKS
+// ═════════════════════════════════════════════════════════════════════════════
+describe('16. User Blocks', () => {
+  const unreadChatNotification = (connectionId: bigint) =>
+    getNotifications(clientB).find(
+      n => n.connectionId === connectionId && n.notificationType.tag === 'ChatMessage' && !n.isRead
+    )
+
+  it('rejects blocking yourself', async () => {
+    await expectError(
+      () => clientB.conn.reducers.blockUser({ identity: clientB.identity }),
+      'Cannot block yourself'
+    )
+  })
+
+  it('clientB blocks clientA', async () => {
+    await clientB.conn.reducers.blockUser({ identity: clientA.identity })
+    await waitFor(() =>
+      getUserBlocks(clientB).some(
+        b => b.blocked.toHexString() === clientA.identity.toHexString()
+      )
+    )
+  })
+
+  it('blocked sender produces no notification and is hidden from chat', async () => {
+    const conn = connectionBetween(clientA, companyAId, companyBId)!
+    await clientB.conn.reducers.markConversationRead({
+      kind: { tag: 'Connection' },
+      conversationId: conn.id,
+      upToMessageId: undefined,
+    })
+    await waitFor(() => unreadChatNotification(conn.id) === undefined)
+
+    await clientA.conn.reducers.sendConnectionChat({
+      connectionId: conn.id,
+      text: 'Are you there?',
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
+    })
+    await waitFor(() => getChats(clientA).some(c => c.text === 'Are you there?'))
+    await sleep(500)
+    expect(unreadChatNotification(conn.id)).toBeUndefined()
+    expect(getChats(clientB).some(c => c.text === 'Are you there?')).toBe(false)
+  })
+
+  it('clientB unblocks clientA and sees the message again', async () => {
+    await clientB.conn.reducers.unblockUser({ identity: clientA.identity })
+    await waitFor(() => getChats(clientB).some(c => c.text === 'Are you there?'))
+    expect(getUserBlocks(clientB)).toHaveLength(0)
+  })
+})
+
+// ═════════════════════════════════════════════════════════════════════════════
+// 17. MESSAGE EDIT & DELETE
+// ═════════════════════════════════════════════════════════════════════════════
+describe('17. Message Edit & Delete', () => {
+  let messageId: bigint
+
+  it('clientA edits their message', async () => {
+    const conn = connectionBetween(clientA, companyAId, companyBId)!
+    await clientA.conn.reducers.sendConnectionChat({
+      connectionId: conn.id,
+      text: 'Install on Monday',
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
+    })
+    await waitFor(() => getChats(clientA).some(c => c.text === 'Install on Monday'))
+    messageId = getChats(clientA).find(c => c.text === 'Install on Monday')!.id
+
+    await clientA.conn.reducers.editChatMessage({
+      kind: { tag: 'Connection' },
+      messageId,
+      text: 'Install on Tuesday',
+    })
+    await waitFor(
+      () => getChats(clientB).find(c => c.id === messageId)?.text === 'Install on Tuesday'
+    )
+    expect(getChats(clientB).find(c => c.id === messageId)!.editedAt).toBeDefined()
+  })
+
+  it('rejects editing someone else message', async () => {
+    await expectError(
+      () =>
+        clientB.conn.reducers.editChatMessage({
+          kind: { tag: 'Connection' },
+          messageId,
+          text: 'Install never',
+        }),
+      'You can only edit your own messages'
+    )
+  })
+
+  it('rejects deleting a message from another company', async () => {
+    await expectError(
+      () =>
+        clientB.conn.reducers.deleteChatMessage({
+          kind: { tag: 'Connection' },
+          messageId,
+        }),
+      'You can only delete your own messages'
+    )
+  })
+
+  it('clientA deletes their message', async () => {
+    await clientA.conn.reducers.deleteChatMessage({
+      kind: { tag: 'Connection' },
+      messageId,
+    })
+    await waitFor(() => getChats(clientB).find(c => c.id === messageId)?.deleted === true)
+    expect(getChats(clientB).find(c => c.id === messageId)!.text).toBe('')
+  })
+
+  it('rejects editing a deleted message', async () => {
+    await expectError(
+      () =>
+        clientA.conn.reducers.editChatMessage({
+          kind: { tag: 'Connection' },
+          messageId,
+          text: 'Install on Wednesday',
+        }),
+      'Message has been deleted'
+    )
+  })
+})
+
+// ═════════════════════════════════════════════════════════════════════════════
+// 18. CHAT DIGESTS
+// ═════════════════════════════════════════════════════════════════════════════
+describe('18. Chat Digests', () => {
+  let connectionId: bigint
+
+  const digest = () =>
+    getNotifications(clientB).find(
+      n => n.connectionId === connectionId && n.notificationType.tag === 'ChatMessage' && !n.isRead
+    )
+
+  const sendFromA = (text: string) =>
+    clientA.conn.reducers.sendConnectionChat({
+      connectionId,
+      text,
+      parentId: undefined,
+      quotedId: undefined,
+      attachmentIds: [],
+    })
+
+  it('consecutive messages are counted in one unread notification', async () => {
+    connectionId = connectionBetween(clientA, companyAId, companyBId)!.id
+    await clientB.conn.reducers.markConversationRead({
+      kind: { tag: 'Connection' },
+      conversationId: connectionId,
+      upToMessageId: undefined,
+    })
+    await waitFor(() => digest() === undefined)
+
+    await sendFromA('Digest one')
+    await sendFromA('Digest two')
+    await sendFromA('Digest three')
+    await waitFor(() => digest()?.messageCount === 3)
+    expect(digest()!.title).toContain('3 new messages')
+    expect(digest()!.body).toContain('Digest three')
+  })
+
+  it('deleting a counted message lowers the count', async () => {
+    const last = getChats(clientA).find(c => c.text === 'Digest three')!
+    await clientA.conn.reducers.deleteChatMessage({
+      kind: { tag: 'Connection' },
+      messageId: last.id,
+    })
+    await waitFor(() => digest()?.messageCount === 2)
+    expect(digest()!.title).toContain('2 new messages')
+    expect(digest()!.body).not.toContain('Digest three')
+  })
+
+  it('reading the conversation resets the count', async () => {
+    await clientB.conn.reducers.markConversationRead({
+      kind: { tag: 'Connection' },
+      conversationId: connectionId,
+      upToMessageId: undefined,
+    })
+    await waitFor(() => digest() === undefined)
+
+    await sendFromA('Digest four')
+    await waitFor(() => digest() !== undefined)
+    expect(digest()!.messageCount).toBe(1)
+    expect(digest()!.title).toContain('1 new message')
+  })
+})
+
+// ═════════════════════════════════════════════════════════════════════════════
+// 19. COMPANY DELETION & RESTORE
+// ═════════════════════════════════════════════════════════════════════════════
+describe('19. Company Deletion & Restore', () => {
+  it('clientD schedules Delta Displays for deletion', async () => {
+    await waitFor(() => getCompanies(clientA).some(c => c.id === companyDId))
+    await clientD.conn.reducers.deleteCompany({})
+    await waitFor(() => getCompanies(clientD).find(c => c.id === companyDId)?.purgeAt != null)
+  })
+
+  it('company pending deletion is hidden from other companies', async () => {
+    await waitFor(() => getCompanies(clientA).find(c => c.id === companyDId) === undefined)
+  })
+
+  it('company pending deletion is read-only', async () => {
+    await expectError(
+      () =>
+        clientD.conn.reducers.generateInviteCode({
+          maxUses: 1,
+          expiresAt: undefined,
+          role: undefined,
+        }),
+      'Company is scheduled for deletion'
+    )
+  })
+
+  it('clientD restores the company', async () => {
+    await clientD.conn.reducers.restoreCompany({})
+    await waitFor(
+      () => getCompanies(clientD).find(c => c.id === companyDId)?.purgeAt === undefined
+    )
+    await waitFor(() => getCompanies(clientA).some(c => c.id === companyDId))
+  })
+
+  it('rejects restoring a company that is not scheduled for deletion', async () => {
+    await expectError(
+      () => clientD.conn.reducers.restoreCompany({}),
+      'Company is not scheduled for deletion'
+    )
+  })
+})
+
+// ═════════════════════════════════════════════════════════════════════════════
+// 20. ACCOUNT DELETION
+// ═════════════════════════════════════════════════════════════════════════════
+describe('20. Account Deletion', () => {
+  it('rejects an Owner deleting their account', async () => {
+    await expectError(
+      () => clientA.conn.reducers.deleteAccount({}),
+      'Transfer ownership of your companies before deleting your account'
+    )
+  })
+
+  it('deleted account leaves its companies and is anonymised in chat', async () => {
+    const frank = await createClient()
+    try {
+      await frank.conn.reducers.createAccount({
+        fullName: 'Frank de Boer',
+        nickname: 'Frank',
+        email: 'frank@alpha.test',
+      })
+      await waitFor(() => myAccount(frank) !== undefined)
+
+      const codesBefore = new Set(getInviteCodes(clientA).map(c => c.code))
+      await clientA.conn.reducers.generateInviteCode({
+        maxUses: 1,
+        expiresAt: undefined,
+        role: { tag: 'Member' },
+      })
+      await waitFor(() => getInviteCodes(clientA).some(c => !codesBefore.has(c.code)))
+      const code = getInviteCodes(clientA).find(c => !codesBefore.has(c.code))!.code
+      await frank.conn.reducers.joinCompany({ code })
+      await waitFor(() => myAccount(frank)?.activeCompanyId === companyAId)
+
+      const conn = connectionBetween(clientA, companyAId, companyBId)!
+      await frank.conn.reducers.sendConnectionChat({
+        connectionId: conn.id,
+        text: 'Greetings from Frank',
+        parentId: undefined,
+        quotedId: undefined,
+        attachmentIds: [],
+      })
+      await waitFor(() => getChats(clientA).some(c => c.text === 'Greetings from Frank'))
+      const messageId = getChats(clientA).find(c => c.text === 'Greetings from Frank')!.id
+
+      const frankHex = frank.identity.toHexString()
+      await frank.conn.reducers.deleteAccount({})
+      await waitFor(() => membershipOf(clientA, frankHex, companyAId) === undefined)
+      await waitFor(
+        () => getAccounts(clientA).find(a => a.identity.toHexString() === frankHex) === undefined
+      )
+      await waitFor(
+        () => getChats(clientA).find(c => c.id === messageId)?.sender.toHexString() === '0'.repeat(64)
+      )
+
+      // The Owner can still moderate the anonymised message
+      await clientA.conn.reducers.deleteChatMessage({
+        kind: { tag: 'Connection' },
+        messageId,
+      })
+      await waitFor(() => getChats(clientA).find(c => c.id === messageId)?.deleted === true)
+    } finally {
+      frank.disconnect()
+    }
+  })
+})
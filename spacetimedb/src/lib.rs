@@ -1,5 +1,11 @@
+use std::time::Duration;
+
+use serde_json::json;
 use spacetimedb::rand::RngCore;
-use spacetimedb::{Identity, ReducerContext, SpacetimeType, Table, Timestamp};
+use spacetimedb::{
+    Identity, LocalReadOnly, ProcedureContext, ReducerContext, ScheduleAt, SpacetimeType, Table,
+    Timestamp, ViewContext,
+};
 
 // ---------------------------------------------------------------------------
 // Types
@@ -18,6 +24,18 @@ pub enum UserRole {
     Pending,
 }
 
+/// Named permissions that make up a company role (see `CompanyRole`).
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Permission {
+    ManageMembers,
+    ManageRoles,
+    ManageConnections,
+    ManageProjects,
+    Chat,
+    EditProfile,
+    ViewAuditLog,
+}
+
 /// Status of a connection between two companies.
 #[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ConnectionStatus {
@@ -42,6 +60,73 @@ pub enum NotificationType {
     ProjectChat,
     ProjectKicked,
     ProjectLeft,
+    ProjectAssignment,
+    ReportSubmitted,
+    ReportReviewed,
+    CompanySuspension,
+    ChatReply,
+    ProjectChatReply,
+    ChatMention,
+    ProjectChatMention,
+    ChannelMessage,
+    ChannelReply,
+    ChannelMention,
+    ChannelAdded,
+}
+
+/// Who a notification belongs to: one of the recipient's companies, or the
+/// platform itself (moderation), which clients show in every company context.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum NotificationScope {
+    #[default]
+    Company,
+    Platform,
+}
+
+/// Kinds of actions recorded in the `audit_event` table.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    ColleagueRemoved,
+    RoleChanged,
+    OwnershipTransferred,
+    CompanyBlocked,
+    CompanyUnblocked,
+    AccountDeleted,
+    CompanyDeletionScheduled,
+    CompanyRestored,
+    CompanyDeleted,
+    ProjectCreated,
+    ProjectInviteSent,
+    ProjectInviteAccepted,
+    ProjectInviteDeclined,
+    ProjectLeft,
+    ProjectAutoDeleted,
+    ProjectKicked,
+    ProjectDeleted,
+    CompanyRoleCreated,
+    CompanyRoleUpdated,
+    CompanyRoleDeleted,
+    CompanyRoleAssigned,
+    ProjectParticipantAssigned,
+    ProjectParticipantRemoved,
+    CompanySuspended,
+    CompanyUnsuspended,
+    ChatMessageDeleted,
+    ChatRetentionChanged,
+    ChannelCreated,
+    ChannelDeleted,
+    ChannelMemberAdded,
+    ChannelMemberRemoved,
+    ProjectUpdated,
+    MessageHidden,
+    MessageUnhidden,
+    ModeratorAppointed,
+    ModeratorRemoved,
+    RateLimitsChanged,
+    CompanyDeletionGraceChanged,
+    StorageQuotaChanged,
+    ChatSearchIndexRebuilt,
+    PlatformOwnerChanged,
 }
 
 /// Status of a company's membership in a project room.
@@ -53,6 +138,90 @@ pub enum ProjectMemberStatus {
     Kicked,
 }
 
+/// Which chat table a message id refers to.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChatKind {
+    Connection,
+    Project,
+    Channel,
+}
+
+/// Actions throttled by `check_rate_limit`.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RateLimitedAction {
+    ConnectionRequest,
+    ConnectionChat,
+    ProjectChat,
+    ChannelChat,
+}
+
+/// What a moderation report points at.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReportTarget {
+    ConnectionMessage(u64),
+    ProjectMessage(u64),
+    Company(u64),
+    User(Identity),
+    ChannelMessage(u64),
+}
+
+/// Review state of a moderation report.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReportStatus {
+    Open,
+    Dismissed,
+    ActionTaken,
+}
+
+/// A user's role inside a project room (see `ProjectParticipant`). Leads
+/// also assign and remove their company's Contributors and Observers without
+/// ManageProjects.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProjectRole {
+    Lead,
+    Contributor,
+    Observer,
+}
+
+/// Upload state of an `Attachment`.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttachmentStatus {
+    Uploading,
+    Ready,
+}
+
+/// A change to a connection or project recorded in its chat as a system
+/// message (see `post_system_message`). Company payloads are company IDs.
+#[derive(SpacetimeType, Clone, Debug, PartialEq, Eq)]
+pub enum SystemEvent {
+    ConnectionAccepted(u64),
+    ProjectInviteSent(u64),
+    ProjectInviteDeclined(u64),
+    ProjectJoined(u64),
+    ProjectLeft(u64),
+    ProjectKicked(u64),
+    ProjectRenamed(String),
+    ProjectDescriptionChanged,
+}
+
+/// How many notifications of a type a user wants (see
+/// `NotificationPreference`). `MentionsOnly` applies to the chat message
+/// types: only replies and @mentions come through. `Off` is "none".
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NotificationLevel {
+    All,
+    MentionsOnly,
+    Off,
+}
+
+/// Whether a chat message was written by a user or posted by the module to
+/// record a `SystemEvent`.
+#[derive(SpacetimeType, Clone, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+    User,
+    System(SystemEvent),
+}
+
 // ---------------------------------------------------------------------------
 // Tables
 // ---------------------------------------------------------------------------
@@ -68,19 +237,61 @@ pub struct OnlineUser {
 /// A registered user account. Every connected identity that completes sign-up
 /// gets one row here. The `active_company_id` tracks which company the user
 /// is currently operating as (they may belong to multiple via `CompanyMember`).
+/// Contact details live in the private `UserContact` table.
 #[spacetimedb::table(accessor = user_account, public)]
 pub struct UserAccount {
     #[primary_key]
     pub identity: Identity,
     pub full_name: String,
     pub nickname: String,
+    /// Legacy column, always empty: e-mail addresses live in the private
+    /// `UserContact` table, where `migrate_user_contacts` moved existing
+    /// ones. Kept so the table still matches data published before the move.
     pub email: String,
     pub active_company_id: Option<u64>,
     pub created_at: Timestamp,
 }
 
+/// Private contact details for a user account (1:1 with `UserAccount`).
+/// Clients read them through the `visible_user_contacts` view, which only
+/// exposes a row to the user themself and to colleagues holding ManageMembers.
+#[spacetimedb::table(accessor = user_contact)]
+pub struct UserContact {
+    #[primary_key]
+    pub identity: Identity,
+    pub email: String,
+    /// Whether the identity provider vouched for `email` (see
+    /// `verified_email`) when it was last set. Only verified addresses can
+    /// claim personal invitations.
+    pub email_verified: bool,
+}
+
+/// The @mention handle of a user account (see `mention_handle`), kept in step
+/// with its nickname so handles can be looked up by index. Not unique: accounts
+/// from before nicknames were unique may share a handle.
+#[spacetimedb::table(
+    accessor = user_mention_handle,
+    index(accessor = handle_by_handle, btree(columns = [handle]))
+)]
+pub struct UserMentionHandle {
+    #[primary_key]
+    pub identity: Identity,
+    pub handle: String,
+}
+
+/// Data migrations applied to this database, one row per entry of
+/// `SCHEMA_MIGRATIONS` (see `run_schema_migrations`).
+#[spacetimedb::table(accessor = schema_migration)]
+pub struct SchemaMigration {
+    #[primary_key]
+    pub name: String,
+    pub applied_at: Timestamp,
+}
+
 /// Many-to-many mapping between users and companies. A user can belong to
-/// multiple companies, each with an independent role.
+/// multiple companies, each with an independent role. `role` is the tier used
+/// for hierarchy checks; `custom_role_id` optionally points at a custom
+/// `CompanyRole` whose permissions replace those of the tier.
 #[spacetimedb::table(
     accessor = company_member, public,
     index(accessor = member_by_company, btree(columns = [company_id])),
@@ -94,10 +305,34 @@ pub struct CompanyMember {
     pub company_id: u64,
     pub role: UserRole,
     pub joined_at: Timestamp,
+    #[default(None::<u64>)]
+    pub custom_role_id: Option<u64>,
+}
+
+/// A named permission set within a company. Every company is seeded with one
+/// role per `UserRole` tier (`tier` is set); members with ManageRoles can edit
+/// those and define custom roles (`tier` is `None`), e.g. "Sales".
+/// Private: members read their companies' roles through `my_company_roles`.
+#[spacetimedb::table(
+    accessor = company_role,
+    index(accessor = role_by_company, btree(columns = [company_id]))
+)]
+pub struct CompanyRole {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub company_id: u64,
+    pub name: String,
+    pub tier: Option<UserRole>,
+    pub permissions: Vec<Permission>,
 }
 
-/// A sign-shop / company in the directory.
-#[spacetimedb::table(accessor = company, public)]
+/// A sign-shop / company in the directory. Private: clients read it through
+/// the `visible_companies` view, which hides companies pending deletion.
+#[spacetimedb::table(
+    accessor = company,
+    index(accessor = company_by_public, btree(columns = [is_public]))
+)]
 pub struct Company {
     #[primary_key]
     #[auto_inc]
@@ -110,11 +345,30 @@ pub struct Company {
     pub bio: String,
     pub is_public: bool,
     pub kvk_number: String,
+    /// Set while the company is scheduled for deletion (see `delete_company`).
+    #[default(None::<Timestamp>)]
+    pub purge_at: Option<Timestamp>,
+}
+
+/// Scheduled purge of a company after its deletion grace period.
+#[spacetimedb::table(accessor = company_purge, scheduled(purge_company))]
+pub struct CompanyPurge {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: ScheduleAt,
+    #[unique]
+    pub company_id: u64,
+    /// The owner who deleted the company, recorded as the purge's actor.
+    pub requested_by: Identity,
 }
 
 /// Invite codes that allow users to join a company without admin hex-pasting.
+/// Joiners start with `role` (Pending unless the creator preset another role).
+/// Codes with an `expires_at` are purged by `purge_expired_invite_code`.
+/// Private: clients read them through `my_invite_codes`.
 #[spacetimedb::table(
-    accessor = invite_code, public,
+    accessor = invite_code,
     index(accessor = invite_by_company, btree(columns = [company_id]))
 )]
 pub struct InviteCode {
@@ -124,11 +378,51 @@ pub struct InviteCode {
     pub company_id: u64,
     pub created_by: Identity,
     pub uses_remaining: u32,
+    #[default(UserRole::Pending)]
+    pub role: UserRole,
+    #[default(None::<Timestamp>)]
+    pub expires_at: Option<Timestamp>,
+}
+
+/// Scheduled removal of an expiring invite code. One row per code, firing at
+/// the code's `expires_at`; deleted together with the code (see
+/// `delete_invite_code_row`).
+#[spacetimedb::table(accessor = invite_code_expiry, scheduled(purge_expired_invite_code))]
+pub struct InviteCodeExpiry {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: ScheduleAt,
+    #[unique]
+    pub code: String,
+}
+
+/// A personal invitation to join a company, bound to an e-mail address rather
+/// than an identity. Only an account whose matching `UserContact.email` was
+/// verified by the identity provider can claim it: automatically when the
+/// account is created, or via `accept_personal_invitation`. The module sends
+/// no e-mail; the inviting admin lets the invitee know. `email` is stored
+/// lowercase. Private: exposed through `my_personal_invitations`.
+#[spacetimedb::table(
+    accessor = personal_invitation,
+    index(accessor = pinv_by_company, btree(columns = [company_id])),
+    index(accessor = pinv_by_email, btree(columns = [email]))
+)]
+pub struct PersonalInvitation {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub company_id: u64,
+    pub email: String,
+    pub role: UserRole,
+    pub invited_by: Identity,
+    pub created_at: Timestamp,
 }
 
 /// Equipment and service capabilities for a company (1:1 with Company).
+/// Private: exposed through the `visible_capabilities` view.
 #[allow(clippy::struct_excessive_bools)] // Domain model: each capability is an independent flag
-#[spacetimedb::table(accessor = capability, public)]
+#[spacetimedb::table(accessor = capability)]
 pub struct Capability {
     #[primary_key]
     pub company_id: u64,
@@ -158,9 +452,12 @@ pub struct Connection {
 }
 
 /// Chat messages exchanged within a connection (during Pending or Accepted).
+/// Private: clients read them through the `my_connection_chat` view.
 #[spacetimedb::table(
-    accessor = connection_chat, public,
-    index(accessor = chat_by_connection, btree(columns = [connection_id]))
+    accessor = connection_chat,
+    index(accessor = chat_by_connection, btree(columns = [connection_id])),
+    index(accessor = chat_by_connection_and_id, btree(columns = [connection_id, id])),
+    index(accessor = chat_by_sender, btree(columns = [sender]))
 )]
 pub struct ConnectionChat {
     #[primary_key]
@@ -170,6 +467,282 @@ pub struct ConnectionChat {
     pub sender: Identity,
     pub text: String,
     pub created_at: Timestamp,
+    /// Set by a platform moderator; hidden messages are left out of the views.
+    #[default(false)]
+    pub hidden: bool,
+    #[default(None::<Timestamp>)]
+    pub edited_at: Option<Timestamp>,
+    /// Soft-deleted: `text` is cleared, earlier versions stay in `ChatRevision`.
+    #[default(false)]
+    pub deleted: bool,
+    /// Thread this message replies to: the ID of the thread's first message.
+    #[default(None::<u64>)]
+    pub parent_id: Option<u64>,
+    /// Another message of the same conversation quoted by this one.
+    #[default(None::<u64>)]
+    pub quoted_id: Option<u64>,
+    /// Number of replies in the thread this message starts, not counting
+    /// deleted ones.
+    #[default(0_u32)]
+    pub reply_count: u32,
+    /// Users @mentioned in `text`, including readers of mentioned companies.
+    /// `None` on messages sent before mentions were parsed.
+    #[default(None::<Vec<Identity>>)]
+    pub mentions: Option<Vec<Identity>>,
+    /// Companies @mentioned in `text` by slug.
+    #[default(None::<Vec<u64>>)]
+    pub mentioned_company_ids: Option<Vec<u64>>,
+    /// `Attachment`s sent with this message. `None` on messages sent before
+    /// attachments were supported.
+    #[default(None::<Vec<u64>>)]
+    pub attachment_ids: Option<Vec<u64>>,
+    /// System messages record a `SystemEvent`; `sender` is the user who
+    /// caused it.
+    #[default(MessageKind::User)]
+    pub message_kind: MessageKind,
+}
+
+/// Earlier versions of edited or deleted chat messages, one row per change.
+/// Private: clients read the history of messages they can see through the
+/// `my_chat_revisions` view.
+#[spacetimedb::table(
+    accessor = chat_revision,
+    index(accessor = revision_by_message, btree(columns = [message_id]))
+)]
+pub struct ChatRevision {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub kind: ChatKind,
+    pub message_id: u64,
+    pub text: String,
+    pub revised_by: Identity,
+    pub revised_at: Timestamp,
+}
+
+/// A user's read position in one conversation (a connection's, project's or
+/// channel's chat). Its unread count is the conversation's `message_count`
+/// (see `ConversationStats`) minus `read_count` and `skipped_count`; the chat
+/// reducers keep both up to date so clients can show badges without loading
+/// the chat tables. A user without a cursor has not read anything yet.
+/// Private: users read their own unread counts through `my_read_cursors`;
+/// other readers' positions are only shared through `visible_read_receipts`.
+#[spacetimedb::table(
+    accessor = read_cursor,
+    index(accessor = cursor_by_identity, btree(columns = [identity])),
+    index(accessor = cursor_by_conversation, btree(columns = [conversation_id]))
+)]
+pub struct ReadCursor {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub identity: Identity,
+    pub kind: ChatKind,
+    pub conversation_id: u64,
+    pub last_read_message_id: u64,
+    pub last_read_at: Option<Timestamp>,
+    /// Visible messages up to and including `last_read_message_id`.
+    pub read_count: u64,
+    /// Visible messages after `last_read_message_id` that are not unread for
+    /// this user: their own and those of users they blocked.
+    pub skipped_count: u64,
+}
+
+/// Number of visible (neither hidden nor deleted) messages in a live
+/// conversation and the ID of its latest message, kept by the chat reducers
+/// so unread counts never scan the conversation. Deleted when the
+/// conversation is archived or deleted.
+#[spacetimedb::table(
+    accessor = conversation_stats,
+    index(accessor = stats_by_conversation, btree(columns = [conversation_id]))
+)]
+pub struct ConversationStats {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub kind: ChatKind,
+    pub conversation_id: u64,
+    pub message_count: u64,
+    pub last_message_id: u64,
+}
+
+/// Users who share their read positions with the other readers of their
+/// conversations (see `visible_read_receipts`). Private: users read their own
+/// row through `my_read_receipt_sharing`.
+#[spacetimedb::table(accessor = read_receipt_sharing)]
+pub struct ReadReceiptSharing {
+    #[primary_key]
+    pub identity: Identity,
+    pub enabled_at: Timestamp,
+}
+
+/// A conversation's read position and unread count for the caller, as
+/// returned by `my_read_cursors`.
+#[derive(SpacetimeType)]
+pub struct UnreadCount {
+    pub kind: ChatKind,
+    pub conversation_id: u64,
+    pub last_read_message_id: u64,
+    pub last_read_at: Option<Timestamp>,
+    pub unread_count: u64,
+}
+
+/// A file shared in a conversation (a connection's, project's or channel's
+/// chat): artwork proofs, drawings, site photos. Uploaded in chunks into
+/// `AttachmentChunk`, checked against `checksum` (BLAKE3, hex) and then sent
+/// with a chat message (`message_id`, 0 until then). `size` counts against
+/// the quota of `company_id`, the uploader's company. Private: clients read
+/// metadata through `visible_attachments` and the content through the
+/// `download_attachment` procedure.
+#[spacetimedb::table(
+    accessor = attachment,
+    index(accessor = attachment_by_conversation, btree(columns = [conversation_id])),
+    index(accessor = attachment_by_company, btree(columns = [company_id])),
+    index(accessor = attachment_by_uploader, btree(columns = [uploader]))
+)]
+pub struct Attachment {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub kind: ChatKind,
+    pub conversation_id: u64,
+    pub company_id: u64,
+    pub uploader: Identity,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub checksum: String,
+    pub status: AttachmentStatus,
+    pub received_bytes: u64,
+    pub chunk_count: u32,
+    pub message_id: u64,
+    pub created_at: Timestamp,
+    pub completed_at: Option<Timestamp>,
+}
+
+/// Content of an `Attachment`, in upload order (`seq`).
+#[spacetimedb::table(
+    accessor = attachment_chunk,
+    index(accessor = chunk_by_attachment, btree(columns = [attachment_id]))
+)]
+pub struct AttachmentChunk {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub attachment_id: u64,
+    pub seq: u32,
+    pub data: Vec<u8>,
+}
+
+/// Attachment storage used by a company and its quota. Created on the first
+/// upload with `DEFAULT_STORAGE_QUOTA_BYTES`; platform moderators change the
+/// quota through `set_storage_quota`. Private: members read their companies'
+/// rows through `my_company_storage`.
+#[spacetimedb::table(accessor = company_storage)]
+pub struct CompanyStorage {
+    #[primary_key]
+    pub company_id: u64,
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+}
+
+/// A conversation kept read-only after its connection or project was deleted
+/// (cancelled, declined, disconnected or unblocked connections, deleted
+/// projects). Its messages stay in the chat tables under the old
+/// `conversation_id`; `ChatArchiveReader` lists who can still read them.
+/// Pruned by `sweep_chat_retention`. Private: readers list their archives
+/// through `my_chat_archives` and read the messages through the chat views.
+#[spacetimedb::table(
+    accessor = chat_archive,
+    index(accessor = archive_by_conversation, btree(columns = [conversation_id]))
+)]
+pub struct ChatArchive {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub kind: ChatKind,
+    pub conversation_id: u64,
+    /// Project name, or the names of the two connected companies.
+    pub title: String,
+    pub company_ids: Vec<u64>,
+    pub archived_at: Timestamp,
+}
+
+/// A reader of an archived conversation at the time it was archived, and the
+/// company they read it through. Access lasts while they hold Chat there.
+#[spacetimedb::table(
+    accessor = chat_archive_reader,
+    index(accessor = archive_reader_by_archive, btree(columns = [archive_id])),
+    index(accessor = archive_reader_by_identity, btree(columns = [identity]))
+)]
+pub struct ChatArchiveReader {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub archive_id: u64,
+    pub identity: Identity,
+    pub company_id: u64,
+}
+
+/// How many days a company keeps chat history; companies without a row keep
+/// it indefinitely. Set through `set_chat_retention`. Public so partners can
+/// see how long shared conversations are kept.
+#[spacetimedb::table(accessor = chat_retention, public)]
+pub struct ChatRetention {
+    #[primary_key]
+    pub company_id: u64,
+    pub retention_days: u32,
+    pub updated_by: Identity,
+    pub updated_at: Timestamp,
+}
+
+/// The recurring `sweep_chat_retention` job, scheduled once a company sets a
+/// retention.
+#[spacetimedb::table(accessor = chat_retention_sweep, scheduled(sweep_chat_retention))]
+pub struct ChatRetentionSweep {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: ScheduleAt,
+}
+
+/// Inverted index over chat text: one row per word (`term`) per message,
+/// maintained when messages are sent, edited, deleted or purged. Private:
+/// only read by the `search_chat` procedure.
+#[spacetimedb::table(
+    accessor = chat_search_term,
+    index(accessor = search_by_term, btree(columns = [term])),
+    index(accessor = search_by_message, btree(columns = [message_id]))
+)]
+pub struct ChatSearchTerm {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub term: String,
+    pub kind: ChatKind,
+    pub message_id: u64,
+    pub occurrences: u32,
+}
+
+/// A message pinned to the top of its conversation, e.g. an agreed price or
+/// install date. At most `MAX_PINNED_MESSAGES` per conversation. Private:
+/// clients read the pins of conversations they can see through
+/// `my_pinned_messages`.
+#[spacetimedb::table(
+    accessor = pinned_message,
+    index(accessor = pin_by_conversation, btree(columns = [conversation_id])),
+    index(accessor = pin_by_message, btree(columns = [message_id])),
+    index(accessor = pin_by_pinner, btree(columns = [pinned_by]))
+)]
+pub struct PinnedMessage {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub kind: ChatKind,
+    pub conversation_id: u64,
+    pub message_id: u64,
+    pub pinned_by: Identity,
+    pub pinned_at: Timestamp,
 }
 
 /// Records that a user has used a specific invite code. Prevents reuse of the
@@ -188,9 +761,17 @@ pub struct UsedInviteCode {
 }
 
 /// In-app notifications. Each row targets a specific user within a company context.
+/// Private: clients read their own rows through the `my_notifications` view.
 #[spacetimedb::table(
-    accessor = notification, public,
-    index(accessor = notif_by_recipient, btree(columns = [recipient_identity]))
+    accessor = notification,
+    index(accessor = notif_by_recipient, btree(columns = [recipient_identity])),
+    index(accessor = notif_by_company, btree(columns = [company_id])),
+    index(accessor = notif_by_message, btree(columns = [message_id])),
+    index(accessor = notif_by_project, btree(columns = [project_id])),
+    index(accessor = notif_by_connection, btree(columns = [connection_id])),
+    index(accessor = notif_by_channel, btree(columns = [channel_id])),
+    index(accessor = notif_by_actor, btree(columns = [actor_identity])),
+    index(accessor = notif_by_target_company, btree(columns = [target_company_id]))
 )]
 pub struct Notification {
     #[primary_key]
@@ -203,6 +784,91 @@ pub struct Notification {
     pub body: String,
     pub is_read: bool,
     pub created_at: Timestamp,
+    /// The chat message a ChatMessage / ProjectChat notification is about (0
+    /// for other types), so edits and deletions can update or withdraw it.
+    /// For a coalesced notification, the latest of its messages.
+    #[default(0_u64)]
+    pub message_id: u64,
+    /// Number of messages a chat message notification stands for: new
+    /// messages in a conversation update the recipient's unread notification
+    /// about it instead of adding one (see `notify_in_conversation`). 1 for
+    /// other notifications.
+    #[default(1_u32)]
+    pub message_count: u32,
+    /// The project the notification is about (0 for none). Deleted with the
+    /// project.
+    #[default(0_u64)]
+    pub project_id: u64,
+    /// The connection the notification is about (0 for none). Deleted with the
+    /// connection.
+    #[default(0_u64)]
+    pub connection_id: u64,
+    /// The internal channel or direct conversation the notification is about
+    /// (0 for none). Deleted with the channel.
+    #[default(0_u64)]
+    pub channel_id: u64,
+    /// The user whose action raised the notification. `Identity::ZERO` for
+    /// moderation decisions and once the actor deletes their account.
+    #[default(Identity::ZERO)]
+    pub actor_identity: Identity,
+    /// The other company the notification is about, e.g. the one requesting a
+    /// connection or leaving a project (0 for none). Deleted with that company.
+    #[default(0_u64)]
+    pub target_company_id: u64,
+    /// Platform notifications belong to no company and have `company_id` 0.
+    #[default(NotificationScope::Company)]
+    pub scope: NotificationScope,
+}
+
+/// What a notification refers to, stored on its reference fields so clients
+/// can link to the subject and cascades can find it (0 for none).
+#[derive(Clone, Copy, Default)]
+struct NotificationRefs {
+    project_id: u64,
+    connection_id: u64,
+    channel_id: u64,
+    target_company_id: u64,
+    /// Leaves `actor_identity` unset, e.g. so moderators stay anonymous.
+    hide_actor: bool,
+    scope: NotificationScope,
+}
+
+/// A user's notification level for one `NotificationType` in one company.
+/// Types without a row are delivered (`All`). For the chat message types
+/// (`ChatMessage`, `ProjectChat`, `ChannelMessage`) the level also governs
+/// that conversation kind's replies and mentions. Private: users read their
+/// own rows through `my_notification_preferences`.
+#[spacetimedb::table(
+    accessor = notification_preference,
+    index(accessor = pref_by_identity, btree(columns = [identity])),
+    index(accessor = pref_by_company, btree(columns = [company_id]))
+)]
+pub struct NotificationPreference {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub identity: Identity,
+    pub company_id: u64,
+    pub notification_type: NotificationType,
+    pub level: NotificationLevel,
+}
+
+/// A conversation a user muted: its chat notifications are skipped, except
+/// @mentions, until `muted_until` (indefinitely if `None`). Private: users
+/// read their own rows through `my_conversation_mutes`.
+#[spacetimedb::table(
+    accessor = conversation_mute,
+    index(accessor = mute_by_identity, btree(columns = [identity])),
+    index(accessor = mute_by_conversation, btree(columns = [conversation_id]))
+)]
+pub struct ConversationMute {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub identity: Identity,
+    pub kind: ChatKind,
+    pub conversation_id: u64,
+    pub muted_until: Option<Timestamp>,
 }
 
 /// A project room where 3+ companies collaborate on a job.
@@ -235,10 +901,33 @@ pub struct ProjectMember {
     pub joined_at: Timestamp,
 }
 
+/// Individual users assigned to a project by their (Accepted) company.
+/// Only participants see the project chat; Observers can read but not post.
+/// Private: clients read it through the `my_project_participants` view.
+#[spacetimedb::table(
+    accessor = project_participant,
+    index(accessor = pp_by_project, btree(columns = [project_id])),
+    index(accessor = pp_by_identity, btree(columns = [identity]))
+)]
+pub struct ProjectParticipant {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub project_id: u64,
+    pub company_id: u64,
+    pub identity: Identity,
+    pub role: ProjectRole,
+    pub assigned_by: Identity,
+    pub assigned_at: Timestamp,
+}
+
 /// Chat messages within a project room.
+/// Private: clients read them through the `my_project_chat` view.
 #[spacetimedb::table(
-    accessor = project_chat, public,
-    index(accessor = pchat_by_project, btree(columns = [project_id]))
+    accessor = project_chat,
+    index(accessor = pchat_by_project, btree(columns = [project_id])),
+    index(accessor = pchat_by_project_and_id, btree(columns = [project_id, id])),
+    index(accessor = pchat_by_sender, btree(columns = [sender]))
 )]
 pub struct ProjectChat {
     #[primary_key]
@@ -248,36 +937,349 @@ pub struct ProjectChat {
     pub sender: Identity,
     pub text: String,
     pub created_at: Timestamp,
+    /// Set by a platform moderator; hidden messages are left out of the views.
+    #[default(false)]
+    pub hidden: bool,
+    #[default(None::<Timestamp>)]
+    pub edited_at: Option<Timestamp>,
+    /// Soft-deleted: `text` is cleared, earlier versions stay in `ChatRevision`.
+    #[default(false)]
+    pub deleted: bool,
+    /// Thread this message replies to: the ID of the thread's first message.
+    #[default(None::<u64>)]
+    pub parent_id: Option<u64>,
+    /// Another message of the same conversation quoted by this one.
+    #[default(None::<u64>)]
+    pub quoted_id: Option<u64>,
+    /// Number of replies in the thread this message starts, not counting
+    /// deleted ones.
+    #[default(0_u32)]
+    pub reply_count: u32,
+    /// Users @mentioned in `text`, including readers of mentioned companies.
+    /// `None` on messages sent before mentions were parsed.
+    #[default(None::<Vec<Identity>>)]
+    pub mentions: Option<Vec<Identity>>,
+    /// Companies @mentioned in `text` by slug.
+    #[default(None::<Vec<u64>>)]
+    pub mentioned_company_ids: Option<Vec<u64>>,
+    /// `Attachment`s sent with this message. `None` on messages sent before
+    /// attachments were supported.
+    #[default(None::<Vec<u64>>)]
+    pub attachment_ids: Option<Vec<u64>>,
+    /// System messages record a `SystemEvent`; `sender` is the user who
+    /// caused it.
+    #[default(MessageKind::User)]
+    pub message_kind: MessageKind,
 }
 
-// ---------------------------------------------------------------------------
-// Validation helpers
-// ---------------------------------------------------------------------------
-
-/// Maximum allowed string lengths for user-facing fields.
-const MAX_FULL_NAME: usize = 50;
-const MAX_NICKNAME: usize = 25;
-const MAX_EMAIL: usize = 100;
-const MAX_COMPANY_NAME: usize = 50;
-const MAX_SLUG: usize = 50;
-const MAX_LOCATION: usize = 100;
-const MAX_BIO: usize = 500;
-const MAX_KVK_NUMBER: usize = 20;
-const MAX_INVITE_CODE: usize = 25;
-const MAX_MESSAGE: usize = 500;
-const MAX_PROJECT_NAME: usize = 80;
-const MAX_PROJECT_DESCRIPTION: usize = 500;
+/// An internal channel of a company (e.g. `#werkplaats`), or a direct
+/// conversation between two of its members (`is_direct`, with an empty
+/// `name`). Channels are managed by members holding ManageMembers; direct
+/// conversations are opened by either member through
+/// `open_direct_conversation`. Private: members read them through
+/// `my_channels`.
+#[spacetimedb::table(
+    accessor = company_channel,
+    index(accessor = channel_by_company, btree(columns = [company_id]))
+)]
+pub struct CompanyChannel {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub company_id: u64,
+    /// Normalized like a slug, without the leading `#`.
+    pub name: String,
+    pub is_direct: bool,
+    pub created_by: Identity,
+    pub created_at: Timestamp,
+}
 
-/// Validates that a trimmed string does not exceed `max_len` characters.
-fn validate_length(value: &str, field: &str, max_len: usize) -> Result<(), String> {
-    if value.len() > max_len {
-        return Err(format!("{field} is too long (max {max_len} characters)"));
-    }
-    Ok(())
+/// A member of a `CompanyChannel`. Only members read and post in the channel,
+/// and only while they hold Chat in its company.
+#[spacetimedb::table(
+    accessor = channel_member,
+    index(accessor = channel_member_by_channel, btree(columns = [channel_id])),
+    index(accessor = channel_member_by_identity, btree(columns = [identity]))
+)]
+pub struct ChannelMember {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub channel_id: u64,
+    pub identity: Identity,
+    pub added_by: Identity,
+    pub joined_at: Timestamp,
 }
 
-/// Validates that a trimmed string is not empty.
-fn validate_not_empty(value: &str, field: &str) -> Result<(), String> {
+/// Chat messages within a company channel or direct conversation.
+/// Private: clients read them through the `my_channel_chat` view.
+#[spacetimedb::table(
+    accessor = channel_chat,
+    index(accessor = channel_chat_by_channel, btree(columns = [channel_id])),
+    index(accessor = channel_chat_by_channel_and_id, btree(columns = [channel_id, id])),
+    index(accessor = channel_chat_by_sender, btree(columns = [sender]))
+)]
+pub struct ChannelChat {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub channel_id: u64,
+    pub sender: Identity,
+    pub text: String,
+    pub created_at: Timestamp,
+    /// Set by a platform moderator; hidden messages are left out of the views.
+    pub hidden: bool,
+    pub edited_at: Option<Timestamp>,
+    /// Soft-deleted: `text` is cleared, earlier versions stay in `ChatRevision`.
+    pub deleted: bool,
+    /// Thread this message replies to: the ID of the thread's first message.
+    pub parent_id: Option<u64>,
+    /// Another message of the same conversation quoted by this one.
+    pub quoted_id: Option<u64>,
+    /// Number of replies in the thread this message starts, not counting
+    /// deleted ones.
+    pub reply_count: u32,
+    /// Users @mentioned in `text`, including readers of mentioned companies.
+    pub mentions: Vec<Identity>,
+    /// Companies @mentioned in `text` by slug.
+    pub mentioned_company_ids: Vec<u64>,
+    /// `Attachment`s sent with this message.
+    pub attachment_ids: Vec<u64>,
+    /// System messages record a `SystemEvent`; `sender` is the user who
+    /// caused it.
+    pub message_kind: MessageKind,
+}
+
+/// A user-to-user block. The blocker no longer sees the blocked user's chat
+/// messages or receives notifications caused by them. Private, so the block is
+/// never revealed to the blocked user; the blocker reads their own list
+/// through the `my_user_blocks` view.
+#[spacetimedb::table(
+    accessor = user_block,
+    index(accessor = ublock_by_blocker, btree(columns = [blocker])),
+    index(accessor = ublock_by_blocked, btree(columns = [blocked]))
+)]
+pub struct UserBlock {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub blocker: Identity,
+    pub blocked: Identity,
+    pub created_at: Timestamp,
+}
+
+/// Platform moderators, independent of any company role. The publishing
+/// identity is seeded in `init`; moderators can appoint further moderators.
+#[spacetimedb::table(accessor = platform_moderator, public)]
+pub struct PlatformModerator {
+    #[primary_key]
+    pub identity: Identity,
+    pub added_by: Identity,
+    pub added_at: Timestamp,
+}
+
+/// A report about a chat message, company or user. `excerpt` snapshots the
+/// reported content so moderators can judge it even after edits or hiding.
+/// Private: clients read it through the `visible_reports` view.
+#[spacetimedb::table(
+    accessor = moderation_report,
+    index(accessor = report_by_reporter, btree(columns = [reporter])),
+    index(accessor = report_by_status, btree(columns = [status]))
+)]
+pub struct ModerationReport {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub reporter: Identity,
+    pub target: ReportTarget,
+    pub reason: String,
+    pub excerpt: String,
+    pub status: ReportStatus,
+    pub created_at: Timestamp,
+    pub reviewed_by: Option<Identity>,
+    pub decision: String,
+    pub reviewed_at: Option<Timestamp>,
+}
+
+/// Companies suspended by a platform moderator. Members of a suspended
+/// company cannot call any reducer that acts as that company.
+#[spacetimedb::table(accessor = company_suspension, public)]
+pub struct CompanySuspension {
+    #[primary_key]
+    pub company_id: u64,
+    pub reason: String,
+    pub suspended_by: Identity,
+    pub suspended_at: Timestamp,
+}
+
+/// One row per throttled action, read as a sliding window by
+/// `check_rate_limit`. Rows older than the longest window of their action are
+/// pruned whenever the same identity or company records a new one.
+#[spacetimedb::table(
+    accessor = rate_limit_event,
+    index(accessor = rle_by_identity, btree(columns = [identity])),
+    index(accessor = rle_by_company, btree(columns = [company_id]))
+)]
+pub struct RateLimitEvent {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub action: RateLimitedAction,
+    pub identity: Identity,
+    pub company_id: u64,
+    pub created_at: Timestamp,
+}
+
+/// Platform-wide rate limits: a single row (id 0) set by platform moderators
+/// through `set_rate_limits`. The `DEFAULT_*` constants apply until then.
+#[spacetimedb::table(accessor = rate_limit_config, public)]
+pub struct RateLimitConfig {
+    #[primary_key]
+    pub id: u32,
+    pub chat_messages_per_minute_user: u32,
+    pub chat_messages_per_minute_company: u32,
+    pub connection_requests_per_hour_user: u32,
+    pub connection_requests_per_day_company: u32,
+}
+
+/// Platform-wide settings: a single row (id 0) seeded by `init` and set by
+/// platform moderators through `set_company_deletion_grace_days` and
+/// `set_platform_owner`. The `DEFAULT_*` constants apply until then.
+#[spacetimedb::table(accessor = platform_config, public)]
+pub struct PlatformConfig {
+    #[primary_key]
+    pub id: u32,
+    pub company_deletion_grace_days: u32,
+    /// Made a moderator whenever the platform has none (see
+    /// `bootstrap_platform_moderator`). The publishing identity at first, or
+    /// `Identity::ZERO` for a database created before owners were recorded.
+    pub owner_identity: Identity,
+}
+
+/// Persistent audit trail. One row per privileged action, scoped to the company
+/// the actor was operating as. Private: members with ViewAuditLog read their
+/// own company's trail through the `my_company_audit_events` view.
+#[spacetimedb::table(
+    accessor = audit_event,
+    index(accessor = audit_by_company, btree(columns = [company_id])),
+    index(accessor = audit_by_actor, btree(columns = [actor])),
+    index(accessor = audit_by_target_user, btree(columns = [target_user]))
+)]
+pub struct AuditEvent {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub actor: Identity,
+    pub company_id: u64,
+    pub action: AuditAction,
+    pub target_identity: Option<Identity>,
+    pub target_company_id: Option<u64>,
+    pub target_project_id: Option<u64>,
+    pub old_value: String,
+    pub new_value: String,
+    pub created_at: Timestamp,
+    /// `target_identity`, or `Identity::ZERO` if there is none. Optional
+    /// columns cannot be indexed, so `delete_account` finds the events about a
+    /// user through this one.
+    pub target_user: Identity,
+}
+
+/// Audit trail of events no company can read, such as the final purge of a
+/// company. Private: platform moderators read it through
+/// `platform_audit_events`.
+#[spacetimedb::table(
+    accessor = platform_audit_event,
+    index(accessor = platform_audit_by_action, btree(columns = [action])),
+    index(accessor = platform_audit_by_actor, btree(columns = [actor]))
+)]
+pub struct PlatformAuditEvent {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub actor: Identity,
+    pub action: AuditAction,
+    pub target_identity: Option<Identity>,
+    pub target_company_id: Option<u64>,
+    pub old_value: String,
+    pub new_value: String,
+    pub created_at: Timestamp,
+}
+
+// ---------------------------------------------------------------------------
+// Validation helpers
+// ---------------------------------------------------------------------------
+
+/// Maximum allowed string lengths for user-facing fields.
+const MAX_FULL_NAME: usize = 50;
+const MAX_NICKNAME: usize = 25;
+const MAX_EMAIL: usize = 100;
+const MAX_COMPANY_NAME: usize = 50;
+const MAX_SLUG: usize = 50;
+const MAX_LOCATION: usize = 100;
+const MAX_BIO: usize = 500;
+const MAX_KVK_NUMBER: usize = 20;
+const MAX_INVITE_CODE: usize = 25;
+const MAX_MESSAGE: usize = 500;
+const MAX_PROJECT_NAME: usize = 80;
+const MAX_PROJECT_DESCRIPTION: usize = 500;
+const MAX_ROLE_NAME: usize = 30;
+const MAX_CHANNEL_NAME: usize = 40;
+const MAX_REPORT_TEXT: usize = 500;
+const MAX_FILENAME: usize = 255;
+const MAX_MIME_TYPE: usize = 100;
+const MAX_SEARCH_QUERY: usize = 100;
+const MAX_SEARCH_TERM: usize = 40;
+
+/// Chat search results: default and maximum count, and the characters of
+/// context before the first match plus the snippet length.
+const DEFAULT_SEARCH_RESULTS: u32 = 20;
+const MAX_SEARCH_RESULTS: u32 = 100;
+const SEARCH_SNIPPET_LEAD: usize = 30;
+const SEARCH_SNIPPET_LEN: usize = 120;
+
+/// Days between `delete_company` and the permanent purge of the company, until
+/// a moderator sets another value, and the longest value they can set.
+const DEFAULT_COMPANY_DELETION_GRACE_DAYS: u32 = 14;
+const MAX_COMPANY_DELETION_GRACE_DAYS: u32 = 90;
+
+/// Shortest chat retention a company can set, and how often chat history past
+/// its retention is pruned.
+const MIN_CHAT_RETENTION_DAYS: u32 = 30;
+const CHAT_RETENTION_SWEEP_HOURS: u64 = 24;
+
+/// Minutes after sending during which a chat message can still be edited.
+const CHAT_EDIT_WINDOW_MINUTES: u64 = 15;
+
+/// Pinned messages per conversation.
+const MAX_PINNED_MESSAGES: usize = 20;
+
+/// Attachment limits. Uploads larger than a chunk are split by the client.
+const MAX_ATTACHMENT_BYTES: u64 = 25 * 1024 * 1024;
+const ATTACHMENT_CHUNK_BYTES: usize = 64 * 1024;
+const MAX_ATTACHMENTS_PER_MESSAGE: usize = 10;
+
+/// Hours after which an unfinished upload is discarded and its storage freed.
+const ATTACHMENT_UPLOAD_TTL_HOURS: i64 = 24;
+
+/// Attachment storage per company until a moderator sets another quota.
+const DEFAULT_STORAGE_QUOTA_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Rate limits used until a moderator stores a `RateLimitConfig`.
+const DEFAULT_CHAT_PER_MINUTE_USER: u32 = 20;
+const DEFAULT_CHAT_PER_MINUTE_COMPANY: u32 = 120;
+const DEFAULT_CONNECTION_REQUESTS_PER_HOUR_USER: u32 = 10;
+const DEFAULT_CONNECTION_REQUESTS_PER_DAY_COMPANY: u32 = 25;
+
+/// Validates that a trimmed string does not exceed `max_len` characters.
+fn validate_length(value: &str, field: &str, max_len: usize) -> Result<(), String> {
+    if value.len() > max_len {
+        return Err(format!("{field} is too long (max {max_len} characters)"));
+    }
+    Ok(())
+}
+
+/// Validates that a trimmed string is not empty.
+fn validate_not_empty(value: &str, field: &str) -> Result<(), String> {
     if value.is_empty() {
         return Err(format!("{field} cannot be empty"));
     }
@@ -369,11 +1371,282 @@ fn id_short(identity: Identity) -> String {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Rate limiting helpers
+// ---------------------------------------------------------------------------
+
+const MINUTE_MICROS: i64 = 60 * 1_000_000;
+const HOUR_MICROS: i64 = 60 * MINUTE_MICROS;
+const DAY_MICROS: i64 = 24 * HOUR_MICROS;
+
+/// The stored platform settings, or the defaults if none have been set.
+fn platform_config(ctx: &ReducerContext) -> PlatformConfig {
+    ctx.db
+        .platform_config()
+        .id()
+        .find(0)
+        .unwrap_or(PlatformConfig {
+            id: 0,
+            company_deletion_grace_days: DEFAULT_COMPANY_DELETION_GRACE_DAYS,
+            owner_identity: Identity::ZERO,
+        })
+}
+
+/// Stores the platform settings row.
+fn save_platform_config(ctx: &ReducerContext, config: PlatformConfig) {
+    if ctx.db.platform_config().id().find(0).is_some() {
+        ctx.db.platform_config().id().update(config);
+    } else {
+        ctx.db.platform_config().insert(config);
+    }
+}
+
+/// The stored rate limits, or the defaults if none have been set.
+fn rate_limit_config(ctx: &ReducerContext) -> RateLimitConfig {
+    ctx.db
+        .rate_limit_config()
+        .id()
+        .find(0)
+        .unwrap_or(RateLimitConfig {
+            id: 0,
+            chat_messages_per_minute_user: DEFAULT_CHAT_PER_MINUTE_USER,
+            chat_messages_per_minute_company: DEFAULT_CHAT_PER_MINUTE_COMPANY,
+            connection_requests_per_hour_user: DEFAULT_CONNECTION_REQUESTS_PER_HOUR_USER,
+            connection_requests_per_day_company: DEFAULT_CONNECTION_REQUESTS_PER_DAY_COMPANY,
+        })
+}
+
+/// Human-readable name of a window length.
+const fn window_name(window_micros: i64) -> &'static str {
+    if window_micros >= DAY_MICROS {
+        "day"
+    } else if window_micros >= HOUR_MICROS {
+        "hour"
+    } else {
+        "minute"
+    }
+}
+
+/// Formats a wait in microseconds as whole seconds, minutes or hours (rounded up).
+fn format_wait(micros: i64) -> String {
+    let secs = (micros + 999_999) / 1_000_000;
+    if secs >= 3600 {
+        format!("{}h", (secs + 3599) / 3600)
+    } else if secs >= 60 {
+        format!("{}m", (secs + 59) / 60)
+    } else {
+        format!("{}s", secs.max(1))
+    }
+}
+
+/// What `action` counts, and its per-identity and per-company limits as
+/// (limit, window) pairs.
+const fn rate_limits(
+    config: &RateLimitConfig,
+    action: RateLimitedAction,
+) -> (&'static str, (u32, i64), (u32, i64)) {
+    match action {
+        RateLimitedAction::ConnectionRequest => (
+            "connection requests",
+            (config.connection_requests_per_hour_user, HOUR_MICROS),
+            (config.connection_requests_per_day_company, DAY_MICROS),
+        ),
+        RateLimitedAction::ConnectionChat
+        | RateLimitedAction::ProjectChat
+        | RateLimitedAction::ChannelChat => (
+            "messages",
+            (config.chat_messages_per_minute_user, MINUTE_MICROS),
+            (config.chat_messages_per_minute_company, MINUTE_MICROS),
+        ),
+    }
+}
+
+/// Enforces the per-identity and per-company sliding-window limits for
+/// `action` and records the attempt. Call it once the action has passed its
+/// other checks; a later failure rolls the recorded event back with the
+/// transaction.
+///
+/// # Errors
+///
+/// Returns an error naming the limit and when to retry if the caller or
+/// their company has reached it.
+fn check_rate_limit(
+    ctx: &ReducerContext,
+    action: RateLimitedAction,
+    company_id: u64,
+) -> Result<(), String> {
+    let config = rate_limit_config(ctx);
+    let (what, (user_limit, user_window), (company_limit, company_window)) =
+        rate_limits(&config, action);
+    let now = ctx.timestamp.to_micros_since_unix_epoch();
+
+    // Prune events that have left every window of their action
+    let is_stale = |e: &RateLimitEvent| {
+        let (_, (_, user_window), (_, company_window)) = rate_limits(&config, e.action);
+        e.created_at.to_micros_since_unix_epoch() <= now - user_window.max(company_window)
+    };
+    let mut stale_ids: Vec<u64> = ctx
+        .db
+        .rate_limit_event()
+        .rle_by_identity()
+        .filter(&ctx.sender())
+        .filter(is_stale)
+        .map(|e| e.id)
+        .collect();
+    stale_ids.extend(
+        ctx.db
+            .rate_limit_event()
+            .rle_by_company()
+            .filter(&company_id)
+            .filter(is_stale)
+            .map(|e| e.id),
+    );
+    stale_ids.sort_unstable();
+    stale_ids.dedup();
+    for id in stale_ids {
+        ctx.db.rate_limit_event().id().delete(id);
+    }
+
+    // Oldest event still inside the window, if the limit has been reached
+    let oldest_when_full = |times: Vec<i64>, limit: u32, window: i64| -> Option<i64> {
+        let in_window: Vec<i64> = times.into_iter().filter(|t| *t > now - window).collect();
+        if in_window.len() >= limit as usize {
+            in_window.into_iter().min()
+        } else {
+            None
+        }
+    };
+
+    let user_times: Vec<i64> = ctx
+        .db
+        .rate_limit_event()
+        .rle_by_identity()
+        .filter(&ctx.sender())
+        .filter(|e| e.action == action)
+        .map(|e| e.created_at.to_micros_since_unix_epoch())
+        .collect();
+    if let Some(oldest) = oldest_when_full(user_times, user_limit, user_window) {
+        return Err(format!(
+            "Too many {}: you can send at most {} per {}. Try again in {}.",
+            what,
+            user_limit,
+            window_name(user_window),
+            format_wait(oldest + user_window - now)
+        ));
+    }
+
+    let company_times: Vec<i64> = ctx
+        .db
+        .rate_limit_event()
+        .rle_by_company()
+        .filter(&company_id)
+        .filter(|e| e.action == action)
+        .map(|e| e.created_at.to_micros_since_unix_epoch())
+        .collect();
+    if let Some(oldest) = oldest_when_full(company_times, company_limit, company_window) {
+        return Err(format!(
+            "Too many {}: your company can send at most {} per {}. Try again in {}.",
+            what,
+            company_limit,
+            window_name(company_window),
+            format_wait(oldest + company_window - now)
+        ));
+    }
+
+    ctx.db.rate_limit_event().insert(RateLimitEvent {
+        id: 0,
+        action,
+        identity: ctx.sender(),
+        company_id,
+        created_at: ctx.timestamp,
+    });
+
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Notification helpers
 // ---------------------------------------------------------------------------
 
-/// Insert a notification for a single recipient.
+/// Everyone `blocker` has blocked.
+fn blocked_by(db: &LocalReadOnly, blocker: Identity) -> Vec<Identity> {
+    db.user_block()
+        .ublock_by_blocker()
+        .filter(&blocker)
+        .map(|b| b.blocked)
+        .collect()
+}
+
+/// Whether `blocker` has blocked `blocked` (see `UserBlock`).
+fn has_blocked(db: &LocalReadOnly, blocker: Identity, blocked: Identity) -> bool {
+    db.user_block()
+        .ublock_by_blocker()
+        .filter(&blocker)
+        .any(|b| b.blocked == blocked)
+}
+
+/// A user's level for a notification type in a company (`All` by default).
+fn notification_level(
+    db: &LocalReadOnly,
+    identity: Identity,
+    company_id: u64,
+    notification_type: NotificationType,
+) -> NotificationLevel {
+    db.notification_preference()
+        .pref_by_identity()
+        .filter(&identity)
+        .find(|p| p.company_id == company_id && p.notification_type == notification_type)
+        .map_or(NotificationLevel::All, |p| p.level)
+}
+
+/// Whether a user's preferences let a notification through. Replies and
+/// mentions also honour the level of their kind's chat message type.
+fn wants_notification(
+    db: &LocalReadOnly,
+    identity: Identity,
+    company_id: u64,
+    notification_type: NotificationType,
+) -> bool {
+    let level = |t: NotificationType| notification_level(db, identity, company_id, t);
+    let chat_types = [ChatKind::Connection, ChatKind::Project, ChatKind::Channel]
+        .into_iter()
+        .map(chat_notification_types)
+        .find(|types| types.contains(notification_type));
+    match chat_types {
+        Some(types) if types.message == notification_type => {
+            level(types.message) == NotificationLevel::All
+        }
+        Some(types) => {
+            level(types.message) != NotificationLevel::Off
+                && level(notification_type) != NotificationLevel::Off
+        }
+        None => level(notification_type) != NotificationLevel::Off,
+    }
+}
+
+/// Whether a user has muted a conversation (and the mute has not expired).
+fn is_conversation_muted(
+    ctx: &ReducerContext,
+    identity: Identity,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> bool {
+    ctx.db
+        .conversation_mute()
+        .mute_by_identity()
+        .filter(&identity)
+        .any(|m| {
+            m.kind == kind
+                && m.conversation_id == conversation_id
+                && m.muted_until.is_none_or(|until| until > ctx.timestamp)
+        })
+}
+
+/// Insert a notification for a single recipient and return its ID. Silently
+/// skipped (returning `None`) when the recipient has blocked the caller or
+/// turned the type off (see `wants_notification`). Platform notifications and
+/// moderation decisions are delivered even if the recipient blocked the
+/// moderator.
 fn notify(
     ctx: &ReducerContext,
     recipient: Identity,
@@ -381,8 +1654,21 @@ fn notify(
     notification_type: NotificationType,
     title: String,
     body: String,
-) {
-    ctx.db.notification().insert(Notification {
+    refs: NotificationRefs,
+) -> Option<u64> {
+    let db = &ctx.as_read_only().db;
+    let from_moderation = refs.scope == NotificationScope::Platform
+        || matches!(
+            notification_type,
+            NotificationType::CompanySuspension | NotificationType::ReportReviewed
+        );
+    if !from_moderation && has_blocked(db, recipient, ctx.sender()) {
+        return None;
+    }
+    if !wants_notification(db, recipient, company_id, notification_type) {
+        return None;
+    }
+    let notification = ctx.db.notification().insert(Notification {
         id: 0,
         recipient_identity: recipient,
         company_id,
@@ -391,2177 +1677,10441 @@ fn notify(
         body,
         is_read: false,
         created_at: ctx.timestamp,
+        message_id: 0,
+        message_count: 1,
+        project_id: refs.project_id,
+        connection_id: refs.connection_id,
+        channel_id: refs.channel_id,
+        actor_identity: if refs.hide_actor {
+            Identity::ZERO
+        } else {
+            ctx.sender()
+        },
+        target_company_id: refs.target_company_id,
+        scope: refs.scope,
     });
+    Some(notification.id)
 }
 
-/// Insert a notification for all members of a company with at least `min_role`,
-/// optionally excluding a specific identity (typically the actor).
-fn notify_company_role(
+/// `notify` for a chat notification about a conversation: also skipped while
+/// the recipient has muted it, unless it is a mention. A new message notifies
+/// by updating the recipient's unread message notification about the
+/// conversation, if there is one: its count goes up and `body` replaces the
+/// previous preview. Returns the ID of the inserted or updated notification.
+#[allow(clippy::too_many_arguments)] // notify's arguments plus the conversation
+fn notify_in_conversation(
     ctx: &ReducerContext,
+    recipient: Identity,
     company_id: u64,
-    min_role: UserRole,
-    exclude: Option<Identity>,
     notification_type: NotificationType,
+    kind: ChatKind,
+    conversation_id: u64,
     title: String,
     body: String,
-) {
-    let recipients: Vec<Identity> = ctx
-        .db
-        .company_member()
-        .member_by_company()
-        .filter(&company_id)
-        .filter(|m| role_level(m.role) >= role_level(min_role))
-        .filter(|m| exclude != Some(m.identity))
-        .map(|m| m.identity)
-        .collect();
+) -> Option<u64> {
+    let ChatNotificationTypes {
+        message: message_type,
+        mention: mention_type,
+        ..
+    } = chat_notification_types(kind);
+    if notification_type != mention_type
+        && is_conversation_muted(ctx, recipient, kind, conversation_id)
+    {
+        return None;
+    }
+
+    let digest = if notification_type == message_type {
+        find_unread_digest(ctx, recipient, company_id, kind, conversation_id)
+    } else {
+        None
+    };
+    let Some(digest) = digest else {
+        let refs = conversation_refs(ctx, kind, conversation_id, company_id);
+        return notify(
+            ctx,
+            recipient,
+            company_id,
+            notification_type,
+            title,
+            body,
+            refs,
+        );
+    };
 
-    for recipient in recipients {
-        notify(ctx, recipient, company_id, notification_type, title.clone(), body.clone());
+    let db = &ctx.as_read_only().db;
+    if has_blocked(db, recipient, ctx.sender())
+        || !wants_notification(db, recipient, company_id, notification_type)
+    {
+        return None;
     }
+    let id = digest.id;
+    let message_count = digest.message_count.saturating_add(1);
+    ctx.db.notification().id().update(Notification {
+        title: digest_title(
+            ctx,
+            kind,
+            conversation_id,
+            recipient,
+            company_id,
+            message_count,
+        ),
+        body,
+        created_at: ctx.timestamp,
+        message_count,
+        actor_identity: ctx.sender(),
+        ..digest
+    });
+    Some(id)
 }
 
-// ---------------------------------------------------------------------------
-// Project helpers
-// ---------------------------------------------------------------------------
-
-fn find_project_membership(
+/// References of a chat notification about a conversation, as seen from
+/// `company_id`: the conversation itself and, for a connection, the other
+/// company.
+fn conversation_refs(
     ctx: &ReducerContext,
-    project_id: u64,
+    kind: ChatKind,
+    conversation_id: u64,
     company_id: u64,
-    status: ProjectMemberStatus,
-) -> Option<ProjectMember> {
-    ctx.db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .find(|m| m.company_id == company_id && m.status == status)
+) -> NotificationRefs {
+    match kind {
+        ChatKind::Connection => NotificationRefs {
+            connection_id: conversation_id,
+            target_company_id: ctx
+                .db
+                .company_connection()
+                .id()
+                .find(conversation_id)
+                .map(|c| {
+                    if c.company_a == company_id {
+                        c.company_b
+                    } else {
+                        c.company_a
+                    }
+                })
+                .unwrap_or_default(),
+            ..NotificationRefs::default()
+        },
+        ChatKind::Project => NotificationRefs {
+            project_id: conversation_id,
+            ..NotificationRefs::default()
+        },
+        ChatKind::Channel => NotificationRefs {
+            channel_id: conversation_id,
+            ..NotificationRefs::default()
+        },
+    }
 }
 
-fn delete_project_cascade(ctx: &ReducerContext, project_id: u64) {
-    // 1. Delete all chat messages
-    let chat_ids: Vec<u64> = ctx
+/// Deletes every notification referring to a project.
+fn delete_project_notifications(ctx: &ReducerContext, project_id: u64) {
+    let ids: Vec<u64> = ctx
         .db
-        .project_chat()
-        .pchat_by_project()
+        .notification()
+        .notif_by_project()
         .filter(&project_id)
-        .map(|c| c.id)
+        .map(|n| n.id)
         .collect();
-    for id in chat_ids {
-        ctx.db.project_chat().id().delete(id);
+    for id in ids {
+        ctx.db.notification().id().delete(id);
     }
+}
 
-    // 2. Delete all members
-    let member_ids: Vec<u64> = ctx
+/// Deletes every notification referring to a connection.
+fn delete_connection_notifications(ctx: &ReducerContext, connection_id: u64) {
+    let ids: Vec<u64> = ctx
         .db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .map(|m| m.id)
+        .notification()
+        .notif_by_connection()
+        .filter(&connection_id)
+        .map(|n| n.id)
         .collect();
-    for id in member_ids {
-        ctx.db.project_member().id().delete(id);
+    for id in ids {
+        ctx.db.notification().id().delete(id);
     }
+}
 
-    // 3. Delete notifications for this project (project_id stored in company_id field won't match,
-    //    but we can clean up by iterating — projects use their own notification types)
-
-    // 4. Delete the project row
-    ctx.db.project().id().delete(project_id);
+/// Deletes every notification referring to an internal channel.
+fn delete_channel_notifications(ctx: &ReducerContext, channel_id: u64) {
+    let ids: Vec<u64> = ctx
+        .db
+        .notification()
+        .notif_by_channel()
+        .filter(&channel_id)
+        .map(|n| n.id)
+        .collect();
+    for id in ids {
+        ctx.db.notification().id().delete(id);
+    }
 }
 
-// ---------------------------------------------------------------------------
-// Permission helpers
-// ---------------------------------------------------------------------------
+/// The recipient's unread message notification about a conversation, if any.
+fn find_unread_digest(
+    ctx: &ReducerContext,
+    recipient: Identity,
+    company_id: u64,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Option<Notification> {
+    let message_type = chat_notification_types(kind).message;
+    conversation_notifications(ctx, kind, conversation_id)
+        .into_iter()
+        .find(|n| {
+            n.recipient_identity == recipient
+                && !n.is_read
+                && n.company_id == company_id
+                && n.notification_type == message_type
+                && n.message_id != 0
+        })
+}
 
-/// Returns a numeric level for role comparison. Higher = more privileged.
-const fn role_level(role: UserRole) -> u8 {
-    match role {
-        UserRole::Pending => 0,
-        UserRole::Field => 1,
-        UserRole::Installer => 2,
-        UserRole::Member => 3,
-        UserRole::Admin => 4,
-        UserRole::Owner => 5,
+/// Notifications referring to a conversation (see `conversation_refs`).
+fn conversation_notifications(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Vec<Notification> {
+    match kind {
+        ChatKind::Connection => ctx
+            .db
+            .notification()
+            .notif_by_connection()
+            .filter(&conversation_id)
+            .collect(),
+        ChatKind::Project => ctx
+            .db
+            .notification()
+            .notif_by_project()
+            .filter(&conversation_id)
+            .collect(),
+        ChatKind::Channel => ctx
+            .db
+            .notification()
+            .notif_by_channel()
+            .filter(&conversation_id)
+            .collect(),
     }
 }
 
-/// Finds a user's membership in a specific company, if it exists.
-fn find_membership(
+/// Title of a message notification standing for `count` messages of a
+/// conversation, e.g. "7 new messages in Project X", as seen by `recipient`
+/// reading through `company_id`.
+fn digest_title(
     ctx: &ReducerContext,
-    identity: Identity,
+    kind: ChatKind,
+    conversation_id: u64,
+    recipient: Identity,
     company_id: u64,
-) -> Option<CompanyMember> {
-    ctx.db
-        .company_member()
-        .member_by_identity()
-        .filter(&identity)
-        .find(|m| m.company_id == company_id)
+    count: u32,
+) -> String {
+    let messages = if count == 1 {
+        "1 new message".to_string()
+    } else {
+        format!("{} new messages", count)
+    };
+    let company_name = |cid: u64| {
+        ctx.db
+            .company()
+            .id()
+            .find(cid)
+            .map(|c| c.name)
+            .unwrap_or_default()
+    };
+    match kind {
+        ChatKind::Connection => {
+            let other = ctx
+                .db
+                .company_connection()
+                .id()
+                .find(conversation_id)
+                .map(|c| {
+                    if c.company_a == company_id {
+                        c.company_b
+                    } else {
+                        c.company_a
+                    }
+                })
+                .unwrap_or_default();
+            format!("{} from {}", messages, company_name(other))
+        }
+        ChatKind::Project => {
+            let name = ctx
+                .db
+                .project()
+                .id()
+                .find(conversation_id)
+                .map(|p| p.name)
+                .unwrap_or_default();
+            format!("{} in {}", messages, name)
+        }
+        ChatKind::Channel => match ctx.db.company_channel().id().find(conversation_id) {
+            Some(channel) if channel.is_direct => {
+                let other = ctx
+                    .db
+                    .channel_member()
+                    .channel_member_by_channel()
+                    .filter(&conversation_id)
+                    .find(|m| m.identity != recipient)
+                    .and_then(|m| ctx.db.user_account().identity().find(m.identity))
+                    .map(|a| a.nickname)
+                    .unwrap_or_default();
+                format!("{} from {}", messages, other)
+            }
+            Some(channel) => format!("{} in {}", messages, channel_title(&channel)),
+            None => messages,
+        },
+    }
 }
 
-/// After removing a membership, update the user's `active_company_id` to the
-/// next available membership whose company still exists, or `None` if none remain.
-fn reassign_active_company(ctx: &ReducerContext, identity: Identity, removed_company_id: u64) {
-    if let Some(account) = ctx.db.user_account().identity().find(identity) {
-        if account.active_company_id == Some(removed_company_id) {
-            let next = ctx
-                .db
-                .company_member()
-                .member_by_identity()
-                .filter(&identity)
-                .find(|m| ctx.db.company().id().find(m.company_id).is_some());
-            ctx.db.user_account().identity().update(UserAccount {
-                active_company_id: next.map(|m| m.company_id),
-                ..account
+/// Withdraws the notifications about a chat message that is being deleted,
+/// hidden or purged. A message notification that also stands for other
+/// messages is kept with a lower count; it loses its preview if the preview
+/// was of this message. Unread message notifications only record their latest
+/// message, so those counting this one are found through the conversation:
+/// their recipient has not read up to it and was notified of it.
+fn withdraw_chat_notifications(ctx: &ReducerContext, kind: ChatKind, message_id: u64) {
+    let mut notifications = chat_notifications(ctx, kind, message_id);
+    let Ok(message) = find_chat_message(ctx, kind, message_id) else {
+        for notification in notifications {
+            ctx.db.notification().id().delete(notification.id);
+        }
+        return;
+    };
+    let conversation_id = message.conversation_id;
+
+    // A hidden or deleted message was withdrawn from the counts already
+    if !message.hidden && !message.deleted {
+        let db = &ctx.as_read_only().db;
+        let message_type = chat_notification_types(kind).message;
+        notifications.extend(
+            conversation_notifications(ctx, kind, conversation_id)
+                .into_iter()
+                .filter(|n| {
+                    n.notification_type == message_type
+                        && !n.is_read
+                        && n.message_id > message_id
+                        && n.recipient_identity != message.sender
+                        && !has_blocked(db, n.recipient_identity, message.sender)
+                        && find_read_cursor(ctx, n.recipient_identity, kind, conversation_id)
+                            .is_none_or(|c| c.last_read_message_id < message_id)
+                }),
+        );
+    }
+
+    // Message notifications whose latest message this was fall back to the
+    // latest of the others still visible
+    let previous_id =
+        latest_visible_message_before(&ctx.as_read_only().db, kind, conversation_id, message_id);
+    for notification in notifications {
+        if notification.message_count <= 1 {
+            ctx.db.notification().id().delete(notification.id);
+            continue;
+        }
+        let message_count = notification.message_count - 1;
+        let title = digest_title(
+            ctx,
+            kind,
+            conversation_id,
+            notification.recipient_identity,
+            notification.company_id,
+            message_count,
+        );
+        if notification.message_id == message_id {
+            ctx.db.notification().id().update(Notification {
+                title,
+                body: String::new(),
+                message_id: previous_id.unwrap_or(0),
+                message_count,
+                ..notification
+            });
+        } else {
+            ctx.db.notification().id().update(Notification {
+                title,
+                message_count,
+                ..notification
             });
         }
     }
 }
 
-/// Retrieves the caller's account and verifies they have at least `min_role`
-/// in their active company. Returns the account and the active company ID.
-///
-/// # Errors
-///
-/// Returns an error if:
-/// - The caller has no account.
-/// - The caller has no active company.
-/// - The caller has no membership in their active company.
-/// - The caller's role in that company is below `min_role`.
-fn require_role_at_least(
+/// Marks the caller's chat notifications about a conversation's messages up
+/// to `up_to_message_id` as read, so the next message starts a new count.
+fn read_chat_notifications(
     ctx: &ReducerContext,
-    min_role: UserRole,
-) -> Result<(UserAccount, u64), String> {
-    let account = ctx
-        .db
-        .user_account()
-        .identity()
-        .find(ctx.sender())
-        .ok_or("Not permitted")?;
-
-    let company_id = account
-        .active_company_id
-        .ok_or("Not permitted")?;
-
-    // Verify the company still exists
-    ctx.db
-        .company()
-        .id()
-        .find(company_id)
-        .ok_or("Not permitted")?;
-
-    let membership = find_membership(ctx, ctx.sender(), company_id)
-        .ok_or("Not permitted")?;
-
-    if role_level(membership.role) < role_level(min_role) {
-        return Err(match min_role {
-            UserRole::Owner => "Only the owner can do this".to_string(),
-            UserRole::Admin => "Only admins and owners can do this".to_string(),
-            UserRole::Member | UserRole::Installer | UserRole::Field | UserRole::Pending => {
-                "You do not have permission".to_string()
-            }
+    kind: ChatKind,
+    conversation_id: u64,
+    up_to_message_id: u64,
+) {
+    let types = chat_notification_types(kind);
+    let notifications: Vec<Notification> = conversation_notifications(ctx, kind, conversation_id)
+        .into_iter()
+        .filter(|n| {
+            n.recipient_identity == ctx.sender()
+                && !n.is_read
+                && types.contains(n.notification_type)
+                && n.message_id != 0
+                && n.message_id <= up_to_message_id
+        })
+        .collect();
+    for notification in notifications {
+        ctx.db.notification().id().update(Notification {
+            is_read: true,
+            ..notification
         });
     }
+}
 
-    Ok((account, company_id))
+/// Records which chat message the given notifications are about.
+fn link_notifications_to_message(
+    ctx: &ReducerContext,
+    notification_ids: Vec<u64>,
+    message_id: u64,
+) {
+    for id in notification_ids {
+        if let Some(notification) = ctx.db.notification().id().find(id) {
+            ctx.db.notification().id().update(Notification {
+                message_id,
+                ..notification
+            });
+        }
+    }
 }
 
-/// Finds an existing connection between two companies (order-independent).
-fn find_connection(ctx: &ReducerContext, a: u64, b: u64) -> Option<Connection> {
-    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
-    let result = ctx
+/// Insert a notification for all members of a company holding `permission`,
+/// optionally excluding a specific identity (typically the actor). Returns the
+/// IDs of the inserted notifications.
+#[allow(clippy::too_many_arguments)] // notify's arguments plus the audience
+fn notify_company_permission(
+    ctx: &ReducerContext,
+    company_id: u64,
+    permission: Permission,
+    exclude: Option<Identity>,
+    notification_type: NotificationType,
+    title: String,
+    body: String,
+    refs: NotificationRefs,
+) -> Vec<u64> {
+    let db = &ctx.as_read_only().db;
+    let recipients: Vec<Identity> = ctx
         .db
-        .company_connection()
-        .conn_by_company_a()
-        .filter(&lo)
-        .find(|c| c.company_b == hi);
-    result
+        .company_member()
+        .member_by_company()
+        .filter(&company_id)
+        .filter(|m| has_permission(db, m, permission))
+        .filter(|m| exclude != Some(m.identity))
+        .map(|m| m.identity)
+        .collect();
+
+    recipients
+        .into_iter()
+        .filter_map(|recipient| {
+            notify(
+                ctx,
+                recipient,
+                company_id,
+                notification_type,
+                title.clone(),
+                body.clone(),
+                refs,
+            )
+        })
+        .collect()
 }
 
-/// Deletes all chat messages associated with a connection.
-fn delete_connection_chat(ctx: &ReducerContext, connection_id: u64) {
-    let chat_ids: Vec<u64> = ctx
+/// Notifies the Chat holders of a company about a message in a conversation
+/// (see `notify_in_conversation`). Returns the IDs of the inserted
+/// notifications.
+fn notify_company_chat(
+    ctx: &ReducerContext,
+    company_id: u64,
+    kind: ChatKind,
+    conversation_id: u64,
+    title: String,
+    body: String,
+) -> Vec<u64> {
+    let db = &ctx.as_read_only().db;
+    let recipients: Vec<Identity> = ctx
         .db
-        .connection_chat()
-        .chat_by_connection()
-        .filter(&connection_id)
-        .map(|c| c.id)
+        .company_member()
+        .member_by_company()
+        .filter(&company_id)
+        .filter(|m| has_permission(db, m, Permission::Chat))
+        .map(|m| m.identity)
         .collect();
-    for id in chat_ids {
-        ctx.db.connection_chat().id().delete(id);
-    }
+
+    let notification_type = chat_notification_types(kind).message;
+    recipients
+        .into_iter()
+        .filter_map(|recipient| {
+            notify_in_conversation(
+                ctx,
+                recipient,
+                company_id,
+                notification_type,
+                kind,
+                conversation_id,
+                title.clone(),
+                body.clone(),
+            )
+        })
+        .collect()
 }
 
 // ---------------------------------------------------------------------------
-// Lifecycle
+// Audit helpers
 // ---------------------------------------------------------------------------
 
-#[spacetimedb::reducer(init)]
-pub const fn init(_ctx: &ReducerContext) {}
+/// Record an audit event for an action taken by the caller on behalf of
+/// `company_id`. Also mirrors the event to the module log.
+#[allow(clippy::too_many_arguments)] // Flat row: each target column is independently optional
+fn record_audit(
+    ctx: &ReducerContext,
+    company_id: u64,
+    action: AuditAction,
+    target_identity: Option<Identity>,
+    target_company_id: Option<u64>,
+    target_project_id: Option<u64>,
+    old_value: String,
+    new_value: String,
+) {
+    log::info!(
+        "AUDIT: User {} of Company {} {:?} (user: {:?}, company: {:?}, project: {:?}) '{}' -> '{}'",
+        id_short(ctx.sender()),
+        company_id,
+        action,
+        target_identity.map(id_short),
+        target_company_id,
+        target_project_id,
+        old_value,
+        new_value
+    );
 
-#[spacetimedb::reducer(client_connected)]
-pub fn client_connected(ctx: &ReducerContext) {
-    if let Some(user) = ctx.db.online_user().identity().find(ctx.sender()) {
-        ctx.db
-            .online_user()
-            .identity()
-            .update(OnlineUser { online: true, ..user });
-    } else {
-        ctx.db.online_user().insert(OnlineUser {
-            identity: ctx.sender(),
-            online: true,
-        });
-    }
+    ctx.db.audit_event().insert(AuditEvent {
+        id: 0,
+        actor: ctx.sender(),
+        company_id,
+        action,
+        target_identity,
+        target_company_id,
+        target_project_id,
+        old_value,
+        new_value,
+        created_at: ctx.timestamp,
+        target_user: target_identity.unwrap_or(Identity::ZERO),
+    });
 }
 
-#[spacetimedb::reducer(client_disconnected)]
-pub fn client_disconnected(ctx: &ReducerContext) {
-    if let Some(user) = ctx.db.online_user().identity().find(ctx.sender()) {
+/// Replaces `identity` as actor and target of every audit event with
+/// `Identity::ZERO`, like the sender of their chat messages.
+fn anonymise_audit_events(ctx: &ReducerContext, identity: Identity) {
+    let acted: Vec<AuditEvent> = ctx
+        .db
+        .audit_event()
+        .audit_by_actor()
+        .filter(&identity)
+        .collect();
+    for event in acted {
+        ctx.db.audit_event().id().update(AuditEvent {
+            actor: Identity::ZERO,
+            ..event
+        });
+    }
+    let targeted: Vec<AuditEvent> = ctx
+        .db
+        .audit_event()
+        .audit_by_target_user()
+        .filter(&identity)
+        .collect();
+    for event in targeted {
+        ctx.db.audit_event().id().update(AuditEvent {
+            target_identity: Some(Identity::ZERO),
+            target_user: Identity::ZERO,
+            ..event
+        });
+    }
+    let platform_acted: Vec<PlatformAuditEvent> = ctx
+        .db
+        .platform_audit_event()
+        .platform_audit_by_actor()
+        .filter(&identity)
+        .collect();
+    for event in platform_acted {
         ctx.db
-            .online_user()
-            .identity()
-            .update(OnlineUser { online: false, ..user });
+            .platform_audit_event()
+            .id()
+            .update(PlatformAuditEvent {
+                actor: Identity::ZERO,
+                ..event
+            });
     }
 }
 
+/// Actions recorded in the platform trail rather than a company's.
+const PLATFORM_AUDIT_ACTIONS: [AuditAction; 11] = [
+    AuditAction::CompanyDeleted,
+    AuditAction::AccountDeleted,
+    AuditAction::MessageHidden,
+    AuditAction::MessageUnhidden,
+    AuditAction::ModeratorAppointed,
+    AuditAction::ModeratorRemoved,
+    AuditAction::RateLimitsChanged,
+    AuditAction::CompanyDeletionGraceChanged,
+    AuditAction::StorageQuotaChanged,
+    AuditAction::ChatSearchIndexRebuilt,
+    AuditAction::PlatformOwnerChanged,
+];
+
+/// Record an audit event in the platform trail, for actions that outlive the
+/// company they concern and moderator decisions (one of
+/// `PLATFORM_AUDIT_ACTIONS`). Also mirrors the event to the module log.
+fn record_platform_audit(
+    ctx: &ReducerContext,
+    actor: Identity,
+    action: AuditAction,
+    target_identity: Option<Identity>,
+    target_company_id: Option<u64>,
+    old_value: String,
+    new_value: String,
+) {
+    log::info!(
+        "AUDIT: User {} {:?} (user: {:?}, company: {:?}) '{}' -> '{}'",
+        id_short(actor),
+        action,
+        target_identity.map(id_short),
+        target_company_id,
+        old_value,
+        new_value
+    );
+
+    ctx.db.platform_audit_event().insert(PlatformAuditEvent {
+        id: 0,
+        actor,
+        action,
+        target_identity,
+        target_company_id,
+        old_value,
+        new_value,
+        created_at: ctx.timestamp,
+    });
+}
+
 // ---------------------------------------------------------------------------
-// Phase 1 — Onboarding
+// Project helpers
 // ---------------------------------------------------------------------------
 
-/// Register a new user account for the calling identity.
-///
-/// # Errors
-///
-/// Returns an error if any field is empty or the account already exists.
-#[spacetimedb::reducer]
-#[allow(clippy::needless_pass_by_value)]
-pub fn create_account(
+fn find_project_membership(
     ctx: &ReducerContext,
-    full_name: String,
-    nickname: String,
-    email: String,
-) -> Result<(), String> {
-    let full_name = full_name.trim().to_string();
-    let nickname = nickname.trim().to_string();
-    let email = email.trim().to_string();
+    project_id: u64,
+    company_id: u64,
+    status: ProjectMemberStatus,
+) -> Option<ProjectMember> {
+    ctx.db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .find(|m| m.company_id == company_id && m.status == status)
+}
+
+/// Finds a user's assignment in a project, if any.
+fn find_project_participant(
+    db: &LocalReadOnly,
+    project_id: u64,
+    identity: Identity,
+) -> Option<ProjectParticipant> {
+    db.project_participant()
+        .pp_by_identity()
+        .filter(&identity)
+        .find(|p| p.project_id == project_id)
+}
+
+/// Verifies the caller may assign their active company's participants in a
+/// project: they hold ManageProjects, or are a Lead of the project through
+/// that company. Returns the active company ID and whether the caller holds
+/// ManageProjects.
+fn require_project_staffing(ctx: &ReducerContext, project_id: u64) -> Result<(u64, bool), String> {
+    let (_account, company_id, membership) = require_active_membership(ctx)?;
+    let db = &ctx.as_read_only().db;
+    if has_permission(db, &membership, Permission::ManageProjects) {
+        return Ok((company_id, true));
+    }
+    find_project_participant(db, project_id, ctx.sender())
+        .filter(|p| p.company_id == company_id && p.role == ProjectRole::Lead)
+        .map(|_| (company_id, false))
+        .ok_or_else(|| "You do not have permission to manage projects".to_string())
+}
+
+/// Deletes a company's participant rows in a project (the company left, was
+/// kicked, or is being deleted).
+fn delete_company_participants(ctx: &ReducerContext, project_id: u64, company_id: u64) {
+    let participant_ids: Vec<u64> = ctx
+        .db
+        .project_participant()
+        .pp_by_project()
+        .filter(&project_id)
+        .filter(|p| p.company_id == company_id)
+        .map(|p| p.id)
+        .collect();
+    for id in participant_ids {
+        ctx.db.project_participant().id().delete(id);
+    }
+}
+
+/// Deletes every project assignment, channel membership and notification
+/// preference a user holds through `company_id` (they left or were removed
+/// from that company).
+fn delete_member_participations(ctx: &ReducerContext, identity: Identity, company_id: u64) {
+    let participant_ids: Vec<u64> = ctx
+        .db
+        .project_participant()
+        .pp_by_identity()
+        .filter(&identity)
+        .filter(|p| p.company_id == company_id)
+        .map(|p| p.id)
+        .collect();
+    for id in participant_ids {
+        ctx.db.project_participant().id().delete(id);
+    }
+
+    let channel_memberships: Vec<ChannelMember> = ctx
+        .db
+        .channel_member()
+        .channel_member_by_identity()
+        .filter(&identity)
+        .filter(|m| {
+            ctx.db
+                .company_channel()
+                .id()
+                .find(m.channel_id)
+                .is_some_and(|c| c.company_id == company_id)
+        })
+        .collect();
+    for membership in &channel_memberships {
+        remove_channel_membership(ctx, membership);
+    }
+
+    let preference_ids: Vec<u64> = ctx
+        .db
+        .notification_preference()
+        .pref_by_identity()
+        .filter(&identity)
+        .filter(|p| p.company_id == company_id)
+        .map(|p| p.id)
+        .collect();
+    for id in preference_ids {
+        ctx.db.notification_preference().id().delete(id);
+    }
+}
+
+/// Sends a chat notification to every participant of a project (except
+/// `exclude`) who still holds Chat in the company they were assigned through
+/// (see `notify_in_conversation`). Returns the IDs of the inserted
+/// notifications.
+fn notify_project_participants(
+    ctx: &ReducerContext,
+    project_id: u64,
+    exclude: Identity,
+    notification_type: NotificationType,
+    title: String,
+    body: String,
+) -> Vec<u64> {
+    let db = &ctx.as_read_only().db;
+    let recipients: Vec<(Identity, u64)> = ctx
+        .db
+        .project_participant()
+        .pp_by_project()
+        .filter(&project_id)
+        .filter(|p| p.identity != exclude)
+        .filter(|p| {
+            find_membership(ctx, p.identity, p.company_id)
+                .is_some_and(|m| has_permission(db, &m, Permission::Chat))
+        })
+        .map(|p| (p.identity, p.company_id))
+        .collect();
+
+    recipients
+        .into_iter()
+        .filter_map(|(recipient, company_id)| {
+            notify_in_conversation(
+                ctx,
+                recipient,
+                company_id,
+                notification_type,
+                ChatKind::Project,
+                project_id,
+                title.clone(),
+                body.clone(),
+            )
+        })
+        .collect()
+}
+
+fn delete_project_cascade(ctx: &ReducerContext, project_id: u64) {
+    // 1. Archive the chat for its current participants
+    let project_name = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .map(|p| p.name)
+        .unwrap_or_default();
+    let company_ids: Vec<u64> = ctx
+        .db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .filter(|m| m.status == ProjectMemberStatus::Accepted)
+        .map(|m| m.company_id)
+        .collect();
+    archive_conversation(
+        ctx,
+        ChatKind::Project,
+        project_id,
+        project_name,
+        company_ids,
+    );
+
+    // 2. Delete all members and participants
+    let participant_ids: Vec<u64> = ctx
+        .db
+        .project_participant()
+        .pp_by_project()
+        .filter(&project_id)
+        .map(|p| p.id)
+        .collect();
+    for id in participant_ids {
+        ctx.db.project_participant().id().delete(id);
+    }
+    let member_ids: Vec<u64> = ctx
+        .db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .map(|m| m.id)
+        .collect();
+    for id in member_ids {
+        ctx.db.project_member().id().delete(id);
+    }
+
+    // 3. Delete notifications referring to this project
+    delete_project_notifications(ctx, project_id);
+
+    // 4. Delete the project row
+    ctx.db.project().id().delete(project_id);
+}
+
+// ---------------------------------------------------------------------------
+// Invitation helpers
+// ---------------------------------------------------------------------------
+
+/// Returns the lowercase e-mail on file for `identity`, if any.
+fn contact_email(ctx: &ReducerContext, identity: Identity) -> Option<String> {
+    ctx.db
+        .user_contact()
+        .identity()
+        .find(identity)
+        .map(|c| c.email.to_lowercase())
+}
+
+/// Returns the lowercase e-mail on file for `identity` if it is verified,
+/// i.e. allowed to claim personal invitations.
+fn verified_contact_email(db: &LocalReadOnly, identity: Identity) -> Option<String> {
+    db.user_contact()
+        .identity()
+        .find(identity)
+        .filter(|c| c.email_verified)
+        .map(|c| c.email.to_lowercase())
+}
+
+/// The caller's e-mail address as vouched for by their identity provider:
+/// the `email` claim of their token, if `email_verified` is true.
+fn verified_email(ctx: &ReducerContext) -> Option<String> {
+    ctx.sender_auth()
+        .jwt()
+        .and_then(|jwt| verified_email_claim(jwt.raw_payload()))
+}
+
+/// Extracts the lowercase `email` claim from a JWT payload, provided the
+/// payload also carries `"email_verified": true`.
+fn verified_email_claim(payload: &str) -> Option<String> {
+    let claims: serde_json::Value = serde_json::from_str(payload).ok()?;
+    if claims
+        .get("email_verified")
+        .and_then(serde_json::Value::as_bool)
+        != Some(true)
+    {
+        return None;
+    }
+    claims
+        .get("email")
+        .and_then(serde_json::Value::as_str)
+        .map(|email| email.trim().to_lowercase())
+}
+
+/// Links the caller to the invitation's company with the invited role and
+/// consumes the invitation. If the caller is already a member, the
+/// invitation is consumed without changing their role.
+fn link_personal_invitation(ctx: &ReducerContext, invitation: &PersonalInvitation) {
+    ctx.db.personal_invitation().id().delete(invitation.id);
+
+    if find_membership(ctx, ctx.sender(), invitation.company_id).is_some() {
+        return;
+    }
+
+    ctx.db.company_member().insert(CompanyMember {
+        id: 0,
+        identity: ctx.sender(),
+        company_id: invitation.company_id,
+        role: invitation.role,
+        joined_at: ctx.timestamp,
+        custom_role_id: None,
+    });
+
+    let Some(account) = ctx.db.user_account().identity().find(ctx.sender()) else {
+        return;
+    };
+    let joiner_name = account.nickname.clone();
+
+    if account.active_company_id.is_none() {
+        ctx.db.user_account().identity().update(UserAccount {
+            active_company_id: Some(invitation.company_id),
+            ..account
+        });
+    }
+
+    let company_name = ctx
+        .db
+        .company()
+        .id()
+        .find(invitation.company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_default();
+    notify_company_permission(
+        ctx,
+        invitation.company_id,
+        Permission::ManageMembers,
+        Some(ctx.sender()),
+        NotificationType::MemberJoined,
+        "New member joined".to_string(),
+        format!("{} joined {}", joiner_name, company_name),
+        NotificationRefs::default(),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Permission helpers
+// ---------------------------------------------------------------------------
+
+/// Returns a numeric level for role comparison. Higher = more privileged.
+const fn role_level(role: UserRole) -> u8 {
+    match role {
+        UserRole::Pending => 0,
+        UserRole::Field => 1,
+        UserRole::Installer => 2,
+        UserRole::Member => 3,
+        UserRole::Admin => 4,
+        UserRole::Owner => 5,
+    }
+}
+
+/// Looks up a company that is not scheduled for deletion. Companies pending
+/// deletion are hidden from everyone but their own members and are read-only.
+fn find_live_company(db: &LocalReadOnly, company_id: u64) -> Option<Company> {
+    db.company()
+        .id()
+        .find(company_id)
+        .filter(|c| c.purge_at.is_none())
+}
+
+/// Whether `identity` can see `company`: live companies are visible to
+/// everyone, companies pending deletion only to their own members.
+fn company_visible_to(db: &LocalReadOnly, identity: Identity, company: &Company) -> bool {
+    company.purge_at.is_none()
+        || db
+            .company_member()
+            .member_by_identity()
+            .filter(&identity)
+            .any(|m| m.company_id == company.id)
+}
+
+/// Finds a user's membership in a specific company, if it exists.
+fn find_membership(
+    ctx: &ReducerContext,
+    identity: Identity,
+    company_id: u64,
+) -> Option<CompanyMember> {
+    ctx.db
+        .company_member()
+        .member_by_identity()
+        .filter(&identity)
+        .find(|m| m.company_id == company_id)
+}
+
+/// Every permission, in declaration order.
+const ALL_PERMISSIONS: [Permission; 7] = [
+    Permission::ManageMembers,
+    Permission::ManageRoles,
+    Permission::ManageConnections,
+    Permission::ManageProjects,
+    Permission::Chat,
+    Permission::EditProfile,
+    Permission::ViewAuditLog,
+];
+
+/// Permissions seeded for each tier when a company is created. These match
+/// the fixed Admin+ / Field+ checks that predate configurable roles.
+fn default_permissions(tier: UserRole) -> Vec<Permission> {
+    match tier {
+        UserRole::Owner | UserRole::Admin => ALL_PERMISSIONS.to_vec(),
+        UserRole::Member | UserRole::Installer | UserRole::Field => vec![Permission::Chat],
+        UserRole::Pending => Vec::new(),
+    }
+}
+
+/// Effective permissions of a membership. The Owner always holds every
+/// permission and Pending members hold none. Otherwise the member's custom
+/// role applies if assigned, else the company's role for their tier (or the
+/// tier defaults if the company has no seeded roles).
+fn member_permissions(db: &LocalReadOnly, membership: &CompanyMember) -> Vec<Permission> {
+    match membership.role {
+        UserRole::Owner => return ALL_PERMISSIONS.to_vec(),
+        UserRole::Pending => return Vec::new(),
+        UserRole::Admin | UserRole::Member | UserRole::Installer | UserRole::Field => {}
+    }
+
+    if let Some(custom) = membership
+        .custom_role_id
+        .and_then(|id| db.company_role().id().find(id))
+    {
+        return custom.permissions;
+    }
+
+    db.company_role()
+        .role_by_company()
+        .filter(&membership.company_id)
+        .find(|r| r.tier == Some(membership.role))
+        .map_or_else(|| default_permissions(membership.role), |r| r.permissions)
+}
+
+/// Whether a membership grants `permission`.
+fn has_permission(db: &LocalReadOnly, membership: &CompanyMember, permission: Permission) -> bool {
+    member_permissions(db, membership).contains(&permission)
+}
+
+/// IDs of all companies where `identity` holds `permission`. The read-only
+/// counterpart of `require_permission`, used by views.
+fn companies_with_permission(
+    db: &LocalReadOnly,
+    identity: Identity,
+    permission: Permission,
+) -> Vec<u64> {
+    db.company_member()
+        .member_by_identity()
+        .filter(&identity)
+        .filter(|m| has_permission(db, m, permission))
+        .map(|m| m.company_id)
+        .collect()
+}
+
+/// After removing a membership, update the user's `active_company_id` to the
+/// next available membership whose company still exists, or `None` if none remain.
+fn reassign_active_company(ctx: &ReducerContext, identity: Identity, removed_company_id: u64) {
+    if let Some(account) = ctx.db.user_account().identity().find(identity) {
+        if account.active_company_id == Some(removed_company_id) {
+            let next = ctx
+                .db
+                .company_member()
+                .member_by_identity()
+                .filter(&identity)
+                .find(|m| ctx.db.company().id().find(m.company_id).is_some());
+            ctx.db.user_account().identity().update(UserAccount {
+                active_company_id: next.map(|m| m.company_id),
+                ..account
+            });
+        }
+    }
+}
+
+/// Removes a membership the user gives up voluntarily and moves their active
+/// company on. Shared by `leave_company` and `delete_account`.
+fn leave_membership(ctx: &ReducerContext, membership: &CompanyMember) {
+    ctx.db.company_member().id().delete(membership.id);
+    delete_member_participations(ctx, membership.identity, membership.company_id);
+    reassign_active_company(ctx, membership.identity, membership.company_id);
+}
+
+/// Retrieves the caller's account and their membership in their active
+/// company. Shared by `require_role_at_least` and `require_permission`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The caller has no account.
+/// - The caller has no active company.
+/// - The caller's active company is scheduled for deletion.
+/// - The caller's active company is suspended by a platform moderator.
+/// - The caller has no membership in their active company.
+fn require_active_membership(
+    ctx: &ReducerContext,
+) -> Result<(UserAccount, u64, CompanyMember), String> {
+    let account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Not permitted")?;
+
+    let company_id = account
+        .active_company_id
+        .ok_or("Not permitted")?;
+
+    // Verify the company still exists and is not pending deletion (read-only)
+    let company = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .ok_or("Not permitted")?;
+    if company.purge_at.is_some() {
+        return Err("Company is scheduled for deletion".to_string());
+    }
+    if ctx
+        .db
+        .company_suspension()
+        .company_id()
+        .find(company_id)
+        .is_some()
+    {
+        return Err("Company is suspended".to_string());
+    }
+
+    let membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+
+    Ok((account, company_id, membership))
+}
+
+/// Retrieves the caller's account and verifies they have at least `min_role`
+/// in their active company. Returns the account and the active company ID.
+/// Used for Owner-only actions; everything else goes through
+/// `require_permission`.
+///
+/// # Errors
+///
+/// Returns an error if `require_active_membership` fails or the caller's
+/// role in that company is below `min_role`.
+fn require_role_at_least(
+    ctx: &ReducerContext,
+    min_role: UserRole,
+) -> Result<(UserAccount, u64), String> {
+    let (account, company_id, membership) = require_active_membership(ctx)?;
+
+    if role_level(membership.role) < role_level(min_role) {
+        return Err(match min_role {
+            UserRole::Owner => "Only the owner can do this".to_string(),
+            UserRole::Admin => "Only admins and owners can do this".to_string(),
+            UserRole::Member | UserRole::Installer | UserRole::Field | UserRole::Pending => {
+                "You do not have permission".to_string()
+            }
+        });
+    }
+
+    Ok((account, company_id))
+}
+
+/// Retrieves the caller's account and verifies their membership in their
+/// active company grants `permission`. Returns the account and the active
+/// company ID.
+///
+/// # Errors
+///
+/// Returns an error if `require_active_membership` fails or the caller's
+/// role does not grant `permission`.
+fn require_permission(
+    ctx: &ReducerContext,
+    permission: Permission,
+) -> Result<(UserAccount, u64), String> {
+    let (account, company_id, membership) = require_active_membership(ctx)?;
+
+    if !has_permission(&ctx.as_read_only().db, &membership, permission) {
+        return Err(match permission {
+            Permission::ManageMembers => "You do not have permission to manage members",
+            Permission::ManageRoles => "You do not have permission to manage roles",
+            Permission::ManageConnections => "You do not have permission to manage connections",
+            Permission::ManageProjects => "You do not have permission to manage projects",
+            Permission::Chat => "You do not have permission to chat",
+            Permission::EditProfile => "You do not have permission to edit the company profile",
+            Permission::ViewAuditLog => "You do not have permission to view the audit log",
+        }
+        .to_string());
+    }
+
+    Ok((account, company_id))
+}
+
+/// Finds an existing connection between two companies (order-independent).
+fn find_connection(ctx: &ReducerContext, a: u64, b: u64) -> Option<Connection> {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    let result = ctx
+        .db
+        .company_connection()
+        .conn_by_company_a()
+        .filter(&lo)
+        .find(|c| c.company_b == hi);
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Lifecycle
+// ---------------------------------------------------------------------------
+
+/// A data migration, run once per database (see `SCHEMA_MIGRATIONS`).
+type Migration = fn(&ReducerContext);
+
+/// One-off data migrations, applied in order by `run_schema_migrations`.
+///
+/// Republishing over existing data (without `--delete-data`) can only add
+/// tables, indexes, enum variants and trailing columns that carry a
+/// `#[default]`; columns are never removed, reordered or retyped. Changes
+/// that have to rewrite or backfill existing rows are added here instead.
+/// Append new entries and never rename or remove old ones: the name is what
+/// marks a migration as applied.
+const SCHEMA_MIGRATIONS: &[(&str, Migration)] = &[
+    ("move_emails_to_user_contact", migrate_user_contacts),
+    ("seed_project_participants", migrate_project_participants),
+    ("count_conversation_messages", migrate_conversation_stats),
+    ("index_mention_handles", migrate_mention_handles),
+    ("index_chat_messages", migrate_chat_search_index),
+];
+
+/// Applies every migration of `SCHEMA_MIGRATIONS` not yet recorded in
+/// `schema_migration`. Runs from `init`, `client_connected` and the scheduled
+/// reducers. Clients that stayed connected across a republish can still call
+/// reducers before that, so each migration must be safe to apply over rows
+/// those calls already wrote.
+fn run_schema_migrations(ctx: &ReducerContext) {
+    for (name, migrate) in SCHEMA_MIGRATIONS {
+        if ctx
+            .db
+            .schema_migration()
+            .name()
+            .find(name.to_string())
+            .is_some()
+        {
+            continue;
+        }
+        migrate(ctx);
+        ctx.db.schema_migration().insert(SchemaMigration {
+            name: (*name).to_string(),
+            applied_at: ctx.timestamp,
+        });
+        log::info!("Applied schema migration {}", name);
+    }
+}
+
+/// Moves e-mail addresses from the legacy `UserAccount.email` column into
+/// `UserContact` and clears the column. A contact row that already exists was
+/// written since the move and is newer, so it is kept.
+fn migrate_user_contacts(ctx: &ReducerContext) {
+    let accounts: Vec<UserAccount> = ctx
+        .db
+        .user_account()
+        .iter()
+        .filter(|a| !a.email.is_empty())
+        .collect();
+    for account in accounts {
+        if ctx
+            .db
+            .user_contact()
+            .identity()
+            .find(account.identity)
+            .is_none()
+        {
+            ctx.db.user_contact().insert(UserContact {
+                identity: account.identity,
+                email: account.email.clone(),
+                email_verified: false,
+            });
+        }
+        ctx.db.user_account().identity().update(UserAccount {
+            email: String::new(),
+            ..account
+        });
+    }
+}
+
+/// Records the mention handle of every existing account.
+fn migrate_mention_handles(ctx: &ReducerContext) {
+    let accounts: Vec<UserAccount> = ctx.db.user_account().iter().collect();
+    for account in accounts {
+        set_mention_handle(ctx, account.identity, &account.nickname);
+    }
+}
+
+/// Indexes the chat messages sent before chat search existed (see
+/// `index_chat_message`).
+fn migrate_chat_search_index(ctx: &ReducerContext) {
+    let connection_messages: Vec<(u64, String)> = ctx
+        .db
+        .connection_chat()
+        .iter()
+        .filter(|m| !m.deleted && m.message_kind == MessageKind::User)
+        .map(|m| (m.id, m.text))
+        .collect();
+    let project_messages: Vec<(u64, String)> = ctx
+        .db
+        .project_chat()
+        .iter()
+        .filter(|m| !m.deleted && m.message_kind == MessageKind::User)
+        .map(|m| (m.id, m.text))
+        .collect();
+    let channel_messages: Vec<(u64, String)> = ctx
+        .db
+        .channel_chat()
+        .iter()
+        .filter(|m| !m.deleted && m.message_kind == MessageKind::User)
+        .map(|m| (m.id, m.text))
+        .collect();
+    for (id, text) in connection_messages {
+        index_chat_message(ctx, ChatKind::Connection, id, &text);
+    }
+    for (id, text) in project_messages {
+        index_chat_message(ctx, ChatKind::Project, id, &text);
+    }
+    for (id, text) in channel_messages {
+        index_chat_message(ctx, ChatKind::Channel, id, &text);
+    }
+}
+
+/// Makes the platform owner (see `PlatformConfig`) a moderator while the
+/// platform has none, e.g. once the last moderator deleted their account.
+fn bootstrap_platform_moderator(ctx: &ReducerContext) {
+    if ctx.db.platform_moderator().count() > 0 {
+        return;
+    }
+    let owner = platform_config(ctx).owner_identity;
+    if owner == Identity::ZERO {
+        return;
+    }
+    ctx.db.platform_moderator().insert(PlatformModerator {
+        identity: owner,
+        added_by: owner,
+        added_at: ctx.timestamp,
+    });
+    record_platform_audit(
+        ctx,
+        owner,
+        AuditAction::ModeratorAppointed,
+        Some(owner),
+        None,
+        String::new(),
+        "platform owner".to_string(),
+    );
+}
+
+/// Assigns participants to the projects that predate `ProjectParticipant`,
+/// where every active member of an Accepted company could chat: the creator
+/// becomes Lead and the other members of those companies Contributors.
+/// Members assigned before the migration ran keep their assignment; a project
+/// that already has some participants still gets the rest, so one early
+/// assignment does not lock its colleagues out.
+fn migrate_project_participants(ctx: &ReducerContext) {
+    let projects: Vec<Project> = ctx.db.project().iter().collect();
+    for project in projects {
+        let companies: Vec<u64> = ctx
+            .db
+            .project_member()
+            .pm_by_project()
+            .filter(&project.id)
+            .filter(|m| m.status == ProjectMemberStatus::Accepted)
+            .map(|m| m.company_id)
+            .collect();
+        for company_id in companies {
+            let members: Vec<CompanyMember> = ctx
+                .db
+                .company_member()
+                .member_by_company()
+                .filter(&company_id)
+                .filter(|m| m.role != UserRole::Pending)
+                .collect();
+            for member in members {
+                if find_project_participant(&ctx.as_read_only().db, project.id, member.identity)
+                    .is_some()
+                {
+                    continue;
+                }
+                let is_creator =
+                    member.identity == project.created_by && company_id == project.owner_company_id;
+                ctx.db.project_participant().insert(ProjectParticipant {
+                    id: 0,
+                    project_id: project.id,
+                    company_id,
+                    identity: member.identity,
+                    role: if is_creator {
+                        ProjectRole::Lead
+                    } else {
+                        ProjectRole::Contributor
+                    },
+                    assigned_by: project.created_by,
+                    assigned_at: ctx.timestamp,
+                });
+            }
+        }
+    }
+}
+
+/// Counts the visible messages of every conversation into
+/// `ConversationStats`, from scratch, and recounts the read cursors against
+/// them.
+fn migrate_conversation_stats(ctx: &ReducerContext) {
+    // Messages sent before the migration ran were counted already
+    let stale: Vec<u64> = ctx.db.conversation_stats().iter().map(|s| s.id).collect();
+    for id in stale {
+        ctx.db.conversation_stats().id().delete(id);
+    }
+
+    let mut messages: Vec<(ChatKind, u64, u64, bool)> = ctx
+        .db
+        .connection_chat()
+        .iter()
+        .map(|m| {
+            (
+                ChatKind::Connection,
+                m.connection_id,
+                m.id,
+                !m.hidden && !m.deleted,
+            )
+        })
+        .collect();
+    messages.extend(ctx.db.project_chat().iter().map(|m| {
+        (
+            ChatKind::Project,
+            m.project_id,
+            m.id,
+            !m.hidden && !m.deleted,
+        )
+    }));
+    messages.extend(ctx.db.channel_chat().iter().map(|m| {
+        (
+            ChatKind::Channel,
+            m.channel_id,
+            m.id,
+            !m.hidden && !m.deleted,
+        )
+    }));
+    for (kind, conversation_id, message_id, visible) in messages {
+        let stats = find_conversation_stats(&ctx.as_read_only().db, kind, conversation_id)
+            .unwrap_or_else(|| {
+                ctx.db.conversation_stats().insert(ConversationStats {
+                    id: 0,
+                    kind,
+                    conversation_id,
+                    message_count: 0,
+                    last_message_id: 0,
+                })
+            });
+        ctx.db.conversation_stats().id().update(ConversationStats {
+            message_count: stats.message_count + u64::from(visible),
+            last_message_id: stats.last_message_id.max(message_id),
+            ..stats
+        });
+    }
+
+    let cursors: Vec<ReadCursor> = ctx.db.read_cursor().iter().collect();
+    for cursor in cursors {
+        place_read_cursor(
+            ctx,
+            cursor.identity,
+            cursor.kind,
+            cursor.conversation_id,
+            cursor.last_read_message_id,
+            cursor.last_read_at,
+        );
+    }
+}
+
+/// Seeds the publishing identity as the platform owner and first platform
+/// moderator.
+#[spacetimedb::reducer(init)]
+pub fn init(ctx: &ReducerContext) {
+    save_platform_config(
+        ctx,
+        PlatformConfig {
+            owner_identity: ctx.sender(),
+            ..platform_config(ctx)
+        },
+    );
+    ctx.db.platform_moderator().insert(PlatformModerator {
+        identity: ctx.sender(),
+        added_by: ctx.sender(),
+        added_at: ctx.timestamp,
+    });
+    run_schema_migrations(ctx);
+}
+
+#[spacetimedb::reducer(client_connected)]
+pub fn client_connected(ctx: &ReducerContext) {
+    run_schema_migrations(ctx);
+    bootstrap_platform_moderator(ctx);
+    if let Some(user) = ctx.db.online_user().identity().find(ctx.sender()) {
+        ctx.db
+            .online_user()
+            .identity()
+            .update(OnlineUser { online: true, ..user });
+    } else {
+        ctx.db.online_user().insert(OnlineUser {
+            identity: ctx.sender(),
+            online: true,
+        });
+    }
+}
+
+#[spacetimedb::reducer(client_disconnected)]
+pub fn client_disconnected(ctx: &ReducerContext) {
+    if let Some(user) = ctx.db.online_user().identity().find(ctx.sender()) {
+        ctx.db
+            .online_user()
+            .identity()
+            .update(OnlineUser { online: false, ..user });
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Visibility (views over private tables)
+// ---------------------------------------------------------------------------
+
+/// Conversations whose chat `identity` can read, sorted: connections of
+/// companies where they hold Chat, projects they participate in through such
+/// a company, or channels of such a company they are a member of, plus
+/// archived ones they can still read.
+fn visible_conversation_ids(db: &LocalReadOnly, identity: Identity, kind: ChatKind) -> Vec<u64> {
+    let chat_companies = companies_with_permission(db, identity, Permission::Chat);
+    let mut ids: Vec<u64> = match kind {
+        ChatKind::Connection => chat_companies
+            .iter()
+            .flat_map(|cid| {
+                db.company_connection()
+                    .conn_by_company_a()
+                    .filter(cid)
+                    .chain(db.company_connection().conn_by_company_b().filter(cid))
+            })
+            // Connections with a partner pending deletion are hidden
+            .filter(|c| {
+                [c.company_a, c.company_b].iter().all(|id| {
+                    db.company()
+                        .id()
+                        .find(id)
+                        .is_some_and(|company| company_visible_to(db, identity, &company))
+                })
+            })
+            .map(|c| c.id)
+            .collect(),
+        ChatKind::Project => db
+            .project_participant()
+            .pp_by_identity()
+            .filter(&identity)
+            .filter(|p| chat_companies.contains(&p.company_id))
+            .map(|p| p.project_id)
+            .collect(),
+        ChatKind::Channel => db
+            .channel_member()
+            .channel_member_by_identity()
+            .filter(&identity)
+            .filter_map(|m| db.company_channel().id().find(m.channel_id))
+            .filter(|c| chat_companies.contains(&c.company_id))
+            .map(|c| c.id)
+            .collect(),
+    };
+    ids.extend(
+        readable_archives(db, identity, kind)
+            .iter()
+            .map(|a| a.conversation_id),
+    );
+    // A connection between two of the caller's own companies shows up twice
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Chat messages of every connection one of `identity`'s companies is part of,
+/// for companies where they hold the Chat permission (matching
+/// `send_connection_chat`), and of archived connections they can still read.
+/// Messages from users they blocked are left out.
+fn visible_connection_chat(db: &LocalReadOnly, identity: Identity) -> Vec<ConnectionChat> {
+    let blocked = blocked_by(db, identity);
+    visible_conversation_ids(db, identity, ChatKind::Connection)
+        .iter()
+        .flat_map(|conn_id| db.connection_chat().chat_by_connection().filter(conn_id))
+        .filter(|c| !c.hidden && !blocked.contains(&c.sender))
+        .collect()
+}
+
+/// Chat messages of every project `identity` participates in, through a
+/// company where they hold the Chat permission (matching `send_project_chat`),
+/// and of archived projects they can still read. Messages from users they
+/// blocked are left out.
+fn visible_project_chat(db: &LocalReadOnly, identity: Identity) -> Vec<ProjectChat> {
+    let blocked = blocked_by(db, identity);
+    visible_conversation_ids(db, identity, ChatKind::Project)
+        .iter()
+        .flat_map(|pid| db.project_chat().pchat_by_project().filter(pid))
+        .filter(|c| !c.hidden && !blocked.contains(&c.sender))
+        .collect()
+}
+
+/// Messages of every channel and direct conversation `identity` is a member
+/// of, in companies where they hold the Chat permission (matching
+/// `send_channel_chat`). Messages from users they blocked are left out.
+fn visible_channel_chat(db: &LocalReadOnly, identity: Identity) -> Vec<ChannelChat> {
+    let blocked = blocked_by(db, identity);
+    visible_conversation_ids(db, identity, ChatKind::Channel)
+        .iter()
+        .flat_map(|cid| db.channel_chat().channel_chat_by_channel().filter(cid))
+        .filter(|c| !c.hidden && !blocked.contains(&c.sender))
+        .collect()
+}
+
+/// Channels `identity` can see, sorted: those they can read (see
+/// `visible_conversation_ids`) plus every channel other than direct
+/// conversations of the companies where they hold ManageMembers.
+fn visible_channel_ids(db: &LocalReadOnly, identity: Identity) -> Vec<u64> {
+    let mut ids = visible_conversation_ids(db, identity, ChatKind::Channel);
+    for cid in companies_with_permission(db, identity, Permission::ManageMembers) {
+        ids.extend(
+            db.company_channel()
+                .channel_by_company()
+                .filter(&cid)
+                .filter(|c| !c.is_direct)
+                .map(|c| c.id),
+        );
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Companies the caller can see: every company except those pending deletion,
+/// which only their own members still see.
+fn visible_companies_for(db: &LocalReadOnly, identity: Identity) -> Vec<Company> {
+    [true, false]
+        .iter()
+        .flat_map(|is_public| db.company().company_by_public().filter(is_public))
+        .filter(|c| company_visible_to(db, identity, c))
+        .collect()
+}
+
+/// The company directory as seen by the caller (see `visible_companies_for`).
+#[spacetimedb::view(accessor = visible_companies, public)]
+pub fn visible_companies(ctx: &ViewContext) -> Vec<Company> {
+    visible_companies_for(&ctx.db, ctx.sender())
+}
+
+/// Capabilities of the companies in `visible_companies`.
+#[spacetimedb::view(accessor = visible_capabilities, public)]
+pub fn visible_capabilities(ctx: &ViewContext) -> Vec<Capability> {
+    let db = &ctx.db;
+    visible_companies_for(db, ctx.sender())
+        .iter()
+        .filter_map(|c| db.capability().company_id().find(c.id))
+        .collect()
+}
+
+/// Connection chat visible to the caller (see `visible_connection_chat`).
+#[spacetimedb::view(accessor = my_connection_chat, public)]
+pub fn my_connection_chat(ctx: &ViewContext) -> Vec<ConnectionChat> {
+    visible_connection_chat(&ctx.db, ctx.sender())
+}
+
+/// Project chat visible to the caller (see `visible_project_chat`).
+#[spacetimedb::view(accessor = my_project_chat, public)]
+pub fn my_project_chat(ctx: &ViewContext) -> Vec<ProjectChat> {
+    visible_project_chat(&ctx.db, ctx.sender())
+}
+
+/// Channel and direct message chat visible to the caller (see
+/// `visible_channel_chat`).
+#[spacetimedb::view(accessor = my_channel_chat, public)]
+pub fn my_channel_chat(ctx: &ViewContext) -> Vec<ChannelChat> {
+    visible_channel_chat(&ctx.db, ctx.sender())
+}
+
+/// Channels and direct conversations visible to the caller (see
+/// `visible_channel_ids`).
+#[spacetimedb::view(accessor = my_channels, public)]
+pub fn my_channels(ctx: &ViewContext) -> Vec<CompanyChannel> {
+    let db = &ctx.db;
+    visible_channel_ids(db, ctx.sender())
+        .into_iter()
+        .filter_map(|id| db.company_channel().id().find(id))
+        .collect()
+}
+
+/// Members of the channels in `my_channels`.
+#[spacetimedb::view(accessor = my_channel_members, public)]
+pub fn my_channel_members(ctx: &ViewContext) -> Vec<ChannelMember> {
+    let db = &ctx.db;
+    visible_channel_ids(db, ctx.sender())
+        .iter()
+        .flat_map(|id| db.channel_member().channel_member_by_channel().filter(id))
+        .collect()
+}
+
+/// Revision history of the edited chat messages the caller can see. The
+/// history of deleted messages is not exposed.
+#[spacetimedb::view(accessor = my_chat_revisions, public)]
+pub fn my_chat_revisions(ctx: &ViewContext) -> Vec<ChatRevision> {
+    let db = &ctx.db;
+    let connection_ids: Vec<u64> = visible_connection_chat(db, ctx.sender())
+        .into_iter()
+        .filter(|m| m.edited_at.is_some() && !m.deleted)
+        .map(|m| m.id)
+        .collect();
+    let project_ids: Vec<u64> = visible_project_chat(db, ctx.sender())
+        .into_iter()
+        .filter(|m| m.edited_at.is_some() && !m.deleted)
+        .map(|m| m.id)
+        .collect();
+    let channel_ids: Vec<u64> = visible_channel_chat(db, ctx.sender())
+        .into_iter()
+        .filter(|m| m.edited_at.is_some() && !m.deleted)
+        .map(|m| m.id)
+        .collect();
+
+    let mut revisions: Vec<ChatRevision> = Vec::new();
+    for id in &connection_ids {
+        revisions.extend(
+            db.chat_revision()
+                .revision_by_message()
+                .filter(id)
+                .filter(|r| r.kind == ChatKind::Connection),
+        );
+    }
+    for id in &project_ids {
+        revisions.extend(
+            db.chat_revision()
+                .revision_by_message()
+                .filter(id)
+                .filter(|r| r.kind == ChatKind::Project),
+        );
+    }
+    for id in &channel_ids {
+        revisions.extend(
+            db.chat_revision()
+                .revision_by_message()
+                .filter(id)
+                .filter(|r| r.kind == ChatKind::Channel),
+        );
+    }
+    revisions
+}
+
+/// Archived conversations the caller can still read (see `ChatArchive`).
+#[spacetimedb::view(accessor = my_chat_archives, public)]
+pub fn my_chat_archives(ctx: &ViewContext) -> Vec<ChatArchive> {
+    let db = &ctx.db;
+    let mut archives = readable_archives(db, ctx.sender(), ChatKind::Connection);
+    archives.extend(readable_archives(db, ctx.sender(), ChatKind::Project));
+    archives
+}
+
+/// Pinned messages of the conversations the caller can read.
+#[spacetimedb::view(accessor = my_pinned_messages, public)]
+pub fn my_pinned_messages(ctx: &ViewContext) -> Vec<PinnedMessage> {
+    let db = &ctx.db;
+    let mut pins: Vec<PinnedMessage> = Vec::new();
+    for kind in [ChatKind::Connection, ChatKind::Project, ChatKind::Channel] {
+        for conversation_id in visible_conversation_ids(db, ctx.sender(), kind) {
+            pins.extend(
+                db.pinned_message()
+                    .pin_by_conversation()
+                    .filter(&conversation_id)
+                    .filter(|p| p.kind == kind),
+            );
+        }
+    }
+    pins
+}
+
+/// Metadata of the attachments sent with chat messages the caller can see,
+/// plus the caller's own uploads (including unfinished and unsent ones).
+#[spacetimedb::view(accessor = visible_attachments, public)]
+pub fn visible_attachments(ctx: &ViewContext) -> Vec<Attachment> {
+    let db = &ctx.db;
+    let mut ids: Vec<u64> = visible_connection_chat(db, ctx.sender())
+        .into_iter()
+        .flat_map(|m| m.attachment_ids.unwrap_or_default())
+        .collect();
+    ids.extend(
+        visible_project_chat(db, ctx.sender())
+            .into_iter()
+            .flat_map(|m| m.attachment_ids.unwrap_or_default()),
+    );
+    ids.extend(
+        visible_channel_chat(db, ctx.sender())
+            .into_iter()
+            .flat_map(|m| m.attachment_ids),
+    );
+    ids.extend(
+        db.attachment()
+            .attachment_by_uploader()
+            .filter(&ctx.sender())
+            .map(|a| a.id),
+    );
+    ids.sort_unstable();
+    ids.dedup();
+
+    ids.into_iter()
+        .filter_map(|id| db.attachment().id().find(id))
+        .collect()
+}
+
+/// Attachment storage usage and quota of every company the caller belongs to.
+#[spacetimedb::view(accessor = my_company_storage, public)]
+pub fn my_company_storage(ctx: &ViewContext) -> Vec<CompanyStorage> {
+    let db = &ctx.db;
+    db.company_member()
+        .member_by_identity()
+        .filter(&ctx.sender())
+        .map(|m| {
+            db.company_storage()
+                .company_id()
+                .find(m.company_id)
+                .unwrap_or(CompanyStorage {
+                    company_id: m.company_id,
+                    used_bytes: 0,
+                    quota_bytes: DEFAULT_STORAGE_QUOTA_BYTES,
+                })
+        })
+        .collect()
+}
+
+/// The caller's read position and unread count in every live conversation
+/// they can read.
+#[spacetimedb::view(accessor = my_read_cursors, public)]
+pub fn my_read_cursors(ctx: &ViewContext) -> Vec<UnreadCount> {
+    let db = &ctx.db;
+    let mut counts: Vec<UnreadCount> = Vec::new();
+    for kind in [ChatKind::Connection, ChatKind::Project, ChatKind::Channel] {
+        for conversation_id in visible_conversation_ids(db, ctx.sender(), kind) {
+            let Some(stats) = find_conversation_stats(db, kind, conversation_id) else {
+                continue;
+            };
+            let cursor = db
+                .read_cursor()
+                .cursor_by_identity()
+                .filter(&ctx.sender())
+                .find(|c| c.kind == kind && c.conversation_id == conversation_id);
+            counts.push(match cursor {
+                Some(c) => UnreadCount {
+                    kind,
+                    conversation_id,
+                    last_read_message_id: c.last_read_message_id,
+                    last_read_at: c.last_read_at,
+                    unread_count: stats
+                        .message_count
+                        .saturating_sub(c.read_count + c.skipped_count),
+                },
+                None => UnreadCount {
+                    kind,
+                    conversation_id,
+                    last_read_message_id: 0,
+                    last_read_at: None,
+                    unread_count: stats.message_count,
+                },
+            });
+        }
+    }
+    counts
+}
+
+/// Whether the caller shares their read positions (see `set_read_receipts`).
+#[spacetimedb::view(accessor = my_read_receipt_sharing, public)]
+pub fn my_read_receipt_sharing(ctx: &ViewContext) -> Option<ReadReceiptSharing> {
+    ctx.db.read_receipt_sharing().identity().find(ctx.sender())
+}
+
+/// Read positions of the other readers of the caller's conversations, limited
+/// to users who share read receipts and only shown to callers who share
+/// theirs. Their read and skipped counts are not shared.
+#[spacetimedb::view(accessor = visible_read_receipts, public)]
+pub fn visible_read_receipts(ctx: &ViewContext) -> Vec<ReadCursor> {
+    let db = &ctx.db;
+    let shares = |identity: Identity| {
+        db.read_receipt_sharing()
+            .identity()
+            .find(identity)
+            .is_some()
+    };
+    if !shares(ctx.sender()) {
+        return Vec::new();
+    }
+
+    let mut receipts: Vec<ReadCursor> = Vec::new();
+    for own in db.read_cursor().cursor_by_identity().filter(&ctx.sender()) {
+        let audience = conversation_audience(db, own.kind, own.conversation_id);
+        if !audience.contains(&ctx.sender()) {
+            continue;
+        }
+        receipts.extend(
+            db.read_cursor()
+                .cursor_by_conversation()
+                .filter(&own.conversation_id)
+                .filter(|c| {
+                    c.kind == own.kind
+                        && c.identity != ctx.sender()
+                        && c.last_read_at.is_some()
+                        && audience.contains(&c.identity)
+                        && shares(c.identity)
+                })
+                .map(|c| ReadCursor {
+                    read_count: 0,
+                    skipped_count: 0,
+                    ..c
+                }),
+        );
+    }
+    receipts
+}
+
+/// The caller's own notifications, across all of their companies.
+#[spacetimedb::view(accessor = my_notifications, public)]
+pub fn my_notifications(ctx: &ViewContext) -> Vec<Notification> {
+    ctx.db
+        .notification()
+        .notif_by_recipient()
+        .filter(&ctx.sender())
+        .collect()
+}
+
+/// The caller's notification preferences, across all of their companies.
+#[spacetimedb::view(accessor = my_notification_preferences, public)]
+pub fn my_notification_preferences(ctx: &ViewContext) -> Vec<NotificationPreference> {
+    ctx.db
+        .notification_preference()
+        .pref_by_identity()
+        .filter(&ctx.sender())
+        .collect()
+}
+
+/// The conversations the caller has muted.
+#[spacetimedb::view(accessor = my_conversation_mutes, public)]
+pub fn my_conversation_mutes(ctx: &ViewContext) -> Vec<ConversationMute> {
+    ctx.db
+        .conversation_mute()
+        .mute_by_identity()
+        .filter(&ctx.sender())
+        .collect()
+}
+
+/// Invite codes of every company where the caller holds ManageMembers,
+/// matching the checks in `generate_invite_code` and `delete_invite_code`.
+#[spacetimedb::view(accessor = my_invite_codes, public)]
+pub fn my_invite_codes(ctx: &ViewContext) -> Vec<InviteCode> {
+    let db = &ctx.db;
+    companies_with_permission(db, ctx.sender(), Permission::ManageMembers)
+        .iter()
+        .flat_map(|cid| db.invite_code().invite_by_company().filter(cid))
+        .collect()
+}
+
+/// Contact details of the caller and of every member of the companies where
+/// the caller holds ManageMembers.
+#[spacetimedb::view(accessor = visible_user_contacts, public)]
+pub fn visible_user_contacts(ctx: &ViewContext) -> Vec<UserContact> {
+    let db = &ctx.db;
+    let mut identities: Vec<Identity> = vec![ctx.sender()];
+    for cid in companies_with_permission(db, ctx.sender(), Permission::ManageMembers) {
+        identities.extend(
+            db.company_member()
+                .member_by_company()
+                .filter(&cid)
+                .map(|m| m.identity),
+        );
+    }
+    identities.sort_unstable();
+    identities.dedup();
+
+    identities
+        .into_iter()
+        .filter_map(|identity| db.user_contact().identity().find(identity))
+        .collect()
+}
+
+/// Audit trail of every company where the caller holds ViewAuditLog.
+#[spacetimedb::view(accessor = my_company_audit_events, public)]
+pub fn my_company_audit_events(ctx: &ViewContext) -> Vec<AuditEvent> {
+    let db = &ctx.db;
+    companies_with_permission(db, ctx.sender(), Permission::ViewAuditLog)
+        .iter()
+        .flat_map(|cid| db.audit_event().audit_by_company().filter(cid))
+        .collect()
+}
+
+/// Personal invitations the caller can see: those of companies where they hold
+/// ManageMembers, plus those addressed to their own verified e-mail.
+#[spacetimedb::view(accessor = my_personal_invitations, public)]
+pub fn my_personal_invitations(ctx: &ViewContext) -> Vec<PersonalInvitation> {
+    let db = &ctx.db;
+    let mut rows: Vec<PersonalInvitation> =
+        companies_with_permission(db, ctx.sender(), Permission::ManageMembers)
+            .iter()
+            .flat_map(|cid| db.personal_invitation().pinv_by_company().filter(cid))
+            .collect();
+
+    if let Some(email) = verified_contact_email(db, ctx.sender()) {
+        for invitation in db.personal_invitation().pinv_by_email().filter(&email) {
+            if !rows.iter().any(|r| r.id == invitation.id) {
+                rows.push(invitation);
+            }
+        }
+    }
+
+    rows
+}
+
+/// The users the caller has blocked.
+#[spacetimedb::view(accessor = my_user_blocks, public)]
+pub fn my_user_blocks(ctx: &ViewContext) -> Vec<UserBlock> {
+    ctx.db
+        .user_block()
+        .ublock_by_blocker()
+        .filter(&ctx.sender())
+        .collect()
+}
+
+/// The platform audit trail, for platform moderators only.
+#[spacetimedb::view(accessor = platform_audit_events, public)]
+pub fn platform_audit_events(ctx: &ViewContext) -> Vec<PlatformAuditEvent> {
+    if ctx
+        .db
+        .platform_moderator()
+        .identity()
+        .find(ctx.sender())
+        .is_none()
+    {
+        return Vec::new();
+    }
+    PLATFORM_AUDIT_ACTIONS
+        .iter()
+        .flat_map(|action| {
+            ctx.db
+                .platform_audit_event()
+                .platform_audit_by_action()
+                .filter(action)
+        })
+        .collect()
+}
+
+/// Moderation reports: all of them for platform moderators, otherwise only
+/// the reports the caller filed.
+#[spacetimedb::view(accessor = visible_reports, public)]
+pub fn visible_reports(ctx: &ViewContext) -> Vec<ModerationReport> {
+    if ctx
+        .db
+        .platform_moderator()
+        .identity()
+        .find(ctx.sender())
+        .is_some()
+    {
+        return [
+            ReportStatus::Open,
+            ReportStatus::Dismissed,
+            ReportStatus::ActionTaken,
+        ]
+        .iter()
+        .flat_map(|status| ctx.db.moderation_report().report_by_status().filter(status))
+        .collect();
+    }
+    ctx.db
+        .moderation_report()
+        .report_by_reporter()
+        .filter(&ctx.sender())
+        .collect()
+}
+
+/// Role definitions of every company the caller belongs to.
+#[spacetimedb::view(accessor = my_company_roles, public)]
+pub fn my_company_roles(ctx: &ViewContext) -> Vec<CompanyRole> {
+    let db = &ctx.db;
+    let company_ids: Vec<u64> = db
+        .company_member()
+        .member_by_identity()
+        .filter(&ctx.sender())
+        .map(|m| m.company_id)
+        .collect();
+
+    company_ids
+        .iter()
+        .flat_map(|cid| db.company_role().role_by_company().filter(cid))
+        .collect()
+}
+
+/// Participants of every project one of the caller's companies is an Accepted
+/// member of.
+#[spacetimedb::view(accessor = my_project_participants, public)]
+pub fn my_project_participants(ctx: &ViewContext) -> Vec<ProjectParticipant> {
+    let db = &ctx.db;
+    let mut project_ids: Vec<u64> = db
+        .company_member()
+        .member_by_identity()
+        .filter(&ctx.sender())
+        .flat_map(|m| db.project_member().pm_by_company().filter(m.company_id))
+        .filter(|pm| pm.status == ProjectMemberStatus::Accepted)
+        .map(|pm| pm.project_id)
+        .collect();
+    project_ids.sort_unstable();
+    project_ids.dedup();
+
+    project_ids
+        .iter()
+        .flat_map(|pid| db.project_participant().pp_by_project().filter(pid))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Phase 1 — Onboarding
+// ---------------------------------------------------------------------------
+
+/// Register a new user account for the calling identity. If the identity
+/// provider verified `email`, pending personal invitations addressed to it
+/// are accepted automatically.
+///
+/// # Errors
+///
+/// Returns an error if any field is empty or the account already exists.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn create_account(
+    ctx: &ReducerContext,
+    full_name: String,
+    nickname: String,
+    email: String,
+) -> Result<(), String> {
+    let full_name = full_name.trim().to_string();
+    let nickname = nickname.trim().to_string();
+    let email = email.trim().to_string();
 
     validate_not_empty(&full_name, "Name")?;
     validate_not_empty(&nickname, "Nickname")?;
     validate_not_empty(&email, "Email")?;
-    validate_length(&full_name, "Full name", MAX_FULL_NAME)?;
-    validate_length(&nickname, "Nickname", MAX_NICKNAME)?;
+    validate_length(&full_name, "Full name", MAX_FULL_NAME)?;
+    validate_length(&nickname, "Nickname", MAX_NICKNAME)?;
+    validate_length(&email, "Email", MAX_EMAIL)?;
+    validate_email(&email)?;
+    if ctx.db.user_account().identity().find(ctx.sender()).is_some() {
+        return Err("Account already exists".to_string());
+    }
+    if nickname_taken(ctx, &nickname, ctx.sender()) {
+        return Err("Nickname is already taken".to_string());
+    }
+
+    set_mention_handle(ctx, ctx.sender(), &nickname);
+    ctx.db.user_account().insert(UserAccount {
+        identity: ctx.sender(),
+        full_name,
+        nickname,
+        email: String::new(),
+        active_company_id: None,
+        created_at: ctx.timestamp,
+    });
+
+    let email_verified = verified_email(ctx).as_deref() == Some(email.to_lowercase().as_str());
+    let invitations: Vec<PersonalInvitation> = if email_verified {
+        ctx.db
+            .personal_invitation()
+            .pinv_by_email()
+            .filter(&email.to_lowercase())
+            .filter(|i| find_live_company(&ctx.as_read_only().db, i.company_id).is_some())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    ctx.db.user_contact().insert(UserContact {
+        identity: ctx.sender(),
+        email,
+        email_verified,
+    });
+
+    for invitation in &invitations {
+        link_personal_invitation(ctx, invitation);
+    }
+
+    Ok(())
+}
+
+/// User updates their own profile (nickname and email only).
+///
+/// # Errors
+///
+/// Returns an error if any field is empty or the account does not exist.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_profile(
+    ctx: &ReducerContext,
+    nickname: String,
+    email: String,
+) -> Result<(), String> {
+    let nickname = nickname.trim().to_string();
+    let email = email.trim().to_string();
+
+    validate_not_empty(&nickname, "Nickname")?;
+    validate_not_empty(&email, "Email")?;
+    validate_length(&nickname, "Nickname", MAX_NICKNAME)?;
+    validate_length(&email, "Email", MAX_EMAIL)?;
+    validate_email(&email)?;
+
+    let account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+    if nickname_taken(ctx, &nickname, ctx.sender()) {
+        return Err("Nickname is already taken".to_string());
+    }
+
+    set_mention_handle(ctx, ctx.sender(), &nickname);
+    ctx.db.user_account().identity().update(UserAccount {
+        nickname,
+        ..account
+    });
+
+    // An account whose legacy e-mail was empty has no contact row yet (see
+    // `migrate_user_contacts`)
+    let contact = UserContact {
+        identity: ctx.sender(),
+        email_verified: verified_email(ctx).as_deref() == Some(email.to_lowercase().as_str()),
+        email,
+    };
+    if ctx
+        .db
+        .user_contact()
+        .identity()
+        .find(ctx.sender())
+        .is_some()
+    {
+        ctx.db.user_contact().identity().update(contact);
+    } else {
+        ctx.db.user_contact().insert(contact);
+    }
+
+    Ok(())
+}
+
+/// Create a new company and link the caller as its owner.
+///
+/// # Errors
+///
+/// Returns an error if any required field is empty, the slug is taken,
+/// or the caller has no account.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn create_company(
+    ctx: &ReducerContext,
+    name: String,
+    slug: String,
+    location: String,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    let slug = normalize_slug(&slug);
+    let location = location.trim().to_string();
+
+    validate_not_empty(&name, "Company name")?;
+    validate_not_empty(&slug, "Slug")?;
+    validate_not_empty(&location, "Location")?;
+    validate_length(&name, "Company name", MAX_COMPANY_NAME)?;
+    validate_length(&slug, "Slug", MAX_SLUG)?;
+    validate_length(&location, "Location", MAX_LOCATION)?;
+
+    let account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    // Check slug uniqueness (unique constraint will also enforce this)
+    if ctx.db.company().slug().find(&slug).is_some() {
+        return Err("Slug is already taken".to_string());
+    }
+
+    let company = ctx.db.company().insert(Company {
+        id: 0,
+        owner_identity: ctx.sender(),
+        name,
+        slug,
+        location,
+        bio: String::new(),
+        is_public: false,
+        kvk_number: String::new(),
+        purge_at: None,
+    });
+
+    // Create default capabilities row
+    ctx.db.capability().insert(Capability {
+        company_id: company.id,
+        can_install: false,
+        has_cnc: false,
+        has_large_format: false,
+        has_bucket_truck: false,
+    });
+
+    // Seed one role per tier with the default permissions
+    for tier in [
+        UserRole::Owner,
+        UserRole::Admin,
+        UserRole::Member,
+        UserRole::Installer,
+        UserRole::Field,
+        UserRole::Pending,
+    ] {
+        ctx.db.company_role().insert(CompanyRole {
+            id: 0,
+            company_id: company.id,
+            name: format!("{:?}", tier),
+            tier: Some(tier),
+            permissions: default_permissions(tier),
+        });
+    }
+
+    // Create membership as Owner
+    ctx.db.company_member().insert(CompanyMember {
+        id: 0,
+        identity: ctx.sender(),
+        company_id: company.id,
+        role: UserRole::Owner,
+        joined_at: ctx.timestamp,
+        custom_role_id: None,
+    });
+
+    // Set as active company
+    ctx.db.user_account().identity().update(UserAccount {
+        active_company_id: Some(company.id),
+        ..account
+    });
+
+    Ok(())
+}
+
+/// Generates an invite code for the caller's company. The code optionally
+/// expires at `expires_at`, and optionally activates joiners directly as
+/// `role` instead of Pending. The preset role must be below the caller's own.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageMembers, the expiry is not in
+/// the future, or the preset role is at or above the caller's level.
+#[spacetimedb::reducer]
+pub fn generate_invite_code(
+    ctx: &ReducerContext,
+    max_uses: u32,
+    expires_at: Option<Timestamp>,
+    role: Option<UserRole>,
+) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+
+    if expires_at.is_some_and(|t| t <= ctx.timestamp) {
+        return Err("Expiry must be in the future".to_string());
+    }
+
+    let role = role.unwrap_or(UserRole::Pending);
+    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+    if role_level(role) >= role_level(caller_membership.role) {
+        return Err("Cannot preset a role at or above your own level".to_string());
+    }
+
+    // Build a readable 16-character code (XXXX-XXXX-XXXX-XXXX) from the
+    // deterministic RNG. Uses an unambiguous charset (no 0/O/1/I).
+    let charset = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+    let mut code = String::with_capacity(19); // 16 chars + 3 dashes
+    let mut rng = ctx.rng();
+    for i in 0..16 {
+        if i > 0 && i % 4 == 0 {
+            code.push('-');
+        }
+        #[allow(clippy::cast_possible_truncation)] // Modulo charset.len() keeps value small
+        let idx = (rng.next_u64() as usize) % charset.len();
+        code.push(charset[idx] as char);
+    }
+
+    if let Some(expiry) = expires_at {
+        ctx.db.invite_code_expiry().insert(InviteCodeExpiry {
+            scheduled_id: 0,
+            scheduled_at: ScheduleAt::Time(expiry),
+            code: code.clone(),
+        });
+    }
+
+    ctx.db.invite_code().insert(InviteCode {
+        code,
+        company_id,
+        created_by: ctx.sender(),
+        uses_remaining: if max_uses == 0 { 1 } else { max_uses },
+        role,
+        expires_at,
+    });
+
+    Ok(())
+}
+
+/// Scheduled: deletes an invite code once its expiry has passed.
+///
+/// # Errors
+///
+/// Returns an error if called by a client instead of the scheduler.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn purge_expired_invite_code(
+    ctx: &ReducerContext,
+    job: InviteCodeExpiry,
+) -> Result<(), String> {
+    if ctx.sender() != ctx.identity() {
+        return Err("Not permitted".to_string());
+    }
+    run_schema_migrations(ctx);
+
+    if let Some(invite) = ctx.db.invite_code().code().find(&job.code) {
+        if invite.expires_at.is_some_and(|t| t <= ctx.timestamp) {
+            ctx.db.invite_code().code().delete(&job.code);
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes an invite code along with its pending expiry job, if any.
+fn delete_invite_code_row(ctx: &ReducerContext, code: &str) {
+    ctx.db.invite_code().code().delete(code.to_string());
+    ctx.db.invite_code_expiry().code().delete(code.to_string());
+}
+
+/// User joins a company using an invite code. Users can join multiple
+/// companies — each membership is independent with its own role.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account, is already a member of
+/// this specific company, the code is invalid, expired, or has no remaining uses.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn join_company(ctx: &ReducerContext, code: String) -> Result<(), String> {
+    let code = code.trim().to_uppercase();
+    validate_length(&code, "Invite code", MAX_INVITE_CODE)?;
+    validate_invite_code_format(&code)?;
+
+    let account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    let invite = ctx
+        .db
+        .invite_code()
+        .code()
+        .find(&code)
+        .ok_or("Invalid invite code")?;
+
+    // Companies pending deletion are read-only
+    find_live_company(&ctx.as_read_only().db, invite.company_id).ok_or("Invalid invite code")?;
+
+    // Check not already a member of THIS company
+    if find_membership(ctx, ctx.sender(), invite.company_id).is_some() {
+        return Err("You are already a member of this company".to_string());
+    }
+
+    if invite.uses_remaining == 0 {
+        return Err("Invite code has been fully used".to_string());
+    }
+
+    if invite.expires_at.is_some_and(|t| t <= ctx.timestamp) {
+        return Err("Invite code has expired".to_string());
+    }
+
+    // Check if this user has already used this specific code
+    let already_used = ctx
+        .db
+        .used_invite_code()
+        .used_by_identity()
+        .filter(&ctx.sender())
+        .any(|u| u.code == code);
+    if already_used {
+        return Err("You have already used this invite code".to_string());
+    }
+
+    // Record the usage before linking
+    ctx.db.used_invite_code().insert(UsedInviteCode {
+        id: 0,
+        identity: ctx.sender(),
+        code: code.clone(),
+        company_id: invite.company_id,
+    });
+
+    // Create membership with the code's preset role — Pending by default,
+    // in which case an Admin or Owner must activate them
+    ctx.db.company_member().insert(CompanyMember {
+        id: 0,
+        identity: ctx.sender(),
+        company_id: invite.company_id,
+        role: invite.role,
+        joined_at: ctx.timestamp,
+        custom_role_id: None,
+    });
+
+    // Capture before potential move
+    let joiner_name = account.nickname.clone();
+
+    // Set as active company if user has no active company
+    if account.active_company_id.is_none() {
+        ctx.db.user_account().identity().update(UserAccount {
+            active_company_id: Some(invite.company_id),
+            ..account
+        });
+    }
+
+    // Decrement uses (or delete if last use).
+    // This is atomic: SpacetimeDB reducers are transactional, so the
+    // find → check → decrement sequence cannot race with another caller.
+    if invite.uses_remaining <= 1 {
+        delete_invite_code_row(ctx, &code);
+    } else {
+        ctx.db.invite_code().code().update(InviteCode {
+            uses_remaining: invite.uses_remaining - 1,
+            ..invite
+        });
+    }
+
+    // Notify members who manage members that a new member joined
+    let company_name = ctx.db.company().id().find(invite.company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_default();
+    notify_company_permission(
+        ctx,
+        invite.company_id,
+        Permission::ManageMembers,
+        Some(ctx.sender()),
+        NotificationType::MemberJoined,
+        "New member joined".to_string(),
+        format!("{} joined {}", joiner_name, company_name),
+        NotificationRefs::default(),
+    );
+
+    Ok(())
+}
+
+/// Deletes an invite code of the caller's company.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageMembers, the code is not found,
+/// or the code belongs to a different company.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn delete_invite_code(ctx: &ReducerContext, code: String) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+
+    let invite = ctx
+        .db
+        .invite_code()
+        .code()
+        .find(&code)
+        .ok_or("Invite code not found")?;
+
+    if invite.company_id != company_id {
+        return Err("Invite code not found".to_string());
+    }
+
+    delete_invite_code_row(ctx, &code);
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Phase 2 — Team Management
+// ---------------------------------------------------------------------------
+
+/// Adds another registered user to the caller's company.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageMembers, the colleague is not
+/// found, or the colleague is already a member of this company.
+#[spacetimedb::reducer]
+pub fn add_colleague_by_identity(
+    ctx: &ReducerContext,
+    colleague_identity: Identity,
+) -> Result<(), String> {
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+
+    ctx.db
+        .user_account()
+        .identity()
+        .find(colleague_identity)
+        .ok_or("Colleague account not found")?;
+
+    if find_membership(ctx, colleague_identity, company_id).is_some() {
+        return Err("User is already a member of this company".to_string());
+    }
+
+    ctx.db.company_member().insert(CompanyMember {
+        id: 0,
+        identity: colleague_identity,
+        company_id,
+        role: UserRole::Member,
+        joined_at: ctx.timestamp,
+        custom_role_id: None,
+    });
+
+    Ok(())
+}
+
+/// Invites someone by e-mail, whether or not they already have an
+/// account. The invited role must be below the caller's own level.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageMembers, the e-mail is invalid,
+/// the role is at or above the caller's level, the address already has a
+/// pending invitation for this company, or belongs to an existing member.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn invite_by_email(ctx: &ReducerContext, email: String, role: UserRole) -> Result<(), String> {
+    let email = email.trim().to_lowercase();
+    validate_not_empty(&email, "Email")?;
     validate_length(&email, "Email", MAX_EMAIL)?;
     validate_email(&email)?;
-    if ctx.db.user_account().identity().find(ctx.sender()).is_some() {
-        return Err("Account already exists".to_string());
+
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+
+    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+    if role_level(role) >= role_level(caller_membership.role) {
+        return Err("Cannot assign a role at or above your own level".to_string());
+    }
+
+    let already_invited = ctx
+        .db
+        .personal_invitation()
+        .pinv_by_email()
+        .filter(&email)
+        .any(|i| i.company_id == company_id);
+    if already_invited {
+        return Err("This address already has a pending invitation".to_string());
+    }
+
+    let already_member = ctx
+        .db
+        .company_member()
+        .member_by_company()
+        .filter(&company_id)
+        .any(|m| contact_email(ctx, m.identity).as_deref() == Some(email.as_str()));
+    if already_member {
+        return Err("User is already a member of this company".to_string());
+    }
+
+    ctx.db.personal_invitation().insert(PersonalInvitation {
+        id: 0,
+        company_id,
+        email,
+        role,
+        invited_by: ctx.sender(),
+        created_at: ctx.timestamp,
+    });
+
+    Ok(())
+}
+
+/// Revokes a pending personal invitation.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageMembers or the invitation is
+/// not found in their company.
+#[spacetimedb::reducer]
+pub fn revoke_personal_invitation(ctx: &ReducerContext, invitation_id: u64) -> Result<(), String> {
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+
+    let invitation = ctx
+        .db
+        .personal_invitation()
+        .id()
+        .find(invitation_id)
+        .filter(|i| i.company_id == company_id)
+        .ok_or("Invitation not found")?;
+
+    ctx.db.personal_invitation().id().delete(invitation.id);
+    Ok(())
+}
+
+/// Accept a personal invitation addressed to the caller's verified e-mail.
+/// Covers invitations sent after the caller's account was created.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account, or the invitation is not
+/// found or not addressed to the caller's verified e-mail.
+#[spacetimedb::reducer]
+pub fn accept_personal_invitation(ctx: &ReducerContext, invitation_id: u64) -> Result<(), String> {
+    ctx.db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    let email = verified_contact_email(&ctx.as_read_only().db, ctx.sender())
+        .ok_or("Invitation not found")?;
+
+    let invitation = ctx
+        .db
+        .personal_invitation()
+        .id()
+        .find(invitation_id)
+        .filter(|i| i.email == email)
+        .ok_or("Invitation not found")?;
+
+    find_live_company(&ctx.as_read_only().db, invitation.company_id).ok_or("Company not found")?;
+
+    if find_membership(ctx, ctx.sender(), invitation.company_id).is_some() {
+        return Err("You are already a member of this company".to_string());
+    }
+
+    link_personal_invitation(ctx, &invitation);
+    Ok(())
+}
+
+/// Removes a user from the company. Admins can only remove Members, Field,
+/// and Pending; Owners can remove anyone except themselves.
+///
+/// # Errors
+///
+/// Returns an error if the caller tries to remove themselves, lacks
+/// ManageMembers, the colleague is not a member of the same company, or has an
+/// equal or higher role than the caller.
+#[spacetimedb::reducer]
+pub fn remove_colleague(
+    ctx: &ReducerContext,
+    colleague_identity: Identity,
+) -> Result<(), String> {
+    if ctx.sender() == colleague_identity {
+        return Err("Cannot remove yourself".to_string());
+    }
+
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+
+    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+
+    let colleague_membership = find_membership(ctx, colleague_identity, company_id)
+        .ok_or("Colleague is not in your company")?;
+
+    // Hierarchy: cannot remove someone with equal or higher role
+    if role_level(colleague_membership.role) >= role_level(caller_membership.role) {
+        return Err("You can only remove members with a lower role than yours".to_string());
+    }
+
+    // Delete the membership and their project assignments
+    ctx.db.company_member().id().delete(colleague_membership.id);
+    delete_member_participations(ctx, colleague_identity, company_id);
+
+    // If their active company was this one, reassign
+    reassign_active_company(ctx, colleague_identity, company_id);
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ColleagueRemoved,
+        Some(colleague_identity),
+        None,
+        None,
+        format!("{:?}", colleague_membership.role),
+        String::new(),
+    );
+
+    // Notify the removed colleague
+    let company_name = ctx.db.company().id().find(company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_default();
+    notify(
+        ctx,
+        colleague_identity,
+        company_id,
+        NotificationType::MemberRemoved,
+        "Removed from company".to_string(),
+        format!("You were removed from {}", company_name),
+        NotificationRefs::default(),
+    );
+
+    Ok(())
+}
+
+/// Voluntarily leave the active company. Owners must transfer ownership
+/// first — they cannot abandon a company.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account, has no active company,
+/// has no membership in that company, or is the Owner.
+#[spacetimedb::reducer]
+pub fn leave_company(ctx: &ReducerContext) -> Result<(), String> {
+    let account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    let company_id = account
+        .active_company_id
+        .ok_or("You do not belong to a company")?;
+
+    let membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("You are not a member of this company")?;
+
+    if membership.role == UserRole::Owner {
+        return Err("Transfer ownership before leaving the company".to_string());
+    }
+
+    // Delete membership and switch active company to next available, or None
+    leave_membership(ctx, &membership);
+
+    Ok(())
+}
+
+/// Permanently deletes the caller's account (right to be forgotten). Leaves
+/// every company, anonymises the sender of their chat messages, attachments,
+/// pins, reports, audit events and the notifications they caused, removes
+/// them from the mentions of other messages, discards
+/// unsent uploads, and deletes their notifications, mutes, used invite codes,
+/// user blocks and contact details. Owners must transfer ownership of every
+/// company first.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account or is the Owner of any company.
+#[spacetimedb::reducer]
+pub fn delete_account(ctx: &ReducerContext) -> Result<(), String> {
+    ctx.db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    let memberships: Vec<CompanyMember> = ctx
+        .db
+        .company_member()
+        .member_by_identity()
+        .filter(&ctx.sender())
+        .collect();
+
+    if memberships.iter().any(|m| m.role == UserRole::Owner) {
+        return Err(
+            "Transfer ownership of your companies before deleting your account".to_string(),
+        );
+    }
+
+    // 1. Leave every company, recording it in each company's audit trail and
+    //    the deletion itself in the platform trail. All of them are
+    //    anonymised in step 3.
+    for membership in &memberships {
+        leave_membership(ctx, membership);
+        record_audit(
+            ctx,
+            membership.company_id,
+            AuditAction::AccountDeleted,
+            Some(ctx.sender()),
+            None,
+            None,
+            format!("{:?}", membership.role),
+            String::new(),
+        );
+    }
+    record_platform_audit(
+        ctx,
+        ctx.sender(),
+        AuditAction::AccountDeleted,
+        None,
+        None,
+        format!("{} memberships", memberships.len()),
+        String::new(),
+    );
+
+    // 2. Anonymise sent chat messages, attachments, pins and filed reports
+    //    and drop the caller from others' mentions (the conversations and
+    //    moderation history stay intact)
+    let conn_chat: Vec<ConnectionChat> = ctx
+        .db
+        .connection_chat()
+        .chat_by_sender()
+        .filter(&ctx.sender())
+        .collect();
+    for msg in conn_chat {
+        anonymise_chat_revisions(ctx, ChatKind::Connection, msg.id);
+        ctx.db.connection_chat().id().update(ConnectionChat {
+            sender: Identity::ZERO,
+            ..msg
+        });
+    }
+    let project_chat: Vec<ProjectChat> = ctx
+        .db
+        .project_chat()
+        .pchat_by_sender()
+        .filter(&ctx.sender())
+        .collect();
+    for msg in project_chat {
+        anonymise_chat_revisions(ctx, ChatKind::Project, msg.id);
+        ctx.db.project_chat().id().update(ProjectChat {
+            sender: Identity::ZERO,
+            ..msg
+        });
+    }
+    let channel_chat: Vec<ChannelChat> = ctx
+        .db
+        .channel_chat()
+        .channel_chat_by_sender()
+        .filter(&ctx.sender())
+        .collect();
+    for msg in channel_chat {
+        anonymise_chat_revisions(ctx, ChatKind::Channel, msg.id);
+        ctx.db.channel_chat().id().update(ChannelChat {
+            sender: Identity::ZERO,
+            ..msg
+        });
+    }
+    let attachments: Vec<Attachment> = ctx
+        .db
+        .attachment()
+        .attachment_by_uploader()
+        .filter(&ctx.sender())
+        .collect();
+    for attachment in attachments {
+        if attachment.message_id == 0 {
+            purge_attachment(ctx, &attachment);
+        } else {
+            ctx.db.attachment().id().update(Attachment {
+                uploader: Identity::ZERO,
+                ..attachment
+            });
+        }
+    }
+    let reports: Vec<ModerationReport> = ctx
+        .db
+        .moderation_report()
+        .report_by_reporter()
+        .filter(&ctx.sender())
+        .collect();
+    for report in reports {
+        ctx.db.moderation_report().id().update(ModerationReport {
+            reporter: Identity::ZERO,
+            ..report
+        });
+    }
+    let pins: Vec<PinnedMessage> = ctx
+        .db
+        .pinned_message()
+        .pin_by_pinner()
+        .filter(&ctx.sender())
+        .collect();
+    for pin in pins {
+        ctx.db.pinned_message().id().update(PinnedMessage {
+            pinned_by: Identity::ZERO,
+            ..pin
+        });
+    }
+    remove_mentions_of(ctx, ctx.sender());
+
+    // 3. Remove the caller from audit trails, delete notifications and remove
+    //    the caller as actor from others'
+    anonymise_audit_events(ctx, ctx.sender());
+    let notif_ids: Vec<u64> = ctx
+        .db
+        .notification()
+        .notif_by_recipient()
+        .filter(&ctx.sender())
+        .map(|n| n.id)
+        .collect();
+    for id in notif_ids {
+        ctx.db.notification().id().delete(id);
+    }
+    let acted: Vec<Notification> = ctx
+        .db
+        .notification()
+        .notif_by_actor()
+        .filter(&ctx.sender())
+        .collect();
+    for notification in acted {
+        ctx.db.notification().id().update(Notification {
+            actor_identity: Identity::ZERO,
+            ..notification
+        });
+    }
+
+    // 4. Delete used invite code records, rate-limit history, read cursors,
+    //    conversation mutes and access to archived conversations
+    let used_ids: Vec<u64> = ctx
+        .db
+        .used_invite_code()
+        .used_by_identity()
+        .filter(&ctx.sender())
+        .map(|u| u.id)
+        .collect();
+    for id in used_ids {
+        ctx.db.used_invite_code().id().delete(id);
+    }
+    let event_ids: Vec<u64> = ctx
+        .db
+        .rate_limit_event()
+        .rle_by_identity()
+        .filter(&ctx.sender())
+        .map(|e| e.id)
+        .collect();
+    for id in event_ids {
+        ctx.db.rate_limit_event().id().delete(id);
+    }
+
+    let cursor_ids: Vec<u64> = ctx
+        .db
+        .read_cursor()
+        .cursor_by_identity()
+        .filter(&ctx.sender())
+        .map(|c| c.id)
+        .collect();
+    for id in cursor_ids {
+        ctx.db.read_cursor().id().delete(id);
+    }
+    ctx.db
+        .read_receipt_sharing()
+        .identity()
+        .delete(ctx.sender());
+    let mute_ids: Vec<u64> = ctx
+        .db
+        .conversation_mute()
+        .mute_by_identity()
+        .filter(&ctx.sender())
+        .map(|m| m.id)
+        .collect();
+    for id in mute_ids {
+        ctx.db.conversation_mute().id().delete(id);
+    }
+    let archive_reader_ids: Vec<u64> = ctx
+        .db
+        .chat_archive_reader()
+        .archive_reader_by_identity()
+        .filter(&ctx.sender())
+        .map(|r| r.id)
+        .collect();
+    for id in archive_reader_ids {
+        ctx.db.chat_archive_reader().id().delete(id);
+    }
+
+    // 5. Delete user blocks in either direction
+    let mut block_ids: Vec<u64> = ctx
+        .db
+        .user_block()
+        .ublock_by_blocker()
+        .filter(&ctx.sender())
+        .map(|b| b.id)
+        .collect();
+    block_ids.extend(
+        ctx.db
+            .user_block()
+            .ublock_by_blocked()
+            .filter(&ctx.sender())
+            .map(|b| b.id),
+    );
+    for id in block_ids {
+        ctx.db.user_block().id().delete(id);
+    }
+
+    // 6. Delete contact details, mention handle, moderator status and the
+    //    account itself
+    ctx.db.user_contact().identity().delete(ctx.sender());
+    ctx.db.user_mention_handle().identity().delete(ctx.sender());
+    ctx.db.platform_moderator().identity().delete(ctx.sender());
+    ctx.db.user_account().identity().delete(ctx.sender());
+
+    Ok(())
+}
+
+/// Switch the user's active company context. The user must have a membership
+/// in the target company.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account or is not a member of the
+/// target company.
+#[spacetimedb::reducer]
+pub fn switch_active_company(ctx: &ReducerContext, company_id: u64) -> Result<(), String> {
+    let account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    ctx.db
+        .company()
+        .id()
+        .find(company_id)
+        .ok_or("Company not found")?;
+
+    find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("You are not a member of this company")?;
+
+    ctx.db.user_account().identity().update(UserAccount {
+        active_company_id: Some(company_id),
+        ..account
+    });
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Phase 3 — Public Presence
+// ---------------------------------------------------------------------------
+
+/// Updates the company's public profile. Requires EditProfile.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks EditProfile, required fields are
+/// empty, or the new slug is already taken by another company.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_company_profile(
+    ctx: &ReducerContext,
+    name: String,
+    slug: String,
+    location: String,
+    bio: String,
+    is_public: bool,
+    kvk_number: String,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    let slug = normalize_slug(&slug);
+    let location = location.trim().to_string();
+    let bio = bio.trim().to_string();
+    let kvk_number = kvk_number.trim().to_string();
+
+    validate_not_empty(&name, "Company name")?;
+    validate_not_empty(&slug, "Slug")?;
+    validate_not_empty(&location, "Location")?;
+    validate_length(&name, "Company name", MAX_COMPANY_NAME)?;
+    validate_length(&slug, "Slug", MAX_SLUG)?;
+    validate_length(&location, "Location", MAX_LOCATION)?;
+    validate_length(&bio, "Bio", MAX_BIO)?;
+    validate_length(&kvk_number, "KVK number", MAX_KVK_NUMBER)?;
+
+    let (_account, company_id) = require_permission(ctx, Permission::EditProfile)?;
+
+    let company = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .ok_or("Company not found")?;
+
+    // If slug changed, check uniqueness
+    if slug != company.slug && ctx.db.company().slug().find(&slug).is_some() {
+        return Err("Slug is already taken".to_string());
+    }
+
+    ctx.db.company().id().update(Company {
+        name,
+        slug,
+        location,
+        bio,
+        is_public,
+        kvk_number,
+        ..company
+    });
+
+    Ok(())
+}
+
+/// Updates the company's capabilities. Requires EditProfile.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks EditProfile or capabilities are not
+/// found for the company.
+#[spacetimedb::reducer]
+#[allow(clippy::fn_params_excessive_bools)] // SpacetimeDB reducer: each capability is an independent flag
+pub fn update_capabilities(
+    ctx: &ReducerContext,
+    can_install: bool,
+    has_cnc: bool,
+    has_large_format: bool,
+    has_bucket_truck: bool,
+) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::EditProfile)?;
+
+    let cap = ctx
+        .db
+        .capability()
+        .company_id()
+        .find(company_id)
+        .ok_or("Capabilities not found")?;
+
+    ctx.db.capability().company_id().update(Capability {
+        can_install,
+        has_cnc,
+        has_large_format,
+        has_bucket_truck,
+        ..cap
+    });
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Phase 4 — Role Management
+// ---------------------------------------------------------------------------
+
+/// Changes a team member's tier in the caller's company. Requires ManageMembers.
+/// Cannot change your own role or promote someone to Owner (use
+/// `transfer_ownership` for that). Admins can only assign roles below
+/// their own level; Owners can assign any non-Owner role.
+///
+/// # Errors
+///
+/// Returns an error if the caller targets themselves, tries to assign Owner,
+/// lacks ManageMembers, the target has no membership in the same company,
+/// the target has an equal or higher role, or the caller tries to assign
+/// a role at or above their own level.
+#[spacetimedb::reducer]
+pub fn update_user_role(
+    ctx: &ReducerContext,
+    target_identity: Identity,
+    new_role: UserRole,
+) -> Result<(), String> {
+    if ctx.sender() == target_identity {
+        return Err("Cannot change your own role".to_string());
+    }
+
+    if new_role == UserRole::Owner {
+        return Err("Use transfer_ownership to assign the Owner role".to_string());
+    }
+
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+
+    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+
+    let target_membership = find_membership(ctx, target_identity, company_id)
+        .ok_or("Target is not in your company")?;
+
+    // Cannot change role of someone at or above your level
+    if role_level(target_membership.role) >= role_level(caller_membership.role) {
+        return Err("Cannot change the role of someone at or above your level".to_string());
+    }
+
+    // Cannot promote someone to or above your own level (unless Owner)
+    if caller_membership.role != UserRole::Owner
+        && role_level(new_role) >= role_level(caller_membership.role)
+    {
+        return Err("Cannot assign a role at or above your own level".to_string());
+    }
+
+    let old_role = target_membership.role;
+    ctx.db.company_member().id().update(CompanyMember {
+        role: new_role,
+        ..target_membership
+    });
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::RoleChanged,
+        Some(target_identity),
+        None,
+        None,
+        format!("{:?}", old_role),
+        format!("{:?}", new_role),
+    );
+
+    // Notify the target that their role was changed
+    let company_name = ctx.db.company().id().find(company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_default();
+    notify(
+        ctx,
+        target_identity,
+        company_id,
+        NotificationType::RoleChanged,
+        "Role updated".to_string(),
+        format!("Your role in {} was changed to {:?}", company_name, new_role),
+        NotificationRefs::default(),
+    );
+
+    Ok(())
+}
+
+/// Transfers company ownership to another team member. The caller (current
+/// Owner) is demoted to Admin and the target becomes the new Owner.
+/// Also updates `Company.owner_identity`.
+///
+/// # Errors
+///
+/// Returns an error if the caller targets themselves, is not the Owner,
+/// the target is not found, or the target is not in the same company.
+#[spacetimedb::reducer]
+pub fn transfer_ownership(
+    ctx: &ReducerContext,
+    new_owner_identity: Identity,
+) -> Result<(), String> {
+    if ctx.sender() == new_owner_identity {
+        return Err("You are already the owner".to_string());
+    }
+
+    let (_caller, company_id) = require_role_at_least(ctx, UserRole::Owner)?;
+
+    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+
+    let target_membership = find_membership(ctx, new_owner_identity, company_id)
+        .ok_or("Target is not in your company")?;
+
+    // Update company owner reference — double-check caller is the recorded owner
+    let company = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .ok_or("Company not found")?;
+
+    if company.owner_identity != ctx.sender() {
+        return Err("Only the owner can do this".to_string());
+    }
+
+    ctx.db.company().id().update(Company {
+        owner_identity: new_owner_identity,
+        ..company
+    });
+
+    // Demote current owner to Admin (on CompanyMember)
+    ctx.db.company_member().id().update(CompanyMember {
+        role: UserRole::Admin,
+        ..caller_membership
+    });
+
+    // Promote target to Owner (on CompanyMember)
+    ctx.db.company_member().id().update(CompanyMember {
+        role: UserRole::Owner,
+        ..target_membership
+    });
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::OwnershipTransferred,
+        Some(new_owner_identity),
+        None,
+        None,
+        ctx.sender().to_hex().to_string(),
+        new_owner_identity.to_hex().to_string(),
+    );
+
+    Ok(())
+}
+
+/// Verifies that the caller may hand out every permission in `permissions`:
+/// nobody can grant a permission they do not hold themselves.
+///
+/// # Errors
+///
+/// Returns an error if `permissions` contains a permission the caller lacks.
+fn require_grantable(
+    ctx: &ReducerContext,
+    company_id: u64,
+    permissions: &[Permission],
+) -> Result<(), String> {
+    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+    let held = member_permissions(&ctx.as_read_only().db, &caller_membership);
+    if permissions.iter().any(|p| !held.contains(p)) {
+        return Err("Cannot grant permissions you do not hold".to_string());
+    }
+    Ok(())
+}
+
+/// Removes duplicate permissions while keeping the first occurrence's order.
+fn dedup_permissions(permissions: Vec<Permission>) -> Vec<Permission> {
+    let mut unique: Vec<Permission> = Vec::with_capacity(permissions.len());
+    for p in permissions {
+        if !unique.contains(&p) {
+            unique.push(p);
+        }
+    }
+    unique
+}
+
+/// Creates a custom role (e.g. "Sales") in the caller's active company.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageRoles, the name is empty, too
+/// long or already used in this company, or the caller tries to grant a
+/// permission they do not hold.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn create_company_role(
+    ctx: &ReducerContext,
+    name: String,
+    permissions: Vec<Permission>,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    validate_not_empty(&name, "Role name")?;
+    validate_length(&name, "Role name", MAX_ROLE_NAME)?;
+
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageRoles)?;
+
+    let name_taken = ctx
+        .db
+        .company_role()
+        .role_by_company()
+        .filter(&company_id)
+        .any(|r| r.name.eq_ignore_ascii_case(&name));
+    if name_taken {
+        return Err("A role with this name already exists".to_string());
+    }
+
+    let permissions = dedup_permissions(permissions);
+    require_grantable(ctx, company_id, &permissions)?;
+
+    let role = ctx.db.company_role().insert(CompanyRole {
+        id: 0,
+        company_id,
+        name: name.clone(),
+        tier: None,
+        permissions: permissions.clone(),
+    });
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::CompanyRoleCreated,
+        None,
+        None,
+        None,
+        String::new(),
+        format!("{} (#{}): {:?}", name, role.id, permissions),
+    );
+
+    Ok(())
+}
+
+/// Updates a role's name and permissions. Tier roles keep their name, and the
+/// Owner and Pending tiers cannot be edited (Owners always hold every
+/// permission; Pending members hold none until activated).
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageRoles, the role is not found in
+/// their company, is a locked tier, a tier role is renamed, the new name is
+/// invalid or taken, or the caller tries to grant a permission they do not hold.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_company_role(
+    ctx: &ReducerContext,
+    role_id: u64,
+    name: String,
+    permissions: Vec<Permission>,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    validate_not_empty(&name, "Role name")?;
+    validate_length(&name, "Role name", MAX_ROLE_NAME)?;
+
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageRoles)?;
+
+    let role = ctx
+        .db
+        .company_role()
+        .id()
+        .find(role_id)
+        .filter(|r| r.company_id == company_id)
+        .ok_or("Role not found")?;
+
+    if matches!(role.tier, Some(UserRole::Owner | UserRole::Pending)) {
+        return Err("This role cannot be edited".to_string());
+    }
+    if role.tier.is_some() && name != role.name {
+        return Err("Default roles cannot be renamed".to_string());
+    }
+
+    let name_taken = ctx
+        .db
+        .company_role()
+        .role_by_company()
+        .filter(&company_id)
+        .any(|r| r.id != role_id && r.name.eq_ignore_ascii_case(&name));
+    if name_taken {
+        return Err("A role with this name already exists".to_string());
+    }
+
+    let permissions = dedup_permissions(permissions);
+    // Both adding and removing a permission count as granting it
+    let changed: Vec<Permission> = permissions
+        .iter()
+        .filter(|p| !role.permissions.contains(p))
+        .chain(role.permissions.iter().filter(|p| !permissions.contains(p)))
+        .copied()
+        .collect();
+    require_grantable(ctx, company_id, &changed)?;
+
+    let old_value = format!("{}: {:?}", role.name, role.permissions);
+    let new_value = format!("{}: {:?}", name, permissions);
+    ctx.db.company_role().id().update(CompanyRole {
+        name,
+        permissions,
+        ..role
+    });
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::CompanyRoleUpdated,
+        None,
+        None,
+        None,
+        old_value,
+        new_value,
+    );
+
+    Ok(())
+}
+
+/// Deletes a custom role. Members who had it fall back to their tier's role.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageRoles, or the role is not found
+/// in their company or is a tier role.
+#[spacetimedb::reducer]
+pub fn delete_company_role(ctx: &ReducerContext, role_id: u64) -> Result<(), String> {
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageRoles)?;
+
+    let role = ctx
+        .db
+        .company_role()
+        .id()
+        .find(role_id)
+        .filter(|r| r.company_id == company_id)
+        .ok_or("Role not found")?;
+
+    if role.tier.is_some() {
+        return Err("Default roles cannot be deleted".to_string());
+    }
+
+    let holders: Vec<CompanyMember> = ctx
+        .db
+        .company_member()
+        .member_by_company()
+        .filter(&company_id)
+        .filter(|m| m.custom_role_id == Some(role_id))
+        .collect();
+    for member in holders {
+        ctx.db.company_member().id().update(CompanyMember {
+            custom_role_id: None,
+            ..member
+        });
+    }
+
+    ctx.db.company_role().id().delete(role_id);
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::CompanyRoleDeleted,
+        None,
+        None,
+        None,
+        format!("{}: {:?}", role.name, role.permissions),
+        String::new(),
+    );
+
+    Ok(())
+}
+
+/// Assigns a custom role to a team member, or clears it with `None` so the
+/// member falls back to their tier's role. The same hierarchy rules as
+/// `update_user_role` apply.
+///
+/// # Errors
+///
+/// Returns an error if the caller targets themselves, lacks ManageRoles, the
+/// target is not in the same company or is at or above the caller's level,
+/// the role is not a custom role of this company, or it grants a permission
+/// the caller does not hold.
+#[spacetimedb::reducer]
+pub fn assign_company_role(
+    ctx: &ReducerContext,
+    target_identity: Identity,
+    role_id: Option<u64>,
+) -> Result<(), String> {
+    if ctx.sender() == target_identity {
+        return Err("Cannot change your own role".to_string());
+    }
+
+    let (_caller, company_id) = require_permission(ctx, Permission::ManageRoles)?;
+
+    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+
+    let target_membership = find_membership(ctx, target_identity, company_id)
+        .ok_or("Target is not in your company")?;
+
+    if role_level(target_membership.role) >= role_level(caller_membership.role) {
+        return Err("Cannot change the role of someone at or above your level".to_string());
+    }
+
+    let new_role_name = match role_id {
+        Some(id) => {
+            let role = ctx
+                .db
+                .company_role()
+                .id()
+                .find(id)
+                .filter(|r| r.company_id == company_id && r.tier.is_none())
+                .ok_or("Role not found")?;
+            require_grantable(ctx, company_id, &role.permissions)?;
+            role.name
+        }
+        None => String::new(),
+    };
+
+    let old_role_name = target_membership
+        .custom_role_id
+        .and_then(|id| ctx.db.company_role().id().find(id))
+        .map(|r| r.name)
+        .unwrap_or_default();
+
+    ctx.db.company_member().id().update(CompanyMember {
+        custom_role_id: role_id,
+        ..target_membership
+    });
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::CompanyRoleAssigned,
+        Some(target_identity),
+        None,
+        None,
+        old_role_name,
+        new_role_name,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Phase 5 — Inter-Company Connections
+// ---------------------------------------------------------------------------
+
+/// Helper: determine which company the `requested_by` identity belongs to
+/// within this connection. Checks membership in both sides of the connection
+/// rather than relying on `active_company_id` (which may have changed since
+/// the request was made).
+///
+/// # Errors
+///
+/// Returns an error if the requesting user is not a member of either company.
+fn requesting_company_id(ctx: &ReducerContext, conn: &Connection) -> Result<u64, String> {
+    if find_membership(ctx, conn.requested_by, conn.company_a).is_some() {
+        return Ok(conn.company_a);
+    }
+    if find_membership(ctx, conn.requested_by, conn.company_b).is_some() {
+        return Ok(conn.company_b);
+    }
+    Err("Requesting user is not a member of either connected company".to_string())
+}
+
+/// Request a connection with another company. If the target has blocked us
+/// the call succeeds silently (ghosting — the block is never revealed).
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageConnections, targets their own
+/// company, the target company does not exist, the message is too long,
+/// or a non-blocked connection already exists.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn request_connection(
+    ctx: &ReducerContext,
+    target_company_id: u64,
+    message: String,
+) -> Result<(), String> {
+    let message = message.trim().to_string();
+    validate_length(&message, "Message", MAX_MESSAGE)?;
+
+    let (_caller, my_company_id) = require_permission(ctx, Permission::ManageConnections)?;
+
+    if my_company_id == target_company_id {
+        return Err("Cannot connect to your own company".to_string());
+    }
+
+    find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    if let Some(conn) = find_connection(ctx, my_company_id, target_company_id) {
+        if conn.status == ConnectionStatus::Blocked {
+            // Ghosting: silently succeed so the block is never revealed
+            return Ok(());
+        }
+        return Err("A connection already exists between these companies".to_string());
+    }
+
+    check_rate_limit(ctx, RateLimitedAction::ConnectionRequest, my_company_id)?;
+
+    let (lo, hi) = if my_company_id <= target_company_id {
+        (my_company_id, target_company_id)
+    } else {
+        (target_company_id, my_company_id)
+    };
+
+    let connection = ctx.db.company_connection().insert(Connection {
+        id: 0,
+        company_a: lo,
+        company_b: hi,
+        status: ConnectionStatus::Pending,
+        requested_by: ctx.sender(),
+        blocking_company_id: None,
+        initial_message: message,
+        created_at: ctx.timestamp,
+    });
+
+    // Notify the target company's connection managers of the incoming request
+    let my_company_name = ctx.db.company().id().find(my_company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_default();
+    notify_company_permission(
+        ctx,
+        target_company_id,
+        Permission::ManageConnections,
+        None,
+        NotificationType::ConnectionRequest,
+        "Connection request".to_string(),
+        format!("{} wants to connect", my_company_name),
+        NotificationRefs {
+            connection_id: connection.id,
+            target_company_id: my_company_id,
+            ..NotificationRefs::default()
+        },
+    );
+
+    Ok(())
+}
+
+/// Cancel a pending connection request. Only the requesting company can cancel.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageConnections, the target company
+/// is not found, no pending connection exists, or the caller's company is not
+/// the requesting side.
+#[spacetimedb::reducer]
+pub fn cancel_request(
+    ctx: &ReducerContext,
+    target_company_id: u64,
+) -> Result<(), String> {
+    let (_caller, my_company_id) = require_permission(ctx, Permission::ManageConnections)?;
+    find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    let conn = find_connection(ctx, my_company_id, target_company_id)
+        .ok_or("No connection exists")?;
+
+    if conn.status != ConnectionStatus::Pending {
+        return Err("Connection is not pending".to_string());
+    }
+
+    if requesting_company_id(ctx, &conn)? != my_company_id {
+        return Err("Only the requesting side can cancel a request".to_string());
+    }
+
+    archive_connection_chat(ctx, &conn);
+    ctx.db.company_connection().id().delete(conn.id);
+    Ok(())
+}
+
+/// Accept a pending connection request. Only the non-requesting company
+/// can accept.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageConnections, the target company
+/// is not found, no pending connection exists, or the caller's company is the
+/// requesting side.
+#[spacetimedb::reducer]
+pub fn accept_connection(
+    ctx: &ReducerContext,
+    target_company_id: u64,
+) -> Result<(), String> {
+    let (_caller, my_company_id) = require_permission(ctx, Permission::ManageConnections)?;
+    find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    let conn = find_connection(ctx, my_company_id, target_company_id)
+        .ok_or("No connection exists")?;
+
+    if conn.status != ConnectionStatus::Pending {
+        return Err("Connection is not pending".to_string());
+    }
+
+    let requesting_cid = requesting_company_id(ctx, &conn)?;
+
+    if requesting_cid == my_company_id {
+        return Err("You cannot accept your own connection request".to_string());
+    }
+
+    let connection_id = conn.id;
+    ctx.db.company_connection().id().update(Connection {
+        status: ConnectionStatus::Accepted,
+        ..conn
+    });
+    post_system_message(
+        ctx,
+        ChatKind::Connection,
+        connection_id,
+        SystemEvent::ConnectionAccepted(my_company_id),
+    );
+
+    // Notify the requesting company that their request was accepted
+    let my_company_name = ctx.db.company().id().find(my_company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_default();
+    notify_company_permission(
+        ctx,
+        requesting_cid,
+        Permission::ManageConnections,
+        None,
+        NotificationType::ConnectionAccepted,
+        "Connection accepted".to_string(),
+        format!("{} accepted your connection request", my_company_name),
+        NotificationRefs {
+            connection_id,
+            target_company_id: my_company_id,
+            ..NotificationRefs::default()
+        },
+    );
+
+    Ok(())
+}
+
+/// Decline a pending connection request (soft reject — deletes the row and
+/// archives its chat). Only the non-requesting company can decline.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageConnections, the target company
+/// is not found, no pending connection exists, or the caller's company is the
+/// requesting side.
+#[spacetimedb::reducer]
+pub fn decline_connection(
+    ctx: &ReducerContext,
+    target_company_id: u64,
+) -> Result<(), String> {
+    let (_caller, my_company_id) = require_permission(ctx, Permission::ManageConnections)?;
+    find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    let conn = find_connection(ctx, my_company_id, target_company_id)
+        .ok_or("No connection exists")?;
+
+    if conn.status != ConnectionStatus::Pending {
+        return Err("Connection is not pending".to_string());
+    }
+
+    if requesting_company_id(ctx, &conn)? == my_company_id {
+        return Err("You cannot decline your own connection request".to_string());
+    }
+
+    // Notify the requesting company that their request was declined
+    let requesting_cid = requesting_company_id(ctx, &conn)?;
+    let my_company_name = ctx.db.company().id().find(my_company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_default();
+    notify_company_permission(
+        ctx,
+        requesting_cid,
+        Permission::ManageConnections,
+        None,
+        NotificationType::ConnectionDeclined,
+        "Connection declined".to_string(),
+        format!("{} declined your connection request", my_company_name),
+        NotificationRefs {
+            target_company_id: my_company_id,
+            ..NotificationRefs::default()
+        },
+    );
+
+    archive_connection_chat(ctx, &conn);
+    ctx.db.company_connection().id().delete(conn.id);
+    Ok(())
+}
+
+/// Block a company. Works from any state — updates an existing connection to
+/// Blocked, or creates a new Blocked row. If already blocked, silently
+/// succeeds without overwriting the original `blocked_by`.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageConnections or targets their own
+/// company.
+#[spacetimedb::reducer]
+pub fn block_company(
+    ctx: &ReducerContext,
+    target_company_id: u64,
+) -> Result<(), String> {
+    let (_caller, my_company_id) = require_permission(ctx, Permission::ManageConnections)?;
+
+    if my_company_id == target_company_id {
+        return Err("Cannot block your own company".to_string());
+    }
+
+    find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    let existing = find_connection(ctx, my_company_id, target_company_id);
+    let previous_status = existing.as_ref().map(|c| c.status);
+
+    if let Some(conn) = existing {
+        if conn.status == ConnectionStatus::Blocked {
+            // Already blocked — keep original blocker, silently succeed
+            return Ok(());
+        }
+        ctx.db.company_connection().id().update(Connection {
+            status: ConnectionStatus::Blocked,
+            blocking_company_id: Some(my_company_id),
+            ..conn
+        });
+    } else {
+        let (lo, hi) = if my_company_id <= target_company_id {
+            (my_company_id, target_company_id)
+        } else {
+            (target_company_id, my_company_id)
+        };
+
+        ctx.db.company_connection().insert(Connection {
+            id: 0,
+            company_a: lo,
+            company_b: hi,
+            status: ConnectionStatus::Blocked,
+            requested_by: ctx.sender(),
+            blocking_company_id: Some(my_company_id),
+            initial_message: String::new(),
+            created_at: ctx.timestamp,
+        });
+    }
+
+    record_audit(
+        ctx,
+        my_company_id,
+        AuditAction::CompanyBlocked,
+        None,
+        Some(target_company_id),
+        None,
+        previous_status
+            .map(|st| format!("{:?}", st))
+            .unwrap_or_default(),
+        format!("{:?}", ConnectionStatus::Blocked),
+    );
+
+    Ok(())
+}
+
+/// Unblock a company. Only the company that performed the block can unblock.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageConnections, the target company
+/// is not found, no blocked connection exists, or the caller's company is not
+/// the one that performed the block.
+#[spacetimedb::reducer]
+pub fn unblock_company(
+    ctx: &ReducerContext,
+    target_company_id: u64,
+) -> Result<(), String> {
+    let (_caller, my_company_id) = require_permission(ctx, Permission::ManageConnections)?;
+    find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    let conn = find_connection(ctx, my_company_id, target_company_id)
+        .ok_or("No connection exists")?;
+
+    if conn.status != ConnectionStatus::Blocked {
+        return Err("Connection is not blocked".to_string());
+    }
+
+    // Only the company that performed the block can unblock
+    if let Some(blocker_company) = conn.blocking_company_id {
+        if blocker_company != my_company_id {
+            return Err("Only the company that blocked can unblock".to_string());
+        }
+    }
+
+    archive_connection_chat(ctx, &conn);
+    ctx.db.company_connection().id().delete(conn.id);
+
+    record_audit(
+        ctx,
+        my_company_id,
+        AuditAction::CompanyUnblocked,
+        None,
+        Some(target_company_id),
+        None,
+        format!("{:?}", ConnectionStatus::Blocked),
+        String::new(),
+    );
+
+    Ok(())
+}
+
+/// Disconnect from a company. Deletes the Accepted connection row; its chat
+/// is archived read-only for both sides.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageConnections, the target company
+/// is not found, no connection exists, or the connection is not in Accepted
+/// status.
+#[spacetimedb::reducer]
+pub fn disconnect_company(
+    ctx: &ReducerContext,
+    target_company_id: u64,
+) -> Result<(), String> {
+    let (_caller, my_company_id) = require_permission(ctx, Permission::ManageConnections)?;
+    find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    let conn = find_connection(ctx, my_company_id, target_company_id)
+        .ok_or("No connection exists")?;
+
+    if conn.status != ConnectionStatus::Accepted {
+        return Err("Connection is not active".to_string());
+    }
+
+    archive_connection_chat(ctx, &conn);
+    ctx.db.company_connection().id().delete(conn.id);
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Company Deletion (cascade-aware)
+// ---------------------------------------------------------------------------
+
+/// Runs the full deletion cascade for a company: invite codes, personal
+/// invitations, memberships, capabilities, roles, channels (+ chat),
+/// connections (+ chat), notifications and projects. Returns the number of members unlinked.
+fn delete_company_cascade(ctx: &ReducerContext, company_id: u64) -> usize {
+    // 1. Delete all invite codes and personal invitations for this company
+    let invite_codes: Vec<String> = ctx
+        .db
+        .invite_code()
+        .invite_by_company()
+        .filter(&company_id)
+        .map(|ic| ic.code)
+        .collect();
+    for code in invite_codes {
+        delete_invite_code_row(ctx, &code);
+    }
+    let invitation_ids: Vec<u64> = ctx
+        .db
+        .personal_invitation()
+        .pinv_by_company()
+        .filter(&company_id)
+        .map(|i| i.id)
+        .collect();
+    for id in invitation_ids {
+        ctx.db.personal_invitation().id().delete(id);
+    }
+
+    // 2. Delete all memberships and reassign active companies
+    let members: Vec<(u64, Identity)> = ctx
+        .db
+        .company_member()
+        .member_by_company()
+        .filter(&company_id)
+        .map(|m| (m.id, m.identity))
+        .collect();
+    for (member_id, member_identity) in &members {
+        ctx.db.company_member().id().delete(*member_id);
+        reassign_active_company(ctx, *member_identity, company_id);
+    }
+
+    // 3. Delete capability row, role definitions and internal channels
+    ctx.db.capability().company_id().delete(company_id);
+    let role_ids: Vec<u64> = ctx
+        .db
+        .company_role()
+        .role_by_company()
+        .filter(&company_id)
+        .map(|r| r.id)
+        .collect();
+    for id in role_ids {
+        ctx.db.company_role().id().delete(id);
+    }
+    let channel_ids: Vec<u64> = ctx
+        .db
+        .company_channel()
+        .channel_by_company()
+        .filter(&company_id)
+        .map(|c| c.id)
+        .collect();
+    for id in channel_ids {
+        delete_channel_cascade(ctx, id);
+    }
+
+    // 4. Delete all connections, archiving their chat for the other side
+    let conn_ids: Vec<u64> = ctx
+        .db
+        .company_connection()
+        .conn_by_company_a()
+        .filter(&company_id)
+        .map(|c| c.id)
+        .collect();
+    let conn_ids_b: Vec<u64> = ctx
+        .db
+        .company_connection()
+        .conn_by_company_b()
+        .filter(&company_id)
+        .map(|c| c.id)
+        .collect();
+    for conn_id in conn_ids.iter().chain(conn_ids_b.iter()) {
+        if let Some(conn) = ctx.db.company_connection().id().find(*conn_id) {
+            archive_connection_chat(ctx, &conn);
+        }
+        ctx.db.company_connection().id().delete(*conn_id);
+    }
+
+    // 5. Delete all notifications for and about this company, and its
+    //    notification preferences
+    let mut notif_ids: Vec<u64> = ctx
+        .db
+        .notification()
+        .notif_by_company()
+        .filter(&company_id)
+        .map(|n| n.id)
+        .collect();
+    notif_ids.extend(
+        ctx.db
+            .notification()
+            .notif_by_target_company()
+            .filter(&company_id)
+            .filter(|n| n.company_id != company_id)
+            .map(|n| n.id),
+    );
+    for notif_id in notif_ids {
+        ctx.db.notification().id().delete(notif_id);
+    }
+    let preference_ids: Vec<u64> = ctx
+        .db
+        .notification_preference()
+        .pref_by_company()
+        .filter(&company_id)
+        .map(|p| p.id)
+        .collect();
+    for id in preference_ids {
+        ctx.db.notification_preference().id().delete(id);
+    }
+
+    // 6. Cascade-delete projects owned by this company
+    let owned_project_ids: Vec<u64> = ctx
+        .db
+        .project()
+        .iter()
+        .filter(|p| p.owner_company_id == company_id)
+        .map(|p| p.id)
+        .collect();
+    for pid in owned_project_ids {
+        delete_project_cascade(ctx, pid);
+    }
+
+    // 7. Remove this company from projects it was a member of (not owner)
+    //    Collect affected project IDs before deleting memberships, so we can
+    //    check for orphaned projects afterward (scoped, not full table scan).
+    let affected_project_ids: Vec<u64> = ctx
+        .db
+        .project_member()
+        .pm_by_company()
+        .filter(&company_id)
+        .map(|m| m.project_id)
+        .collect();
+    let pm_ids: Vec<u64> = ctx
+        .db
+        .project_member()
+        .pm_by_company()
+        .filter(&company_id)
+        .map(|m| m.id)
+        .collect();
+    for pm_id in pm_ids {
+        ctx.db.project_member().id().delete(pm_id);
+    }
+    for pid in &affected_project_ids {
+        delete_company_participants(ctx, *pid, company_id);
+    }
+
+    // 8. Auto-delete any affected projects left with 0 Accepted members
+    for pid in affected_project_ids {
+        // Project may have already been cascade-deleted in step 6
+        if ctx.db.project().id().find(pid).is_none() {
+            continue;
+        }
+        let has_accepted = ctx
+            .db
+            .project_member()
+            .pm_by_project()
+            .filter(&pid)
+            .any(|m| m.status == ProjectMemberStatus::Accepted);
+        if !has_accepted {
+            delete_project_cascade(ctx, pid);
+        }
+    }
+
+    // 9. Delete the suspension record (if any), storage usage, chat retention,
+    //    rate-limit history and the company row. Attachments the company's
+    //    members sent to surviving conversations stay with those conversations.
+    ctx.db.company_suspension().company_id().delete(company_id);
+    ctx.db.company_storage().company_id().delete(company_id);
+    ctx.db.chat_retention().company_id().delete(company_id);
+    let event_ids: Vec<u64> = ctx
+        .db
+        .rate_limit_event()
+        .rle_by_company()
+        .filter(&company_id)
+        .map(|e| e.id)
+        .collect();
+    for id in event_ids {
+        ctx.db.rate_limit_event().id().delete(id);
+    }
+    ctx.db.company().id().delete(company_id);
+
+    members.len()
+}
+
+/// Schedules the company for permanent deletion after the platform's grace
+/// period (see `PlatformConfig`). Until then the company is hidden from other
+/// companies and read-only, and the Owner can call `restore_company`. The
+/// cascade runs in `purge_company` when the schedule fires.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not the Owner, the company is not found,
+/// or it is already scheduled for deletion.
+#[spacetimedb::reducer]
+pub fn delete_company(ctx: &ReducerContext) -> Result<(), String> {
+    let (_caller, company_id) = require_role_at_least(ctx, UserRole::Owner)?;
+
+    let company = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .ok_or("Company not found")?;
+
+    if company.owner_identity != ctx.sender() {
+        return Err("Only the owner can do this".to_string());
+    }
+
+    let grace_days = u64::from(platform_config(ctx).company_deletion_grace_days);
+    let purge_at = ctx.timestamp + Duration::from_secs(grace_days * 24 * 60 * 60);
+
+    ctx.db.company_purge().insert(CompanyPurge {
+        scheduled_id: 0,
+        scheduled_at: ScheduleAt::Time(purge_at),
+        company_id,
+        requested_by: ctx.sender(),
+    });
+
+    let company_name = company.name.clone();
+    ctx.db.company().id().update(Company {
+        purge_at: Some(purge_at),
+        ..company
+    });
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::CompanyDeletionScheduled,
+        None,
+        Some(company_id),
+        None,
+        company_name,
+        String::new(),
+    );
+
+    Ok(())
+}
+
+/// Cancels a scheduled company deletion. Only the Owner can do this, and only
+/// before the grace period ends.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account or active company, is not
+/// the Owner, or the company is not scheduled for deletion.
+#[spacetimedb::reducer]
+pub fn restore_company(ctx: &ReducerContext) -> Result<(), String> {
+    // Not via `require_role_at_least`: that rejects companies pending deletion
+    let account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    let company_id = account
+        .active_company_id
+        .ok_or("You do not belong to a company")?;
+
+    let company = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .ok_or("Company not found")?;
+
+    let membership = find_membership(ctx, ctx.sender(), company_id)
+        .ok_or("Not permitted")?;
+
+    if membership.role != UserRole::Owner || company.owner_identity != ctx.sender() {
+        return Err("Only the owner can do this".to_string());
+    }
+
+    if company.purge_at.is_none() {
+        return Err("Company is not scheduled for deletion".to_string());
+    }
+
+    ctx.db.company_purge().company_id().delete(company_id);
+
+    let company_name = company.name.clone();
+    ctx.db.company().id().update(Company {
+        purge_at: None,
+        ..company
+    });
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::CompanyRestored,
+        None,
+        Some(company_id),
+        None,
+        String::new(),
+        company_name,
+    );
+
+    Ok(())
+}
+
+/// Scheduled: permanently deletes a company once its grace period has ended.
+///
+/// # Errors
+///
+/// Returns an error if called by a client instead of the scheduler.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn purge_company(ctx: &ReducerContext, job: CompanyPurge) -> Result<(), String> {
+    if ctx.sender() != ctx.identity() {
+        return Err("Not permitted".to_string());
+    }
+    run_schema_migrations(ctx);
+
+    // Restored in the meantime, or already gone
+    let Some(company) = ctx.db.company().id().find(job.company_id) else {
+        return Ok(());
+    };
+    if company.purge_at.is_none() {
+        return Ok(());
+    }
+
+    let members_unlinked = delete_company_cascade(ctx, job.company_id);
+
+    // The company's own trail is unreadable once it is gone
+    record_platform_audit(
+        ctx,
+        job.requested_by,
+        AuditAction::CompanyDeleted,
+        None,
+        Some(job.company_id),
+        company.name,
+        format!("{} members unlinked", members_unlinked),
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Phase 6 — Connection Chat
+// ---------------------------------------------------------------------------
+
+/// Send a chat message within a connection. Only allowed when the connection
+/// is Pending or Accepted, and the caller belongs to one of the two companies.
+/// The message can reply to (`parent_id`) and/or quote (`quoted_id`) another
+/// message of the connection; a reply notifies only the author of the parent.
+/// `attachment_ids` are the caller's finished uploads to this connection.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account or company, the connection
+/// or its partner company is not found, the connection is Blocked, the
+/// caller's company is not part of the connection, a referenced message or
+/// attachment is not found or cannot be sent, or the message is empty or too
+/// long.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn send_connection_chat(
+    ctx: &ReducerContext,
+    connection_id: u64,
+    text: String,
+    parent_id: Option<u64>,
+    quoted_id: Option<u64>,
+    attachment_ids: Vec<u64>,
+) -> Result<(), String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("Message cannot be empty".to_string());
+    }
+    validate_length(&text, "Message", MAX_MESSAGE)?;
+
+    // Pending users cannot chat — they must be activated first
+    let (_account, my_company_id) = require_permission(ctx, Permission::Chat)?;
+
+    let conn = ctx
+        .db
+        .company_connection()
+        .id()
+        .find(connection_id)
+        .ok_or("Connection not found")?;
+
+    if conn.status == ConnectionStatus::Blocked {
+        return Err("Cannot chat on a blocked connection".to_string());
+    }
+
+    if conn.company_a != my_company_id && conn.company_b != my_company_id {
+        return Err("Your company is not part of this connection".to_string());
+    }
+
+    // The partner company is read-only while it is scheduled for deletion
+    let partner_id = if conn.company_a == my_company_id {
+        conn.company_b
+    } else {
+        conn.company_a
+    };
+    find_live_company(&ctx.as_read_only().db, partner_id).ok_or("Connection not found")?;
+
+    let reply_to = resolve_chat_references(
+        ctx,
+        ChatKind::Connection,
+        connection_id,
+        parent_id,
+        quoted_id,
+    )?;
+    let mentions = resolve_mentions(ctx, ChatKind::Connection, connection_id, &text);
+    let attachment_ids =
+        check_chat_attachments(ctx, ChatKind::Connection, connection_id, attachment_ids)?;
+
+    check_rate_limit(ctx, RateLimitedAction::ConnectionChat, my_company_id)?;
+
+    let message = ctx.db.connection_chat().insert(ConnectionChat {
+        id: 0,
+        connection_id,
+        sender: ctx.sender(),
+        text: text.clone(),
+        created_at: ctx.timestamp,
+        hidden: false,
+        edited_at: None,
+        deleted: false,
+        parent_id: reply_to.as_ref().map(|r| r.root_id),
+        quoted_id,
+        reply_count: 0,
+        mentions: Some(mentions.users.clone()),
+        mentioned_company_ids: Some(mentions.company_ids.clone()),
+        attachment_ids: Some(attachment_ids.clone()),
+        message_kind: MessageKind::User,
+    });
+    link_attachments(ctx, &attachment_ids, message.id);
+    index_chat_message(ctx, ChatKind::Connection, message.id, &text);
+
+    // Notify the other company about the new message
+    let other_company_id = if conn.company_a == my_company_id {
+        conn.company_b
+    } else {
+        conn.company_a
+    };
+    let sender_name = _account.nickname.clone();
+    let preview = truncate_preview(&text, chat_preview_len(ChatKind::Connection));
+    let mut notification_ids: Vec<u64> = if let Some(reply_to) = reply_to {
+        update_reply_count(ctx, ChatKind::Connection, reply_to.root_id, true);
+        notify_chat_reply(
+            ctx,
+            ChatKind::Connection,
+            connection_id,
+            &reply_to,
+            "New reply".to_string(),
+            format!("{} replied: {}", sender_name, preview),
+        )
+        .into_iter()
+        .collect()
+    } else {
+        notify_company_chat(
+            ctx,
+            other_company_id,
+            ChatKind::Connection,
+            connection_id,
+            "New message".to_string(),
+            format!("{}: {}", sender_name, preview),
+        )
+    };
+    notification_ids.extend(notify_mentions(
+        ctx,
+        ChatKind::Connection,
+        connection_id,
+        &mentions.users,
+        &notification_ids,
+        format!("{} mentioned you", sender_name),
+        format!("{}: {}", sender_name, preview),
+    ));
+    link_notifications_to_message(ctx, notification_ids, message.id);
+    count_new_message(ctx, ChatKind::Connection, connection_id, message.id);
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Phase 7 — Notifications
+// ---------------------------------------------------------------------------
+
+/// Mark a single notification as read.
+#[spacetimedb::reducer]
+pub fn mark_notification_read(ctx: &ReducerContext, notification_id: u64) -> Result<(), String> {
+    let notif = ctx
+        .db
+        .notification()
+        .id()
+        .find(notification_id)
+        .ok_or("Notification not found")?;
+
+    if notif.recipient_identity != ctx.sender() {
+        return Err("Not your notification".to_string());
+    }
+
+    ctx.db.notification().id().update(Notification {
+        is_read: true,
+        ..notif
+    });
+
+    Ok(())
+}
+
+/// Mark all unread notifications as read for the caller within a specific
+/// company, including their platform notifications.
+#[spacetimedb::reducer]
+pub fn mark_all_notifications_read(ctx: &ReducerContext, company_id: u64) -> Result<(), String> {
+    let _account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    let to_update: Vec<Notification> = ctx
+        .db
+        .notification()
+        .notif_by_recipient()
+        .filter(&ctx.sender())
+        .filter(|n| {
+            (n.company_id == company_id || n.scope == NotificationScope::Platform) && !n.is_read
+        })
+        .collect();
+
+    for notif in to_update {
+        ctx.db.notification().id().update(Notification {
+            is_read: true,
+            ..notif
+        });
+    }
+
+    Ok(())
+}
+
+/// Delete all read notifications for the caller within a specific company,
+/// including their platform notifications.
+#[spacetimedb::reducer]
+pub fn clear_notifications(ctx: &ReducerContext, company_id: u64) -> Result<(), String> {
+    let _account = ctx
+        .db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    let to_delete: Vec<u64> = ctx
+        .db
+        .notification()
+        .notif_by_recipient()
+        .filter(&ctx.sender())
+        .filter(|n| {
+            (n.company_id == company_id || n.scope == NotificationScope::Platform) && n.is_read
+        })
+        .map(|n| n.id)
+        .collect();
+
+    for id in to_delete {
+        ctx.db.notification().id().delete(id);
+    }
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Notification Preferences
+// ---------------------------------------------------------------------------
+
+/// Sets how many notifications of `notification_type` the caller receives in
+/// one of their companies. `All` removes the preference. `MentionsOnly` is
+/// only available for the chat message types and keeps replies and @mentions
+/// while skipping the other messages.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a member of the company or sets
+/// `MentionsOnly` for another type.
+#[spacetimedb::reducer]
+pub fn set_notification_preference(
+    ctx: &ReducerContext,
+    company_id: u64,
+    notification_type: NotificationType,
+    level: NotificationLevel,
+) -> Result<(), String> {
+    find_membership(ctx, ctx.sender(), company_id).ok_or("You are not a member of this company")?;
+
+    let is_message_type = [ChatKind::Connection, ChatKind::Project, ChatKind::Channel]
+        .into_iter()
+        .any(|kind| chat_notification_types(kind).message == notification_type);
+    if level == NotificationLevel::MentionsOnly && !is_message_type {
+        return Err("Mentions only is available for chat message notifications".to_string());
+    }
+
+    let existing = ctx
+        .db
+        .notification_preference()
+        .pref_by_identity()
+        .filter(&ctx.sender())
+        .find(|p| p.company_id == company_id && p.notification_type == notification_type);
+    match (existing, level) {
+        (Some(preference), NotificationLevel::All) => {
+            ctx.db.notification_preference().id().delete(preference.id);
+        }
+        (Some(preference), _) => {
+            ctx.db
+                .notification_preference()
+                .id()
+                .update(NotificationPreference {
+                    level,
+                    ..preference
+                });
+        }
+        (None, NotificationLevel::All) => {}
+        (None, _) => {
+            ctx.db
+                .notification_preference()
+                .insert(NotificationPreference {
+                    id: 0,
+                    identity: ctx.sender(),
+                    company_id,
+                    notification_type,
+                    level,
+                });
+        }
+    }
+
+    Ok(())
+}
+
+/// Mutes a conversation the caller can read until `muted_until`, or
+/// indefinitely if `None`. Muting again replaces the end time. @mentions
+/// still notify.
+///
+/// # Errors
+///
+/// Returns an error if the caller cannot read the conversation or
+/// `muted_until` is not in the future.
+#[spacetimedb::reducer]
+pub fn mute_conversation(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    muted_until: Option<Timestamp>,
+) -> Result<(), String> {
+    if !conversation_audience(&ctx.as_read_only().db, kind, conversation_id).contains(&ctx.sender())
+    {
+        return Err("Conversation not found".to_string());
+    }
+    if muted_until.is_some_and(|until| until <= ctx.timestamp) {
+        return Err("Mute end must be in the future".to_string());
+    }
+
+    // Drop this conversation's mute and any that have expired
+    let stale_ids: Vec<u64> = ctx
+        .db
+        .conversation_mute()
+        .mute_by_identity()
+        .filter(&ctx.sender())
+        .filter(|m| {
+            (m.kind == kind && m.conversation_id == conversation_id)
+                || m.muted_until.is_some_and(|until| until <= ctx.timestamp)
+        })
+        .map(|m| m.id)
+        .collect();
+    for id in stale_ids {
+        ctx.db.conversation_mute().id().delete(id);
+    }
+
+    ctx.db.conversation_mute().insert(ConversationMute {
+        id: 0,
+        identity: ctx.sender(),
+        kind,
+        conversation_id,
+        muted_until,
+    });
+
+    Ok(())
+}
+
+/// Unmutes a conversation.
+///
+/// # Errors
+///
+/// Returns an error if the caller has not muted the conversation.
+#[spacetimedb::reducer]
+pub fn unmute_conversation(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Result<(), String> {
+    let mute = ctx
+        .db
+        .conversation_mute()
+        .mute_by_identity()
+        .filter(&ctx.sender())
+        .find(|m| m.kind == kind && m.conversation_id == conversation_id)
+        .ok_or("Conversation is not muted")?;
+    ctx.db.conversation_mute().id().delete(mute.id);
+
+    Ok(())
+}
+
+/// Deletes every mute of a conversation that is going away.
+fn delete_conversation_mutes(ctx: &ReducerContext, kind: ChatKind, conversation_id: u64) {
+    let mute_ids: Vec<u64> = ctx
+        .db
+        .conversation_mute()
+        .mute_by_conversation()
+        .filter(&conversation_id)
+        .filter(|m| m.kind == kind)
+        .map(|m| m.id)
+        .collect();
+    for id in mute_ids {
+        ctx.db.conversation_mute().id().delete(id);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// User Blocks
+// ---------------------------------------------------------------------------
+
+/// Blocks another user. Their chat messages disappear from the caller's chat
+/// views and they no longer trigger notifications to the caller. The blocked
+/// user is never told.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account, targets themselves, the
+/// user is not found, or is already blocked.
+#[spacetimedb::reducer]
+pub fn block_user(ctx: &ReducerContext, identity: Identity) -> Result<(), String> {
+    ctx.db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
+
+    if identity == ctx.sender() {
+        return Err("Cannot block yourself".to_string());
+    }
+
+    ctx.db
+        .user_account()
+        .identity()
+        .find(identity)
+        .ok_or("User not found")?;
+
+    if has_blocked(&ctx.as_read_only().db, ctx.sender(), identity) {
+        return Err("User is already blocked".to_string());
+    }
+
+    ctx.db.user_block().insert(UserBlock {
+        id: 0,
+        blocker: ctx.sender(),
+        blocked: identity,
+        created_at: ctx.timestamp,
+    });
+    recount_user(ctx, ctx.sender(), identity);
+
+    Ok(())
+}
+
+/// Lifts a block on another user. Messages sent while blocked become visible
+/// again; notifications suppressed during the block are not restored.
+///
+/// # Errors
+///
+/// Returns an error if the user is not blocked by the caller.
+#[spacetimedb::reducer]
+pub fn unblock_user(ctx: &ReducerContext, identity: Identity) -> Result<(), String> {
+    let block = ctx
+        .db
+        .user_block()
+        .ublock_by_blocker()
+        .filter(&ctx.sender())
+        .find(|b| b.blocked == identity)
+        .ok_or("User is not blocked")?;
+
+    ctx.db.user_block().id().delete(block.id);
+    recount_user(ctx, ctx.sender(), identity);
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Phase 8 — Projects (Multi-Company Rooms)
+// ---------------------------------------------------------------------------
+
+/// Create a new project room. The caller's active company becomes the owner
+/// and is automatically added as an Accepted member, with the caller as Lead.
+#[spacetimedb::reducer]
+pub fn create_project(
+    ctx: &ReducerContext,
+    name: String,
+    description: String,
+) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageProjects)?;
+
+    let name = name.trim().to_string();
+    let description = description.trim().to_string();
+    validate_not_empty(&name, "Project name")?;
+    validate_length(&name, "Project name", MAX_PROJECT_NAME)?;
+    validate_length(&description, "Description", MAX_PROJECT_DESCRIPTION)?;
+
+    let project = ctx.db.project().insert(Project {
+        id: 0,
+        owner_company_id: company_id,
+        name: name.clone(),
+        description,
+        created_by: ctx.sender(),
+        created_at: ctx.timestamp,
+    });
+
+    // Auto-add creator's company as Accepted member
+    ctx.db.project_member().insert(ProjectMember {
+        id: 0,
+        project_id: project.id,
+        company_id,
+        status: ProjectMemberStatus::Accepted,
+        invited_by: ctx.sender(),
+        joined_at: ctx.timestamp,
+    });
+    ctx.db.project_participant().insert(ProjectParticipant {
+        id: 0,
+        project_id: project.id,
+        company_id,
+        identity: ctx.sender(),
+        role: ProjectRole::Lead,
+        assigned_by: ctx.sender(),
+        assigned_at: ctx.timestamp,
+    });
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectCreated,
+        None,
+        None,
+        Some(project.id),
+        String::new(),
+        name,
+    );
+
+    Ok(())
+}
+
+/// Rename a project or change its description. Only members of the project
+/// owner company with ManageProjects can edit. Each change is recorded in the
+/// project chat as a system message.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_project(
+    ctx: &ReducerContext,
+    project_id: u64,
+    name: String,
+    description: String,
+) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageProjects)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    if project.owner_company_id != company_id {
+        return Err("Only the owner company can edit the project".to_string());
+    }
+
+    let name = name.trim().to_string();
+    let description = description.trim().to_string();
+    validate_not_empty(&name, "Project name")?;
+    validate_length(&name, "Project name", MAX_PROJECT_NAME)?;
+    validate_length(&description, "Description", MAX_PROJECT_DESCRIPTION)?;
+
+    let renamed = name != project.name;
+    let description_changed = description != project.description;
+    if !renamed && !description_changed {
+        return Ok(());
+    }
+
+    let old_name = project.name.clone();
+    ctx.db.project().id().update(Project {
+        name: name.clone(),
+        description,
+        ..project
+    });
+
+    if renamed {
+        post_system_message(
+            ctx,
+            ChatKind::Project,
+            project_id,
+            SystemEvent::ProjectRenamed(name.clone()),
+        );
+    }
+    if description_changed {
+        post_system_message(
+            ctx,
+            ChatKind::Project,
+            project_id,
+            SystemEvent::ProjectDescriptionChanged,
+        );
+    }
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectUpdated,
+        None,
+        None,
+        Some(project_id),
+        old_name,
+        name,
+    );
+
+    Ok(())
+}
+
+/// Invite another company to a project. Only members of the project owner
+/// company with ManageProjects can invite. Cleans up old Left/Kicked rows before inserting.
+#[spacetimedb::reducer]
+pub fn invite_to_project(
+    ctx: &ReducerContext,
+    project_id: u64,
+    target_company_id: u64,
+) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageProjects)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    if project.owner_company_id != company_id {
+        return Err("Only the owner company can invite".to_string());
+    }
+
+    // Target company must exist
+    let _target = find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    if target_company_id == company_id {
+        return Err("Cannot invite your own company".to_string());
+    }
+
+    // Require an accepted connection between the two companies
+    let conn = find_connection(ctx, company_id, target_company_id);
+    match conn {
+        Some(c) if c.status == ConnectionStatus::Accepted => { /* ok */ }
+        _ => return Err("You must have an accepted connection with this company first".to_string()),
+    }
+
+    // Check for existing membership
+    let existing: Option<ProjectMember> = ctx
+        .db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .find(|m| m.company_id == target_company_id);
+
+    if let Some(existing_member) = existing {
+        match existing_member.status {
+            ProjectMemberStatus::Accepted => {
+                return Err("Company is already a member of this project".to_string());
+            }
+            ProjectMemberStatus::Invited => {
+                return Err("Company has already been invited".to_string());
+            }
+            ProjectMemberStatus::Left | ProjectMemberStatus::Kicked => {
+                // Clean up old row so we can re-invite
+                ctx.db.project_member().id().delete(existing_member.id);
+            }
+        }
+    }
+
+    ctx.db.project_member().insert(ProjectMember {
+        id: 0,
+        project_id,
+        company_id: target_company_id,
+        status: ProjectMemberStatus::Invited,
+        invited_by: ctx.sender(),
+        joined_at: ctx.timestamp,
+    });
+    post_system_message(
+        ctx,
+        ChatKind::Project,
+        project_id,
+        SystemEvent::ProjectInviteSent(target_company_id),
+    );
+
+    // Notify target company project managers
+    notify_company_permission(
+        ctx,
+        target_company_id,
+        Permission::ManageProjects,
+        None,
+        NotificationType::ProjectInvite,
+        format!("Project invitation: {}", project.name),
+        format!(
+            "Your company has been invited to join project '{}'",
+            project.name
+        ),
+        NotificationRefs {
+            project_id,
+            target_company_id: company_id,
+            ..NotificationRefs::default()
+        },
+    );
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectInviteSent,
+        None,
+        Some(target_company_id),
+        Some(project_id),
+        String::new(),
+        format!("{:?}", ProjectMemberStatus::Invited),
+    );
+
+    Ok(())
+}
+
+/// Accept a pending project invitation. Caller needs ManageProjects in the
+/// invited company and becomes its project Lead, unless they already take part
+/// in the project through another company.
+#[spacetimedb::reducer]
+pub fn accept_project_invite(ctx: &ReducerContext, project_id: u64) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageProjects)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    let membership = find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Invited)
+        .ok_or("No pending invitation found")?;
+
+    ctx.db.project_member().id().update(ProjectMember {
+        status: ProjectMemberStatus::Accepted,
+        joined_at: ctx.timestamp,
+        ..membership
+    });
+    // A user takes part in a project through one company only
+    if find_project_participant(&ctx.as_read_only().db, project_id, ctx.sender()).is_none() {
+        ctx.db.project_participant().insert(ProjectParticipant {
+            id: 0,
+            project_id,
+            company_id,
+            identity: ctx.sender(),
+            role: ProjectRole::Lead,
+            assigned_by: ctx.sender(),
+            assigned_at: ctx.timestamp,
+        });
+    }
+    post_system_message(
+        ctx,
+        ChatKind::Project,
+        project_id,
+        SystemEvent::ProjectJoined(company_id),
+    );
+
+    let company_name = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    // Notify all other Accepted companies' project managers
+    let other_accepted: Vec<u64> = ctx
+        .db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .filter(|m| m.status == ProjectMemberStatus::Accepted && m.company_id != company_id)
+        .map(|m| m.company_id)
+        .collect();
+
+    for cid in other_accepted {
+        notify_company_permission(
+            ctx,
+            cid,
+            Permission::ManageProjects,
+            None,
+            NotificationType::ProjectAccepted,
+            format!("{} joined project", company_name),
+            format!(
+                "{} accepted the invitation to project '{}'",
+                company_name, project.name
+            ),
+            NotificationRefs {
+                project_id,
+                target_company_id: company_id,
+                ..NotificationRefs::default()
+            },
+        );
+    }
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectInviteAccepted,
+        None,
+        None,
+        Some(project_id),
+        format!("{:?}", ProjectMemberStatus::Invited),
+        format!("{:?}", ProjectMemberStatus::Accepted),
+    );
+
+    Ok(())
+}
+
+/// Decline a pending project invitation. Deletes the Invited row.
+#[spacetimedb::reducer]
+pub fn decline_project_invite(ctx: &ReducerContext, project_id: u64) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageProjects)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    let membership = find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Invited)
+        .ok_or("No pending invitation found")?;
+
+    ctx.db.project_member().id().delete(membership.id);
+    post_system_message(
+        ctx,
+        ChatKind::Project,
+        project_id,
+        SystemEvent::ProjectInviteDeclined(company_id),
+    );
+
+    let company_name = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    // Notify owner company project managers
+    notify_company_permission(
+        ctx,
+        project.owner_company_id,
+        Permission::ManageProjects,
+        None,
+        NotificationType::ProjectDeclined,
+        format!("{} declined project invite", company_name),
+        format!(
+            "{} declined the invitation to project '{}'",
+            company_name, project.name
+        ),
+        NotificationRefs {
+            project_id,
+            target_company_id: company_id,
+            ..NotificationRefs::default()
+        },
+    );
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectInviteDeclined,
+        None,
+        None,
+        Some(project_id),
+        format!("{:?}", ProjectMemberStatus::Invited),
+        String::new(),
+    );
+
+    Ok(())
+}
+
+/// Assign a user of the caller's company to a project as Lead, Contributor or
+/// Observer, or change their project role. Caller needs ManageProjects, or
+/// must be a Lead of the project for that company, and the company must be an
+/// Accepted member. Leads cannot assign Leads or change a Lead's role.
+/// Pending members cannot be assigned.
+#[spacetimedb::reducer]
+pub fn assign_project_participant(
+    ctx: &ReducerContext,
+    project_id: u64,
+    target_identity: Identity,
+    role: ProjectRole,
+) -> Result<(), String> {
+    let (company_id, is_manager) = require_project_staffing(ctx, project_id)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Accepted)
+        .ok_or("Your company is not a member of this project")?;
+
+    let target_membership = find_membership(ctx, target_identity, company_id)
+        .ok_or("User is not a member of your company")?;
+    if target_membership.role == UserRole::Pending {
+        return Err("Pending members cannot be assigned to projects".to_string());
+    }
+
+    let existing = find_project_participant(&ctx.as_read_only().db, project_id, target_identity);
+    let touches_lead = role == ProjectRole::Lead
+        || existing
+            .as_ref()
+            .is_some_and(|p| p.role == ProjectRole::Lead);
+    if touches_lead && !is_manager {
+        return Err("Only project managers can assign or change Leads".to_string());
+    }
+
+    let old_value = match existing {
+        Some(existing) if existing.company_id != company_id => {
+            return Err("User is already assigned through another company".to_string());
+        }
+        Some(existing) => {
+            let old_value = format!("{:?}", existing.role);
+            ctx.db
+                .project_participant()
+                .id()
+                .update(ProjectParticipant {
+                    role,
+                    assigned_by: ctx.sender(),
+                    assigned_at: ctx.timestamp,
+                    ..existing
+                });
+            old_value
+        }
+        None => {
+            ctx.db.project_participant().insert(ProjectParticipant {
+                id: 0,
+                project_id,
+                company_id,
+                identity: target_identity,
+                role,
+                assigned_by: ctx.sender(),
+                assigned_at: ctx.timestamp,
+            });
+            String::new()
+        }
+    };
+
+    if target_identity != ctx.sender() {
+        notify(
+            ctx,
+            target_identity,
+            company_id,
+            NotificationType::ProjectAssignment,
+            format!("Assigned to project '{}'", project.name),
+            format!("You are now {:?} on project '{}'", role, project.name),
+            NotificationRefs {
+                project_id,
+                ..NotificationRefs::default()
+            },
+        );
+    }
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectParticipantAssigned,
+        Some(target_identity),
+        None,
+        Some(project_id),
+        old_value,
+        format!("{:?}", role),
+    );
+
+    Ok(())
+}
+
+/// Remove a user of the caller's company from a project. Caller needs
+/// ManageProjects, or must be a Lead of the project for that company (Leads
+/// cannot remove Leads). The company itself stays a member of the project.
+#[spacetimedb::reducer]
+pub fn remove_project_participant(
+    ctx: &ReducerContext,
+    project_id: u64,
+    target_identity: Identity,
+) -> Result<(), String> {
+    let (company_id, is_manager) = require_project_staffing(ctx, project_id)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    let participant = find_project_participant(&ctx.as_read_only().db, project_id, target_identity)
+        .filter(|p| p.company_id == company_id)
+        .ok_or("User is not assigned to this project")?;
+    if participant.role == ProjectRole::Lead && !is_manager {
+        return Err("Only project managers can remove Leads".to_string());
+    }
+
+    ctx.db.project_participant().id().delete(participant.id);
+
+    if target_identity != ctx.sender() {
+        notify(
+            ctx,
+            target_identity,
+            company_id,
+            NotificationType::ProjectAssignment,
+            format!("Removed from project '{}'", project.name),
+            format!("You are no longer assigned to project '{}'", project.name),
+            NotificationRefs {
+                project_id,
+                ..NotificationRefs::default()
+            },
+        );
+    }
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectParticipantRemoved,
+        Some(target_identity),
+        None,
+        Some(project_id),
+        format!("{:?}", participant.role),
+        String::new(),
+    );
+
+    Ok(())
+}
+
+/// Send a chat message in a project room. Caller needs Chat in an Accepted
+/// member company and a Lead or Contributor assignment in the project.
+/// Fan-out notifications to all other participants, or, for a reply
+/// (`parent_id`), only to the author of the parent message. `quoted_id`
+/// quotes another message of the room; `attachment_ids` are the caller's
+/// finished uploads to the room.
+#[spacetimedb::reducer]
+pub fn send_project_chat(
+    ctx: &ReducerContext,
+    project_id: u64,
+    text: String,
+    parent_id: Option<u64>,
+    quoted_id: Option<u64>,
+    attachment_ids: Vec<u64>,
+) -> Result<(), String> {
+    let (account, company_id) = require_permission(ctx, Permission::Chat)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    // Caller's company must be an Accepted member
+    find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Accepted)
+        .ok_or("Your company is not a member of this project")?;
+
+    // Caller must be assigned to the project through that company
+    let participant = find_project_participant(&ctx.as_read_only().db, project_id, ctx.sender())
+        .filter(|p| p.company_id == company_id)
+        .ok_or("You are not assigned to this project")?;
+    if participant.role == ProjectRole::Observer {
+        return Err("Observers cannot post in this project".to_string());
+    }
+
+    let text = text.trim().to_string();
+    validate_not_empty(&text, "Message")?;
+    validate_length(&text, "Message", MAX_MESSAGE)?;
+
+    let reply_to =
+        resolve_chat_references(ctx, ChatKind::Project, project_id, parent_id, quoted_id)?;
+    let mentions = resolve_mentions(ctx, ChatKind::Project, project_id, &text);
+    let attachment_ids =
+        check_chat_attachments(ctx, ChatKind::Project, project_id, attachment_ids)?;
+
+    check_rate_limit(ctx, RateLimitedAction::ProjectChat, company_id)?;
+
+    let message = ctx.db.project_chat().insert(ProjectChat {
+        id: 0,
+        project_id,
+        sender: ctx.sender(),
+        text: text.clone(),
+        created_at: ctx.timestamp,
+        hidden: false,
+        edited_at: None,
+        deleted: false,
+        parent_id: reply_to.as_ref().map(|r| r.root_id),
+        quoted_id,
+        reply_count: 0,
+        mentions: Some(mentions.users.clone()),
+        mentioned_company_ids: Some(mentions.company_ids.clone()),
+        attachment_ids: Some(attachment_ids.clone()),
+        message_kind: MessageKind::User,
+    });
+    link_attachments(ctx, &attachment_ids, message.id);
+    index_chat_message(ctx, ChatKind::Project, message.id, &text);
+
+    let sender_name = account.nickname.clone();
+    let company_name = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+    let preview = truncate_preview(&text, chat_preview_len(ChatKind::Project));
+
+    // Replies notify the parent's author; other messages everyone else
+    // assigned to the project (Chat holders)
+    let mut notification_ids: Vec<u64> = if let Some(reply_to) = reply_to {
+        update_reply_count(ctx, ChatKind::Project, reply_to.root_id, true);
+        notify_chat_reply(
+            ctx,
+            ChatKind::Project,
+            project_id,
+            &reply_to,
+            format!("{} — reply from {}", project.name, company_name),
+            format!("[{}] {} replied: {}", company_name, sender_name, preview),
+        )
+        .into_iter()
+        .collect()
+    } else {
+        notify_project_participants(
+            ctx,
+            project_id,
+            ctx.sender(),
+            NotificationType::ProjectChat,
+            format!("{} — {}", project.name, company_name),
+            format!("[{}] {}: {}", company_name, sender_name, preview),
+        )
+    };
+    notification_ids.extend(notify_mentions(
+        ctx,
+        ChatKind::Project,
+        project_id,
+        &mentions.users,
+        &notification_ids,
+        format!("{} — {} mentioned you", project.name, sender_name),
+        format!("[{}] {}: {}", company_name, sender_name, preview),
+    ));
+    link_notifications_to_message(ctx, notification_ids, message.id);
+    count_new_message(ctx, ChatKind::Project, project_id, message.id);
+
+    Ok(())
+}
+
+/// Leave a project. The owner company cannot leave (must delete the project).
+/// If no Accepted members remain after leaving, the project is auto-deleted.
+#[spacetimedb::reducer]
+pub fn leave_project(ctx: &ReducerContext, project_id: u64) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageProjects)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    if project.owner_company_id == company_id {
+        return Err("Owner company cannot leave. Delete the project instead.".to_string());
+    }
+
+    let membership = find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Accepted)
+        .ok_or("Your company is not a member of this project")?;
+
+    ctx.db.project_member().id().update(ProjectMember {
+        status: ProjectMemberStatus::Left,
+        ..membership
+    });
+    delete_company_participants(ctx, project_id, company_id);
+    post_system_message(
+        ctx,
+        ChatKind::Project,
+        project_id,
+        SystemEvent::ProjectLeft(company_id),
+    );
+
+    let company_name = ctx
+        .db
+        .company()
+        .id()
+        .find(company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    // Notify other Accepted companies' project managers
+    let other_accepted: Vec<u64> = ctx
+        .db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .filter(|m| m.status == ProjectMemberStatus::Accepted && m.company_id != company_id)
+        .map(|m| m.company_id)
+        .collect();
+
+    for cid in &other_accepted {
+        notify_company_permission(
+            ctx,
+            *cid,
+            Permission::ManageProjects,
+            None,
+            NotificationType::ProjectLeft,
+            format!("{} left project", company_name),
+            format!(
+                "{} left project '{}'",
+                company_name, project.name
+            ),
+            NotificationRefs {
+                project_id,
+                target_company_id: company_id,
+                ..NotificationRefs::default()
+            },
+        );
+    }
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectLeft,
+        None,
+        None,
+        Some(project_id),
+        format!("{:?}", ProjectMemberStatus::Accepted),
+        format!("{:?}", ProjectMemberStatus::Left),
+    );
+
+    // If no Accepted members remain at all, auto-delete the project
+    let remaining_accepted = ctx
+        .db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .any(|m| m.status == ProjectMemberStatus::Accepted);
+    if !remaining_accepted {
+        delete_project_cascade(ctx, project_id);
+        record_audit(
+            ctx,
+            company_id,
+            AuditAction::ProjectAutoDeleted,
+            None,
+            None,
+            Some(project_id),
+            project.name.clone(),
+            String::new(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Kick a company from a project. Only the owner company's project managers can kick.
+#[spacetimedb::reducer]
+pub fn kick_from_project(
+    ctx: &ReducerContext,
+    project_id: u64,
+    target_company_id: u64,
+) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageProjects)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    if project.owner_company_id != company_id {
+        return Err("Only the owner company can kick members".to_string());
+    }
+
+    if target_company_id == company_id {
+        return Err("Cannot kick your own company".to_string());
+    }
+
+    find_live_company(&ctx.as_read_only().db, target_company_id)
+        .ok_or("Target company not found")?;
+
+    let membership = find_project_membership(ctx, project_id, target_company_id, ProjectMemberStatus::Accepted)
+        .ok_or("Target company is not an active member")?;
+
+    ctx.db.project_member().id().update(ProjectMember {
+        status: ProjectMemberStatus::Kicked,
+        ..membership
+    });
+    delete_company_participants(ctx, project_id, target_company_id);
+    post_system_message(
+        ctx,
+        ChatKind::Project,
+        project_id,
+        SystemEvent::ProjectKicked(target_company_id),
+    );
+
+    let target_name = ctx
+        .db
+        .company()
+        .id()
+        .find(target_company_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    // Notify kicked company's project managers
+    notify_company_permission(
+        ctx,
+        target_company_id,
+        Permission::ManageProjects,
+        None,
+        NotificationType::ProjectKicked,
+        format!("Removed from project '{}'", project.name),
+        format!(
+            "Your company has been removed from project '{}'",
+            project.name
+        ),
+        NotificationRefs {
+            project_id,
+            target_company_id: company_id,
+            ..NotificationRefs::default()
+        },
+    );
+
+    // Notify other remaining Accepted companies' project managers
+    let other_accepted: Vec<u64> = ctx
+        .db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .filter(|m| {
+            m.status == ProjectMemberStatus::Accepted
+                && m.company_id != company_id
+                && m.company_id != target_company_id
+        })
+        .map(|m| m.company_id)
+        .collect();
+
+    for cid in other_accepted {
+        notify_company_permission(
+            ctx,
+            cid,
+            Permission::ManageProjects,
+            None,
+            NotificationType::ProjectKicked,
+            format!("{} removed from project", target_name),
+            format!(
+                "{} was removed from project '{}'",
+                target_name, project.name
+            ),
+            NotificationRefs {
+                project_id,
+                target_company_id,
+                ..NotificationRefs::default()
+            },
+        );
+    }
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectKicked,
+        None,
+        Some(target_company_id),
+        Some(project_id),
+        format!("{:?}", ProjectMemberStatus::Accepted),
+        format!("{:?}", ProjectMemberStatus::Kicked),
+    );
+
+    Ok(())
+}
+
+/// Delete a project entirely. Only the owner company's project managers can delete.
+/// Cascade-deletes all members and chat messages.
+#[spacetimedb::reducer]
+pub fn delete_project(ctx: &ReducerContext, project_id: u64) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageProjects)?;
+
+    let project = ctx
+        .db
+        .project()
+        .id()
+        .find(project_id)
+        .ok_or("Project not found")?;
+
+    if project.owner_company_id != company_id {
+        return Err("Only the owner company can delete the project".to_string());
     }
 
-    ctx.db.user_account().insert(UserAccount {
-        identity: ctx.sender(),
-        full_name,
-        nickname,
-        email,
-        active_company_id: None,
-        created_at: ctx.timestamp,
-    });
+    let project_name = project.name.clone();
+
+    // Notify all other Accepted members before deleting
+    let other_accepted: Vec<u64> = ctx
+        .db
+        .project_member()
+        .pm_by_project()
+        .filter(&project_id)
+        .filter(|m| m.status == ProjectMemberStatus::Accepted && m.company_id != company_id)
+        .map(|m| m.company_id)
+        .collect();
+
+    for cid in other_accepted {
+        notify_company_permission(
+            ctx,
+            cid,
+            Permission::ManageProjects,
+            None,
+            NotificationType::ProjectLeft,
+            format!("Project '{}' deleted", project_name),
+            format!(
+                "Project '{}' has been deleted by the owner",
+                project_name
+            ),
+            NotificationRefs {
+                target_company_id: company_id,
+                ..NotificationRefs::default()
+            },
+        );
+    }
+
+    delete_project_cascade(ctx, project_id);
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ProjectDeleted,
+        None,
+        None,
+        Some(project_id),
+        project_name,
+        String::new(),
+    );
 
     Ok(())
 }
 
-/// User updates their own profile (nickname and email only).
+// ---------------------------------------------------------------------------
+// Company Channels
+// ---------------------------------------------------------------------------
+
+/// Normalizes a channel name like a slug, dropping a leading `#`, so
+/// `#Werkplaats` becomes `werkplaats`.
+fn normalize_channel_name(raw: &str) -> String {
+    normalize_slug(raw.trim().trim_start_matches('#'))
+}
+
+/// How a channel is named in notifications: `#name`, or "Direct message".
+fn channel_title(channel: &CompanyChannel) -> String {
+    if channel.is_direct {
+        "Direct message".to_string()
+    } else {
+        format!("#{}", channel.name)
+    }
+}
+
+/// Finds a user's membership of a channel, if any.
+fn find_channel_member(
+    db: &LocalReadOnly,
+    channel_id: u64,
+    identity: Identity,
+) -> Option<ChannelMember> {
+    db.channel_member()
+        .channel_member_by_identity()
+        .filter(&identity)
+        .find(|m| m.channel_id == channel_id)
+}
+
+/// Finds a channel of the caller's active company (not a direct conversation).
+fn find_company_channel(
+    ctx: &ReducerContext,
+    channel_id: u64,
+    company_id: u64,
+) -> Result<CompanyChannel, String> {
+    let channel = ctx
+        .db
+        .company_channel()
+        .id()
+        .find(channel_id)
+        .filter(|c| c.company_id == company_id)
+        .ok_or("Channel not found")?;
+    if channel.is_direct {
+        return Err("Direct conversations cannot be managed".to_string());
+    }
+    Ok(channel)
+}
+
+/// Checks that `identity` can be added to a channel of `company_id`: an
+/// activated member of the company.
+fn require_channel_candidate(
+    ctx: &ReducerContext,
+    identity: Identity,
+    company_id: u64,
+) -> Result<(), String> {
+    let membership =
+        find_membership(ctx, identity, company_id).ok_or("User is not a member of your company")?;
+    if membership.role == UserRole::Pending {
+        return Err("Pending members cannot join channels".to_string());
+    }
+    Ok(())
+}
+
+/// Adds `identity` to a channel on behalf of the caller.
+fn insert_channel_member(ctx: &ReducerContext, channel_id: u64, identity: Identity) {
+    ctx.db.channel_member().insert(ChannelMember {
+        id: 0,
+        channel_id,
+        identity,
+        added_by: ctx.sender(),
+        joined_at: ctx.timestamp,
+    });
+}
+
+/// Deletes a channel with its messages, members, read cursors and mutes.
+fn delete_channel_cascade(ctx: &ReducerContext, channel_id: u64) {
+    delete_conversation_messages(ctx, ChatKind::Channel, channel_id);
+    delete_read_cursors(ctx, ChatKind::Channel, channel_id);
+    delete_conversation_mutes(ctx, ChatKind::Channel, channel_id);
+    delete_channel_notifications(ctx, channel_id);
+    let member_ids: Vec<u64> = ctx
+        .db
+        .channel_member()
+        .channel_member_by_channel()
+        .filter(&channel_id)
+        .map(|m| m.id)
+        .collect();
+    for id in member_ids {
+        ctx.db.channel_member().id().delete(id);
+    }
+    ctx.db.company_channel().id().delete(channel_id);
+}
+
+/// Removes a user from a channel along with their read cursor and mute. A
+/// direct conversation both members have left is deleted.
+fn remove_channel_membership(ctx: &ReducerContext, membership: &ChannelMember) {
+    ctx.db.channel_member().id().delete(membership.id);
+    if let Some(cursor) = find_read_cursor(
+        ctx,
+        membership.identity,
+        ChatKind::Channel,
+        membership.channel_id,
+    ) {
+        ctx.db.read_cursor().id().delete(cursor.id);
+    }
+    let mute = ctx
+        .db
+        .conversation_mute()
+        .mute_by_identity()
+        .filter(&membership.identity)
+        .find(|m| m.kind == ChatKind::Channel && m.conversation_id == membership.channel_id);
+    if let Some(mute) = mute {
+        ctx.db.conversation_mute().id().delete(mute.id);
+    }
+
+    let is_direct = ctx
+        .db
+        .company_channel()
+        .id()
+        .find(membership.channel_id)
+        .is_some_and(|c| c.is_direct);
+    let is_empty = ctx
+        .db
+        .channel_member()
+        .channel_member_by_channel()
+        .filter(&membership.channel_id)
+        .next()
+        .is_none();
+    if is_direct && is_empty {
+        delete_channel_cascade(ctx, membership.channel_id);
+    }
+}
+
+/// Sends a chat notification to every member of a channel (except `exclude`)
+/// who can read it (see `notify_in_conversation`). Returns the IDs of the
+/// inserted notifications.
+fn notify_channel_members(
+    ctx: &ReducerContext,
+    channel: &CompanyChannel,
+    exclude: Identity,
+    notification_type: NotificationType,
+    title: String,
+    body: String,
+) -> Vec<u64> {
+    conversation_audience(&ctx.as_read_only().db, ChatKind::Channel, channel.id)
+        .into_iter()
+        .filter(|recipient| *recipient != exclude)
+        .filter_map(|recipient| {
+            notify_in_conversation(
+                ctx,
+                recipient,
+                channel.company_id,
+                notification_type,
+                ChatKind::Channel,
+                channel.id,
+                title.clone(),
+                body.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Creates an internal channel in the caller's company with the caller and
+/// `members` as its members. Caller needs ManageMembers. Names are normalized
+/// (see `normalize_channel_name`) and unique within the company.
 ///
 /// # Errors
 ///
-/// Returns an error if any field is empty or the account does not exist.
+/// Returns an error if the caller lacks ManageMembers, the name is empty, too
+/// long or taken, or one of `members` is not an activated member of the
+/// company.
 #[spacetimedb::reducer]
 #[allow(clippy::needless_pass_by_value)]
-pub fn update_profile(
+pub fn create_channel(
     ctx: &ReducerContext,
-    nickname: String,
-    email: String,
+    name: String,
+    members: Vec<Identity>,
 ) -> Result<(), String> {
-    let nickname = nickname.trim().to_string();
-    let email = email.trim().to_string();
+    let (_account, company_id) = require_permission(ctx, Permission::ManageMembers)?;
 
-    validate_not_empty(&nickname, "Nickname")?;
-    validate_not_empty(&email, "Email")?;
-    validate_length(&nickname, "Nickname", MAX_NICKNAME)?;
-    validate_length(&email, "Email", MAX_EMAIL)?;
-    validate_email(&email)?;
-
-    let account = ctx
+    let name = normalize_channel_name(&name);
+    validate_not_empty(&name, "Channel name")?;
+    validate_length(&name, "Channel name", MAX_CHANNEL_NAME)?;
+    let name_taken = ctx
         .db
-        .user_account()
-        .identity()
-        .find(ctx.sender())
-        .ok_or("Account not found")?;
+        .company_channel()
+        .channel_by_company()
+        .filter(&company_id)
+        .any(|c| !c.is_direct && c.name == name);
+    if name_taken {
+        return Err(format!("Channel #{} already exists", name));
+    }
 
-    ctx.db.user_account().identity().update(UserAccount {
-        nickname,
-        email,
-        ..account
+    let mut identities: Vec<Identity> = vec![ctx.sender()];
+    for identity in members {
+        if !identities.contains(&identity) {
+            require_channel_candidate(ctx, identity, company_id)?;
+            identities.push(identity);
+        }
+    }
+
+    let channel = ctx.db.company_channel().insert(CompanyChannel {
+        id: 0,
+        company_id,
+        name,
+        is_direct: false,
+        created_by: ctx.sender(),
+        created_at: ctx.timestamp,
     });
+    let title = channel_title(&channel);
+    for identity in identities {
+        insert_channel_member(ctx, channel.id, identity);
+        if identity != ctx.sender() {
+            notify(
+                ctx,
+                identity,
+                company_id,
+                NotificationType::ChannelAdded,
+                format!("Added to {}", title),
+                format!("You are now a member of {}", title),
+                NotificationRefs {
+                    channel_id: channel.id,
+                    ..NotificationRefs::default()
+                },
+            );
+        }
+    }
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ChannelCreated,
+        None,
+        None,
+        None,
+        String::new(),
+        title,
+    );
 
     Ok(())
 }
 
-/// Create a new company and link the caller as its owner.
+/// Deletes a channel of the caller's company with all of its messages. Caller
+/// needs ManageMembers. Direct conversations cannot be deleted; they go away
+/// once both members have left.
 ///
 /// # Errors
 ///
-/// Returns an error if any required field is empty, the slug is taken,
-/// or the caller has no account.
+/// Returns an error if the caller lacks ManageMembers or the channel is not
+/// found in their company.
 #[spacetimedb::reducer]
-#[allow(clippy::needless_pass_by_value)]
-pub fn create_company(
+pub fn delete_channel(ctx: &ReducerContext, channel_id: u64) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+    let channel = find_company_channel(ctx, channel_id, company_id)?;
+
+    delete_channel_cascade(ctx, channel_id);
+
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ChannelDeleted,
+        None,
+        None,
+        None,
+        channel_title(&channel),
+        String::new(),
+    );
+
+    Ok(())
+}
+
+/// Adds a colleague to a channel of the caller's company. Caller needs
+/// ManageMembers; Pending members cannot be added.
+///
+/// # Errors
+///
+/// Returns an error if the caller lacks ManageMembers, the channel is not
+/// found in their company, or the user cannot join or already is a member.
+#[spacetimedb::reducer]
+pub fn add_channel_member(
     ctx: &ReducerContext,
-    name: String,
-    slug: String,
-    location: String,
+    channel_id: u64,
+    target_identity: Identity,
 ) -> Result<(), String> {
-    let name = name.trim().to_string();
-    let slug = normalize_slug(&slug);
-    let location = location.trim().to_string();
+    let (_account, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+    let channel = find_company_channel(ctx, channel_id, company_id)?;
 
-    validate_not_empty(&name, "Company name")?;
-    validate_not_empty(&slug, "Slug")?;
-    validate_not_empty(&location, "Location")?;
-    validate_length(&name, "Company name", MAX_COMPANY_NAME)?;
-    validate_length(&slug, "Slug", MAX_SLUG)?;
-    validate_length(&location, "Location", MAX_LOCATION)?;
+    require_channel_candidate(ctx, target_identity, company_id)?;
+    if find_channel_member(&ctx.as_read_only().db, channel_id, target_identity).is_some() {
+        return Err("User is already a member of this channel".to_string());
+    }
 
-    let account = ctx
-        .db
-        .user_account()
-        .identity()
-        .find(ctx.sender())
-        .ok_or("Account not found")?;
+    insert_channel_member(ctx, channel_id, target_identity);
 
-    // Check slug uniqueness (unique constraint will also enforce this)
-    if ctx.db.company().slug().find(&slug).is_some() {
-        return Err("Slug is already taken".to_string());
+    let title = channel_title(&channel);
+    if target_identity != ctx.sender() {
+        notify(
+            ctx,
+            target_identity,
+            company_id,
+            NotificationType::ChannelAdded,
+            format!("Added to {}", title),
+            format!("You are now a member of {}", title),
+            NotificationRefs {
+                channel_id,
+                ..NotificationRefs::default()
+            },
+        );
     }
 
-    let company = ctx.db.company().insert(Company {
-        id: 0,
-        owner_identity: ctx.sender(),
-        name,
-        slug,
-        location,
-        bio: String::new(),
-        is_public: false,
-        kvk_number: String::new(),
-    });
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ChannelMemberAdded,
+        Some(target_identity),
+        None,
+        None,
+        String::new(),
+        title,
+    );
 
-    // Create default capabilities row
-    ctx.db.capability().insert(Capability {
-        company_id: company.id,
-        can_install: false,
-        has_cnc: false,
-        has_large_format: false,
-        has_bucket_truck: false,
-    });
+    Ok(())
+}
 
-    // Create membership as Owner
-    ctx.db.company_member().insert(CompanyMember {
-        id: 0,
-        identity: ctx.sender(),
-        company_id: company.id,
-        role: UserRole::Owner,
-        joined_at: ctx.timestamp,
-    });
+/// Removes a user from a channel. Anyone can leave a channel or direct
+/// conversation they are a member of; removing someone else needs
+/// ManageMembers in the channel's company and is not possible in direct
+/// conversations. Messages stay in the channel.
+///
+/// # Errors
+///
+/// Returns an error if the channel is not found, the caller may not remove
+/// the user, or the user is not a member.
+#[spacetimedb::reducer]
+pub fn remove_channel_member(
+    ctx: &ReducerContext,
+    channel_id: u64,
+    target_identity: Identity,
+) -> Result<(), String> {
+    let removed_by = if target_identity == ctx.sender() {
+        ctx.db
+            .company_channel()
+            .id()
+            .find(channel_id)
+            .ok_or("Channel not found")?;
+        None
+    } else {
+        let (_account, company_id) = require_permission(ctx, Permission::ManageMembers)?;
+        Some((
+            company_id,
+            find_company_channel(ctx, channel_id, company_id)?,
+        ))
+    };
 
-    // Set as active company
-    ctx.db.user_account().identity().update(UserAccount {
-        active_company_id: Some(company.id),
-        ..account
-    });
+    let membership = find_channel_member(&ctx.as_read_only().db, channel_id, target_identity)
+        .ok_or("User is not a member of this channel")?;
+    remove_channel_membership(ctx, &membership);
+
+    if let Some((company_id, channel)) = removed_by {
+        record_audit(
+            ctx,
+            company_id,
+            AuditAction::ChannelMemberRemoved,
+            Some(target_identity),
+            None,
+            None,
+            channel_title(&channel),
+            String::new(),
+        );
+    }
 
     Ok(())
 }
 
-/// Admin generates an invite code for their company.
+/// Opens a direct conversation between the caller and a colleague in their
+/// active company, or does nothing if the two already have one. Both need
+/// Chat there. Clients find the conversation through `my_channels`.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role.
+/// Returns an error if the caller lacks Chat, targets themself, or the
+/// colleague is not a member of the company holding Chat.
 #[spacetimedb::reducer]
-pub fn generate_invite_code(ctx: &ReducerContext, max_uses: u32) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+pub fn open_direct_conversation(
+    ctx: &ReducerContext,
+    colleague_identity: Identity,
+) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::Chat)?;
 
-    // Build a readable 16-character code (XXXX-XXXX-XXXX-XXXX) from the
-    // deterministic RNG. Uses an unambiguous charset (no 0/O/1/I).
-    let charset = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
-    let mut code = String::with_capacity(19); // 16 chars + 3 dashes
-    let mut rng = ctx.rng();
-    for i in 0..16 {
-        if i > 0 && i % 4 == 0 {
-            code.push('-');
-        }
-        #[allow(clippy::cast_possible_truncation)] // Modulo charset.len() keeps value small
-        let idx = (rng.next_u64() as usize) % charset.len();
-        code.push(charset[idx] as char);
+    if colleague_identity == ctx.sender() {
+        return Err("Cannot start a conversation with yourself".to_string());
+    }
+    let db = &ctx.as_read_only().db;
+    find_membership(ctx, colleague_identity, company_id)
+        .filter(|m| has_permission(db, m, Permission::Chat))
+        .ok_or("Colleague is not in your company")?;
+
+    let existing = ctx
+        .db
+        .channel_member()
+        .channel_member_by_identity()
+        .filter(&ctx.sender())
+        .filter_map(|m| ctx.db.company_channel().id().find(m.channel_id))
+        .any(|c| {
+            c.is_direct
+                && c.company_id == company_id
+                && find_channel_member(db, c.id, colleague_identity).is_some()
+        });
+    if existing {
+        return Ok(());
     }
 
-    ctx.db.invite_code().insert(InviteCode {
-        code,
+    let channel = ctx.db.company_channel().insert(CompanyChannel {
+        id: 0,
         company_id,
+        name: String::new(),
+        is_direct: true,
         created_by: ctx.sender(),
-        uses_remaining: if max_uses == 0 { 1 } else { max_uses },
+        created_at: ctx.timestamp,
     });
+    insert_channel_member(ctx, channel.id, ctx.sender());
+    insert_channel_member(ctx, channel.id, colleague_identity);
 
     Ok(())
 }
 
-/// User joins a company using an invite code. Users can join multiple
-/// companies — each membership is independent with its own role.
-///
-/// # Errors
-///
-/// Returns an error if the caller has no account, is already a member of
-/// this specific company, the code is invalid, or the code has no remaining uses.
+/// Send a message in a channel or direct conversation. Caller needs Chat in
+/// the channel's company (their active company) and must be a member.
+/// Fan-out notifications go to all other members, or, for a reply
+/// (`parent_id`), only to the author of the parent message. `quoted_id`
+/// quotes another message of the channel; `attachment_ids` are the caller's
+/// finished uploads to the channel.
 #[spacetimedb::reducer]
-#[allow(clippy::needless_pass_by_value)]
-pub fn join_company(ctx: &ReducerContext, code: String) -> Result<(), String> {
-    let code = code.trim().to_uppercase();
-    validate_length(&code, "Invite code", MAX_INVITE_CODE)?;
-    validate_invite_code_format(&code)?;
+pub fn send_channel_chat(
+    ctx: &ReducerContext,
+    channel_id: u64,
+    text: String,
+    parent_id: Option<u64>,
+    quoted_id: Option<u64>,
+    attachment_ids: Vec<u64>,
+) -> Result<(), String> {
+    let company_id = require_chat_poster(ctx, ChatKind::Channel, channel_id)?;
+    let channel = ctx
+        .db
+        .company_channel()
+        .id()
+        .find(channel_id)
+        .ok_or("Channel not found")?;
 
-    let account = ctx
+    let text = text.trim().to_string();
+    validate_not_empty(&text, "Message")?;
+    validate_length(&text, "Message", MAX_MESSAGE)?;
+
+    let reply_to =
+        resolve_chat_references(ctx, ChatKind::Channel, channel_id, parent_id, quoted_id)?;
+    let mentions = resolve_mentions(ctx, ChatKind::Channel, channel_id, &text);
+    let attachment_ids =
+        check_chat_attachments(ctx, ChatKind::Channel, channel_id, attachment_ids)?;
+
+    check_rate_limit(ctx, RateLimitedAction::ChannelChat, company_id)?;
+
+    let message = ctx.db.channel_chat().insert(ChannelChat {
+        id: 0,
+        channel_id,
+        sender: ctx.sender(),
+        text: text.clone(),
+        created_at: ctx.timestamp,
+        hidden: false,
+        edited_at: None,
+        deleted: false,
+        parent_id: reply_to.as_ref().map(|r| r.root_id),
+        quoted_id,
+        reply_count: 0,
+        mentions: mentions.users.clone(),
+        mentioned_company_ids: mentions.company_ids.clone(),
+        attachment_ids: attachment_ids.clone(),
+        message_kind: MessageKind::User,
+    });
+    link_attachments(ctx, &attachment_ids, message.id);
+    index_chat_message(ctx, ChatKind::Channel, message.id, &text);
+
+    let sender_name = ctx
         .db
         .user_account()
         .identity()
         .find(ctx.sender())
-        .ok_or("Account not found")?;
+        .map(|a| a.nickname)
+        .unwrap_or_default();
+    let title = channel_title(&channel);
+    let body = format!(
+        "{}: {}",
+        sender_name,
+        truncate_preview(&text, chat_preview_len(ChatKind::Channel))
+    );
 
-    let invite = ctx
-        .db
-        .invite_code()
-        .code()
-        .find(&code)
-        .ok_or("Invalid invite code")?;
+    // Replies notify the parent's author; other messages everyone else in
+    // the channel
+    let mut notification_ids: Vec<u64> = if let Some(reply_to) = reply_to {
+        update_reply_count(ctx, ChatKind::Channel, reply_to.root_id, true);
+        notify_chat_reply(
+            ctx,
+            ChatKind::Channel,
+            channel_id,
+            &reply_to,
+            format!("{} — reply from {}", title, sender_name),
+            body.clone(),
+        )
+        .into_iter()
+        .collect()
+    } else {
+        notify_channel_members(
+            ctx,
+            &channel,
+            ctx.sender(),
+            NotificationType::ChannelMessage,
+            format!("{} — {}", title, sender_name),
+            body.clone(),
+        )
+    };
+    notification_ids.extend(notify_mentions(
+        ctx,
+        ChatKind::Channel,
+        channel_id,
+        &mentions.users,
+        &notification_ids,
+        format!("{} — {} mentioned you", title, sender_name),
+        body,
+    ));
+    link_notifications_to_message(ctx, notification_ids, message.id);
+    count_new_message(ctx, ChatKind::Channel, channel_id, message.id);
 
-    // Check not already a member of THIS company
-    if find_membership(ctx, ctx.sender(), invite.company_id).is_some() {
-        return Err("You are already a member of this company".to_string());
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Chat Threads and Editing
+// ---------------------------------------------------------------------------
+
+/// The thread a new message joins and the author of the message it answers.
+struct ReplyTarget {
+    root_id: u64,
+    parent_author: Identity,
+}
+
+/// Checks the optional parent and quoted message of a new message in
+/// conversation `conversation_id` (a connection, project or channel ID). Both
+/// must be visible user messages of the same conversation. A reply to a reply joins the
+/// parent's thread, so threads stay one level deep.
+///
+/// # Errors
+///
+/// Returns an error if either referenced message is not found in the
+/// conversation, or is hidden or deleted.
+fn resolve_chat_references(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    parent_id: Option<u64>,
+    quoted_id: Option<u64>,
+) -> Result<Option<ReplyTarget>, String> {
+    let is_referenceable = |m: &ChatMessageInfo| {
+        m.conversation_id == conversation_id && !m.hidden && !m.deleted && !m.is_system
+    };
+
+    if let Some(id) = quoted_id {
+        find_chat_message(ctx, kind, id)
+            .ok()
+            .filter(is_referenceable)
+            .ok_or("Quoted message not found")?;
     }
 
-    if invite.uses_remaining == 0 {
-        return Err("Invite code has been fully used".to_string());
+    let Some(id) = parent_id else {
+        return Ok(None);
+    };
+    let parent = find_chat_message(ctx, kind, id)
+        .ok()
+        .filter(is_referenceable)
+        .ok_or("Parent message not found")?;
+    Ok(Some(ReplyTarget {
+        root_id: parent.parent_id.unwrap_or(id),
+        parent_author: parent.sender,
+    }))
+}
+
+/// Counts a new reply on its thread's first message, or uncounts a deleted
+/// one.
+fn update_reply_count(ctx: &ReducerContext, kind: ChatKind, root_id: u64, added: bool) {
+    let count = |reply_count: u32| {
+        if added {
+            reply_count + 1
+        } else {
+            reply_count.saturating_sub(1)
+        }
+    };
+    match kind {
+        ChatKind::Connection => {
+            if let Some(root) = ctx.db.connection_chat().id().find(root_id) {
+                ctx.db.connection_chat().id().update(ConnectionChat {
+                    reply_count: count(root.reply_count),
+                    ..root
+                });
+            }
+        }
+        ChatKind::Project => {
+            if let Some(root) = ctx.db.project_chat().id().find(root_id) {
+                ctx.db.project_chat().id().update(ProjectChat {
+                    reply_count: count(root.reply_count),
+                    ..root
+                });
+            }
+        }
+        ChatKind::Channel => {
+            if let Some(root) = ctx.db.channel_chat().id().find(root_id) {
+                ctx.db.channel_chat().id().update(ChannelChat {
+                    reply_count: count(root.reply_count),
+                    ..root
+                });
+            }
+        }
     }
+}
 
-    // Check if this user has already used this specific code
-    let already_used = ctx
-        .db
-        .used_invite_code()
-        .used_by_identity()
-        .filter(&ctx.sender())
-        .any(|u| u.code == code);
-    if already_used {
-        return Err("You have already used this invite code".to_string());
+/// The company through which `identity` reads a conversation: one of the
+/// connection's companies, the company they were assigned to the project
+/// through, or the company of a channel they are a member of. They must hold
+/// Chat there.
+fn chat_company_for(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    identity: Identity,
+) -> Option<u64> {
+    let db = &ctx.as_read_only().db;
+    let candidates: Vec<u64> = match kind {
+        ChatKind::Connection => ctx
+            .db
+            .company_connection()
+            .id()
+            .find(conversation_id)
+            .map(|c| vec![c.company_a, c.company_b])
+            .unwrap_or_default(),
+        ChatKind::Project => find_project_participant(db, conversation_id, identity)
+            .map(|p| p.company_id)
+            .into_iter()
+            .collect(),
+        ChatKind::Channel => ctx
+            .db
+            .company_channel()
+            .id()
+            .find(conversation_id)
+            .filter(|c| find_channel_member(db, c.id, identity).is_some())
+            .map(|c| c.company_id)
+            .into_iter()
+            .collect(),
+    };
+    candidates.into_iter().find(|cid| {
+        find_membership(ctx, identity, *cid)
+            .is_some_and(|m| has_permission(db, &m, Permission::Chat))
+    })
+}
+
+/// Notifies the author of the message being replied to, in the company
+/// through which they read the conversation. Replies to one's own or
+/// anonymised messages notify no one.
+fn notify_chat_reply(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    reply_to: &ReplyTarget,
+    title: String,
+    body: String,
+) -> Option<u64> {
+    let author = reply_to.parent_author;
+    if author == ctx.sender() || author == Identity::ZERO {
+        return None;
     }
+    let company_id = chat_company_for(ctx, kind, conversation_id, author)?;
+    notify_in_conversation(
+        ctx,
+        author,
+        company_id,
+        chat_notification_types(kind).reply,
+        kind,
+        conversation_id,
+        title,
+        body,
+    )
+}
 
-    // Record the usage before linking
-    ctx.db.used_invite_code().insert(UsedInviteCode {
-        id: 0,
-        identity: ctx.sender(),
-        code: code.clone(),
-        company_id: invite.company_id,
-    });
+/// Characters of message text quoted in chat notifications.
+const fn chat_preview_len(kind: ChatKind) -> usize {
+    match kind {
+        ChatKind::Connection => 50,
+        ChatKind::Project | ChatKind::Channel => 80,
+    }
+}
 
-    // Create membership as Pending — an Admin or Owner must activate them
-    ctx.db.company_member().insert(CompanyMember {
+/// Notification types raised for new messages of one `ChatKind`.
+#[derive(Clone, Copy)]
+struct ChatNotificationTypes {
+    /// Fan-out to the conversation, coalesced per conversation.
+    message: NotificationType,
+    /// To the author of the message replied to.
+    reply: NotificationType,
+    /// To each @mentioned user.
+    mention: NotificationType,
+}
+
+impl ChatNotificationTypes {
+    fn contains(self, notification_type: NotificationType) -> bool {
+        [self.message, self.reply, self.mention].contains(&notification_type)
+    }
+}
+
+/// Notification types raised for new messages of `kind`.
+const fn chat_notification_types(kind: ChatKind) -> ChatNotificationTypes {
+    match kind {
+        ChatKind::Connection => ChatNotificationTypes {
+            message: NotificationType::ChatMessage,
+            reply: NotificationType::ChatReply,
+            mention: NotificationType::ChatMention,
+        },
+        ChatKind::Project => ChatNotificationTypes {
+            message: NotificationType::ProjectChat,
+            reply: NotificationType::ProjectChatReply,
+            mention: NotificationType::ProjectChatMention,
+        },
+        ChatKind::Channel => ChatNotificationTypes {
+            message: NotificationType::ChannelMessage,
+            reply: NotificationType::ChannelReply,
+            mention: NotificationType::ChannelMention,
+        },
+    }
+}
+
+/// The parts of a chat message of either kind that editing and deletion need.
+struct ChatMessageInfo {
+    sender: Identity,
+    text: String,
+    created_at: Timestamp,
+    deleted: bool,
+    hidden: bool,
+    /// Posted by the module to record a `SystemEvent`.
+    is_system: bool,
+    /// The connection, project or channel the message was sent in.
+    conversation_id: u64,
+    parent_id: Option<u64>,
+    mentions: Vec<Identity>,
+    attachment_ids: Vec<u64>,
+    /// Companies taking part in the conversation; none for direct
+    /// conversations, whose messages only their sender can delete.
+    company_ids: Vec<u64>,
+    project_id: Option<u64>,
+}
+
+/// Looks up a chat message of either kind.
+fn find_chat_message(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    message_id: u64,
+) -> Result<ChatMessageInfo, String> {
+    match kind {
+        ChatKind::Connection => {
+            let msg = ctx
+                .db
+                .connection_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            let company_ids = ctx
+                .db
+                .company_connection()
+                .id()
+                .find(msg.connection_id)
+                .map(|c| vec![c.company_a, c.company_b])
+                .unwrap_or_default();
+            Ok(ChatMessageInfo {
+                sender: msg.sender,
+                text: msg.text,
+                created_at: msg.created_at,
+                deleted: msg.deleted,
+                hidden: msg.hidden,
+                is_system: msg.message_kind != MessageKind::User,
+                conversation_id: msg.connection_id,
+                parent_id: msg.parent_id,
+                mentions: msg.mentions.unwrap_or_default(),
+                attachment_ids: msg.attachment_ids.unwrap_or_default(),
+                company_ids,
+                project_id: None,
+            })
+        }
+        ChatKind::Project => {
+            let msg = ctx
+                .db
+                .project_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            let company_ids = ctx
+                .db
+                .project_member()
+                .pm_by_project()
+                .filter(&msg.project_id)
+                .filter(|m| m.status == ProjectMemberStatus::Accepted)
+                .map(|m| m.company_id)
+                .collect();
+            Ok(ChatMessageInfo {
+                sender: msg.sender,
+                text: msg.text,
+                created_at: msg.created_at,
+                deleted: msg.deleted,
+                hidden: msg.hidden,
+                is_system: msg.message_kind != MessageKind::User,
+                conversation_id: msg.project_id,
+                parent_id: msg.parent_id,
+                mentions: msg.mentions.unwrap_or_default(),
+                attachment_ids: msg.attachment_ids.unwrap_or_default(),
+                company_ids,
+                project_id: Some(msg.project_id),
+            })
+        }
+        ChatKind::Channel => {
+            let msg = ctx
+                .db
+                .channel_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            let company_ids = ctx
+                .db
+                .company_channel()
+                .id()
+                .find(msg.channel_id)
+                .filter(|c| !c.is_direct)
+                .map(|c| vec![c.company_id])
+                .unwrap_or_default();
+            Ok(ChatMessageInfo {
+                sender: msg.sender,
+                text: msg.text,
+                created_at: msg.created_at,
+                deleted: msg.deleted,
+                hidden: msg.hidden,
+                is_system: msg.message_kind != MessageKind::User,
+                conversation_id: msg.channel_id,
+                parent_id: msg.parent_id,
+                mentions: msg.mentions,
+                attachment_ids: msg.attachment_ids,
+                company_ids,
+                project_id: None,
+            })
+        }
+    }
+}
+
+/// Writes a message's new text and mentions and flags it as edited, or as
+/// deleted (which also drops its attachment references).
+fn store_chat_text(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    message_id: u64,
+    text: String,
+    mentions: Mentions,
+    deleted: bool,
+) {
+    match kind {
+        ChatKind::Connection => {
+            if let Some(msg) = ctx.db.connection_chat().id().find(message_id) {
+                let edited_at = if deleted {
+                    msg.edited_at
+                } else {
+                    Some(ctx.timestamp)
+                };
+                let attachment_ids = if deleted {
+                    Some(Vec::new())
+                } else {
+                    msg.attachment_ids.clone()
+                };
+                ctx.db.connection_chat().id().update(ConnectionChat {
+                    text,
+                    edited_at,
+                    deleted,
+                    mentions: Some(mentions.users),
+                    mentioned_company_ids: Some(mentions.company_ids),
+                    attachment_ids,
+                    ..msg
+                });
+            }
+        }
+        ChatKind::Project => {
+            if let Some(msg) = ctx.db.project_chat().id().find(message_id) {
+                let edited_at = if deleted {
+                    msg.edited_at
+                } else {
+                    Some(ctx.timestamp)
+                };
+                let attachment_ids = if deleted {
+                    Some(Vec::new())
+                } else {
+                    msg.attachment_ids.clone()
+                };
+                ctx.db.project_chat().id().update(ProjectChat {
+                    text,
+                    edited_at,
+                    deleted,
+                    mentions: Some(mentions.users),
+                    mentioned_company_ids: Some(mentions.company_ids),
+                    attachment_ids,
+                    ..msg
+                });
+            }
+        }
+        ChatKind::Channel => {
+            if let Some(msg) = ctx.db.channel_chat().id().find(message_id) {
+                let edited_at = if deleted {
+                    msg.edited_at
+                } else {
+                    Some(ctx.timestamp)
+                };
+                let attachment_ids = if deleted {
+                    Vec::new()
+                } else {
+                    msg.attachment_ids.clone()
+                };
+                ctx.db.channel_chat().id().update(ChannelChat {
+                    text,
+                    edited_at,
+                    deleted,
+                    mentions: mentions.users,
+                    mentioned_company_ids: mentions.company_ids,
+                    attachment_ids,
+                    ..msg
+                });
+            }
+        }
+    }
+}
+
+/// Keeps a message's current text as a revision before it is changed.
+fn record_chat_revision(ctx: &ReducerContext, kind: ChatKind, message_id: u64, text: String) {
+    ctx.db.chat_revision().insert(ChatRevision {
         id: 0,
-        identity: ctx.sender(),
-        company_id: invite.company_id,
-        role: UserRole::Pending,
-        joined_at: ctx.timestamp,
+        kind,
+        message_id,
+        text,
+        revised_by: ctx.sender(),
+        revised_at: ctx.timestamp,
     });
+}
 
-    // Capture before potential move
-    let joiner_name = account.nickname.clone();
+/// Deletes the revision history of a message (the message itself is going away).
+fn delete_chat_revisions(ctx: &ReducerContext, kind: ChatKind, message_id: u64) {
+    let revision_ids: Vec<u64> = ctx
+        .db
+        .chat_revision()
+        .revision_by_message()
+        .filter(&message_id)
+        .filter(|r| r.kind == kind)
+        .map(|r| r.id)
+        .collect();
+    for id in revision_ids {
+        ctx.db.chat_revision().id().delete(id);
+    }
+}
 
-    // Set as active company if user has no active company
-    if account.active_company_id.is_none() {
-        ctx.db.user_account().identity().update(UserAccount {
-            active_company_id: Some(invite.company_id),
-            ..account
+/// Clears the caller as author of a message's revisions (account deletion).
+fn anonymise_chat_revisions(ctx: &ReducerContext, kind: ChatKind, message_id: u64) {
+    let revisions: Vec<ChatRevision> = ctx
+        .db
+        .chat_revision()
+        .revision_by_message()
+        .filter(&message_id)
+        .filter(|r| r.kind == kind && r.revised_by == ctx.sender())
+        .collect();
+    for revision in revisions {
+        ctx.db.chat_revision().id().update(ChatRevision {
+            revised_by: Identity::ZERO,
+            ..revision
         });
     }
+}
 
-    // Decrement uses (or delete if last use).
-    // This is atomic: SpacetimeDB reducers are transactional, so the
-    // find → check → decrement sequence cannot race with another caller.
-    if invite.uses_remaining <= 1 {
-        ctx.db.invite_code().code().delete(&code);
-    } else {
-        ctx.db.invite_code().code().update(InviteCode {
-            uses_remaining: invite.uses_remaining - 1,
-            ..invite
+/// Removes a user from the mentions of every message (account deletion).
+/// Mentions are not indexed, so this scans the chat tables.
+fn remove_mentions_of(ctx: &ReducerContext, identity: Identity) {
+    let without = |ids: &[Identity]| {
+        ids.iter()
+            .copied()
+            .filter(|id| *id != identity)
+            .collect::<Vec<_>>()
+    };
+    let conn_chat: Vec<ConnectionChat> = ctx
+        .db
+        .connection_chat()
+        .iter()
+        .filter(|m| {
+            m.mentions
+                .as_ref()
+                .is_some_and(|ids| ids.contains(&identity))
+        })
+        .collect();
+    for msg in conn_chat {
+        let mentions = msg.mentions.as_deref().map(without);
+        ctx.db
+            .connection_chat()
+            .id()
+            .update(ConnectionChat { mentions, ..msg });
+    }
+    let project_chat: Vec<ProjectChat> = ctx
+        .db
+        .project_chat()
+        .iter()
+        .filter(|m| {
+            m.mentions
+                .as_ref()
+                .is_some_and(|ids| ids.contains(&identity))
+        })
+        .collect();
+    for msg in project_chat {
+        let mentions = msg.mentions.as_deref().map(without);
+        ctx.db
+            .project_chat()
+            .id()
+            .update(ProjectChat { mentions, ..msg });
+    }
+    let channel_chat: Vec<ChannelChat> = ctx
+        .db
+        .channel_chat()
+        .iter()
+        .filter(|m| m.mentions.contains(&identity))
+        .collect();
+    for msg in channel_chat {
+        let mentions = without(&msg.mentions);
+        ctx.db
+            .channel_chat()
+            .id()
+            .update(ChannelChat { mentions, ..msg });
+    }
+}
+
+/// Notifications raised for a message, matched on its kind's notification type.
+fn chat_notifications(ctx: &ReducerContext, kind: ChatKind, message_id: u64) -> Vec<Notification> {
+    ctx.db
+        .notification()
+        .notif_by_message()
+        .filter(&message_id)
+        .filter(|n| chat_notification_types(kind).contains(n.notification_type))
+        .collect()
+}
+
+/// Edits one of the caller's own chat messages within
+/// `CHAT_EDIT_WINDOW_MINUTES` of sending. The previous text is kept in
+/// `ChatRevision`, notifications about the message quote the new text, and
+/// users newly @mentioned by the edit are notified.
+///
+/// # Errors
+///
+/// Returns an error if the message is not found, is a system message, was not
+/// sent by the caller, is deleted, archived or past the edit window, the
+/// caller can no longer post in its conversation, or the new text is empty or
+/// too long.
+#[spacetimedb::reducer]
+#[allow(clippy::needless_pass_by_value)]
+pub fn edit_chat_message(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    message_id: u64,
+    text: String,
+) -> Result<(), String> {
+    let text = text.trim().to_string();
+    validate_not_empty(&text, "Message")?;
+    validate_length(&text, "Message", MAX_MESSAGE)?;
+
+    let message = find_chat_message(ctx, kind, message_id)?;
+    if message.is_system {
+        return Err("System messages cannot be edited".to_string());
+    }
+    if message.sender != ctx.sender() {
+        return Err("You can only edit your own messages".to_string());
+    }
+    require_not_archived(ctx, kind, message.conversation_id)?;
+    require_chat_poster(ctx, kind, message.conversation_id)?;
+    if message.deleted {
+        return Err("Message has been deleted".to_string());
+    }
+    let deadline = message.created_at + Duration::from_secs(CHAT_EDIT_WINDOW_MINUTES * 60);
+    if ctx.timestamp > deadline {
+        return Err(format!(
+            "Messages can only be edited within {} minutes of sending",
+            CHAT_EDIT_WINDOW_MINUTES
+        ));
+    }
+    if message.text == text {
+        return Ok(());
+    }
+
+    // Update notification previews in place
+    let old_preview = truncate_preview(&message.text, chat_preview_len(kind));
+    let new_preview = truncate_preview(&text, chat_preview_len(kind));
+    let mut existing_ids: Vec<u64> = Vec::new();
+    let mut body_prefix: Option<String> = None;
+    for notification in chat_notifications(ctx, kind, message_id) {
+        existing_ids.push(notification.id);
+        if let Some(prefix) = notification.body.strip_suffix(old_preview.as_str()) {
+            let body = format!("{}{}", prefix, new_preview);
+            body_prefix.get_or_insert_with(|| prefix.to_string());
+            ctx.db.notification().id().update(Notification {
+                body,
+                ..notification
+            });
+        }
+    }
+
+    // Notify users the edit mentions for the first time
+    let mentions = resolve_mentions(ctx, kind, message.conversation_id, &text);
+    let newly_mentioned: Vec<Identity> = mentions
+        .users
+        .iter()
+        .copied()
+        .filter(|id| !message.mentions.contains(id))
+        .collect();
+    if !newly_mentioned.is_empty() {
+        let sender_name = ctx
+            .db
+            .user_account()
+            .identity()
+            .find(ctx.sender())
+            .map(|a| a.nickname)
+            .unwrap_or_default();
+        let body_prefix = body_prefix.unwrap_or_else(|| match kind {
+            ChatKind::Connection | ChatKind::Channel => format!("{}: ", sender_name),
+            ChatKind::Project => {
+                let company_name =
+                    chat_company_for(ctx, kind, message.conversation_id, ctx.sender())
+                        .and_then(|cid| ctx.db.company().id().find(cid))
+                        .map(|c| c.name)
+                        .unwrap_or_default();
+                format!("[{}] {}: ", company_name, sender_name)
+            }
         });
+        let title = match kind {
+            ChatKind::Connection => format!("{} mentioned you", sender_name),
+            ChatKind::Project => {
+                let project_name = ctx
+                    .db
+                    .project()
+                    .id()
+                    .find(message.conversation_id)
+                    .map(|p| p.name)
+                    .unwrap_or_default();
+                format!("{} — {} mentioned you", project_name, sender_name)
+            }
+            ChatKind::Channel => {
+                let title = ctx
+                    .db
+                    .company_channel()
+                    .id()
+                    .find(message.conversation_id)
+                    .map(|c| channel_title(&c))
+                    .unwrap_or_default();
+                format!("{} — {} mentioned you", title, sender_name)
+            }
+        };
+        let new_ids = notify_mentions(
+            ctx,
+            kind,
+            message.conversation_id,
+            &newly_mentioned,
+            &existing_ids,
+            title,
+            format!("{}{}", body_prefix, new_preview),
+        );
+        link_notifications_to_message(ctx, new_ids, message_id);
     }
 
-    // Notify admins/owners that a new member joined
-    let company_name = ctx.db.company().id().find(invite.company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_default();
-    notify_company_role(
-        ctx,
-        invite.company_id,
-        UserRole::Admin,
-        Some(ctx.sender()),
-        NotificationType::MemberJoined,
-        "New member joined".to_string(),
-        format!("{} joined {}", joiner_name, company_name),
-    );
+    record_chat_revision(ctx, kind, message_id, message.text);
+    index_chat_message(ctx, kind, message_id, &text);
+    store_chat_text(ctx, kind, message_id, text, mentions, false);
 
     Ok(())
 }
 
-/// Admin deletes an invite code.
+/// Soft-deletes a chat message: its text is cleared and moved to
+/// `ChatRevision`, the message is flagged as deleted and unpinned, and
+/// notifications about it are withdrawn. Senders can delete their own
+/// messages while they can still post in the conversation; members holding
+/// ManageMembers can delete messages that colleagues with a lower role, or
+/// since deleted accounts, sent in their company's connections, projects and
+/// channels (but not in direct conversations).
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role, the code is not found,
-/// or the code belongs to a different company.
+/// Returns an error if the message is not found, is a system message, is
+/// already deleted or archived, or the caller is neither its sender nor
+/// allowed to moderate it.
 #[spacetimedb::reducer]
-#[allow(clippy::needless_pass_by_value)]
-pub fn delete_invite_code(ctx: &ReducerContext, code: String) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+pub fn delete_chat_message(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    message_id: u64,
+) -> Result<(), String> {
+    let message = find_chat_message(ctx, kind, message_id)?;
+    if message.is_system {
+        return Err("System messages cannot be deleted".to_string());
+    }
+    if message.deleted {
+        return Err("Message has already been deleted".to_string());
+    }
+    require_not_archived(ctx, kind, message.conversation_id)?;
 
-    let invite = ctx
-        .db
-        .invite_code()
-        .code()
-        .find(&code)
-        .ok_or("Invite code not found")?;
+    if message.sender == ctx.sender() {
+        require_chat_poster(ctx, kind, message.conversation_id)?;
+    } else {
+        let (_account, company_id) = require_permission(ctx, Permission::ManageMembers)
+            .map_err(|_| "You can only delete your own messages".to_string())?;
+        if !message.company_ids.contains(&company_id) {
+            return Err("You can only delete your own messages".to_string());
+        }
+        // Messages of deleted accounts have no sender left to outrank
+        if message.sender != Identity::ZERO {
+            let sender_membership = find_membership(ctx, message.sender, company_id)
+                .ok_or("You can only delete your own messages")?;
+            let caller_membership =
+                find_membership(ctx, ctx.sender(), company_id).ok_or("Not permitted")?;
+
+            // Hierarchy: same rule as `remove_colleague`
+            if role_level(sender_membership.role) >= role_level(caller_membership.role) {
+                return Err(
+                    "You can only delete messages of members with a lower role than yours"
+                        .to_string(),
+                );
+            }
+        }
+        record_audit(
+            ctx,
+            company_id,
+            AuditAction::ChatMessageDeleted,
+            Some(message.sender),
+            None,
+            message.project_id,
+            truncate_preview(&message.text, 80),
+            String::new(),
+        );
+    }
 
-    if invite.company_id != company_id {
-        return Err("Invite code not found".to_string());
+    withdraw_chat_notifications(ctx, kind, message_id);
+
+    for id in &message.attachment_ids {
+        if let Some(attachment) = ctx.db.attachment().id().find(*id) {
+            purge_attachment(ctx, &attachment);
+        }
+    }
+
+    if let Some(root_id) = message.parent_id {
+        update_reply_count(ctx, kind, root_id, false);
+    }
+    let conversation_id = message.conversation_id;
+    record_chat_revision(ctx, kind, message_id, message.text);
+    delete_message_pins(ctx, kind, message_id);
+    unindex_chat_message(ctx, kind, message_id);
+    store_chat_text(
+        ctx,
+        kind,
+        message_id,
+        String::new(),
+        Mentions::default(),
+        true,
+    );
+    if !message.hidden {
+        count_message_visibility(
+            ctx,
+            kind,
+            conversation_id,
+            message_id,
+            message.sender,
+            false,
+        );
     }
 
-    ctx.db.invite_code().code().delete(&code);
     Ok(())
 }
 
 // ---------------------------------------------------------------------------
-// Phase 2 — Team Management
+// System Messages and Pins
 // ---------------------------------------------------------------------------
 
-/// Admin adds another registered user to their company.
+/// Text shown for a system message.
+fn system_event_text(ctx: &ReducerContext, event: &SystemEvent) -> String {
+    let company_name = |cid: u64| {
+        ctx.db
+            .company()
+            .id()
+            .find(cid)
+            .map(|c| c.name)
+            .unwrap_or_else(|| "Unknown".to_string())
+    };
+    match event {
+        SystemEvent::ConnectionAccepted(cid) => {
+            format!("{} accepted the connection", company_name(*cid))
+        }
+        SystemEvent::ProjectInviteSent(cid) => {
+            format!("{} was invited to the project", company_name(*cid))
+        }
+        SystemEvent::ProjectInviteDeclined(cid) => {
+            format!("{} declined the invitation", company_name(*cid))
+        }
+        SystemEvent::ProjectJoined(cid) => format!("{} joined the project", company_name(*cid)),
+        SystemEvent::ProjectLeft(cid) => format!("{} left the project", company_name(*cid)),
+        SystemEvent::ProjectKicked(cid) => {
+            format!("{} was removed from the project", company_name(*cid))
+        }
+        SystemEvent::ProjectRenamed(name) => format!("The project was renamed to '{}'", name),
+        SystemEvent::ProjectDescriptionChanged => "The project description was updated".to_string(),
+    }
+}
+
+/// Posts a system message recording `event` in a conversation, with the
+/// caller as sender. System messages count as unread like other messages but
+/// raise no notifications, are not indexed for search and cannot be edited,
+/// deleted or replied to.
+fn post_system_message(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    event: SystemEvent,
+) {
+    let text = system_event_text(ctx, &event);
+    let message_kind = MessageKind::System(event);
+    let message_id = match kind {
+        ChatKind::Connection => {
+            ctx.db
+                .connection_chat()
+                .insert(ConnectionChat {
+                    id: 0,
+                    connection_id: conversation_id,
+                    sender: ctx.sender(),
+                    text,
+                    created_at: ctx.timestamp,
+                    hidden: false,
+                    edited_at: None,
+                    deleted: false,
+                    parent_id: None,
+                    quoted_id: None,
+                    reply_count: 0,
+                    mentions: Some(Vec::new()),
+                    mentioned_company_ids: Some(Vec::new()),
+                    attachment_ids: Some(Vec::new()),
+                    message_kind,
+                })
+                .id
+        }
+        ChatKind::Project => {
+            ctx.db
+                .project_chat()
+                .insert(ProjectChat {
+                    id: 0,
+                    project_id: conversation_id,
+                    sender: ctx.sender(),
+                    text,
+                    created_at: ctx.timestamp,
+                    hidden: false,
+                    edited_at: None,
+                    deleted: false,
+                    parent_id: None,
+                    quoted_id: None,
+                    reply_count: 0,
+                    mentions: Some(Vec::new()),
+                    mentioned_company_ids: Some(Vec::new()),
+                    attachment_ids: Some(Vec::new()),
+                    message_kind,
+                })
+                .id
+        }
+        ChatKind::Channel => {
+            ctx.db
+                .channel_chat()
+                .insert(ChannelChat {
+                    id: 0,
+                    channel_id: conversation_id,
+                    sender: ctx.sender(),
+                    text,
+                    created_at: ctx.timestamp,
+                    hidden: false,
+                    edited_at: None,
+                    deleted: false,
+                    parent_id: None,
+                    quoted_id: None,
+                    reply_count: 0,
+                    mentions: Vec::new(),
+                    mentioned_company_ids: Vec::new(),
+                    attachment_ids: Vec::new(),
+                    message_kind,
+                })
+                .id
+        }
+    };
+    count_new_message(ctx, kind, conversation_id, message_id);
+}
+
+/// Unpins a message that is being deleted.
+fn delete_message_pins(ctx: &ReducerContext, kind: ChatKind, message_id: u64) {
+    let pin_ids: Vec<u64> = ctx
+        .db
+        .pinned_message()
+        .pin_by_message()
+        .filter(&message_id)
+        .filter(|p| p.kind == kind)
+        .map(|p| p.id)
+        .collect();
+    for id in pin_ids {
+        ctx.db.pinned_message().id().delete(id);
+    }
+}
+
+/// Pins a message to the top of its conversation. Anyone who can post in the
+/// conversation can pin, up to `MAX_PINNED_MESSAGES` per conversation.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role, the colleague is not
-/// found, or the colleague is already a member of this company.
+/// Returns an error if the message is not found, hidden or deleted, its
+/// conversation is archived or the caller cannot post in it, the message is
+/// already pinned, or the conversation has reached the pin limit.
 #[spacetimedb::reducer]
-pub fn add_colleague_by_identity(
+pub fn pin_chat_message(
     ctx: &ReducerContext,
-    colleague_identity: Identity,
+    kind: ChatKind,
+    message_id: u64,
 ) -> Result<(), String> {
-    let (_caller, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
-
-    ctx.db
-        .user_account()
-        .identity()
-        .find(colleague_identity)
-        .ok_or("Colleague account not found")?;
+    let message = find_chat_message(ctx, kind, message_id)?;
+    if message.hidden || message.deleted {
+        return Err("Message not found".to_string());
+    }
+    let conversation_id = message.conversation_id;
+    require_not_archived(ctx, kind, conversation_id)?;
+    require_chat_poster(ctx, kind, conversation_id)?;
 
-    if find_membership(ctx, colleague_identity, company_id).is_some() {
-        return Err("User is already a member of this company".to_string());
+    let pinned: Vec<u64> = ctx
+        .db
+        .pinned_message()
+        .pin_by_conversation()
+        .filter(&conversation_id)
+        .filter(|p| p.kind == kind)
+        .map(|p| p.message_id)
+        .collect();
+    if pinned.contains(&message_id) {
+        return Err("Message is already pinned".to_string());
+    }
+    if pinned.len() >= MAX_PINNED_MESSAGES {
+        return Err(format!(
+            "A conversation can have at most {} pinned messages",
+            MAX_PINNED_MESSAGES
+        ));
     }
 
-    ctx.db.company_member().insert(CompanyMember {
+    ctx.db.pinned_message().insert(PinnedMessage {
         id: 0,
-        identity: colleague_identity,
-        company_id,
-        role: UserRole::Member,
-        joined_at: ctx.timestamp,
+        kind,
+        conversation_id,
+        message_id,
+        pinned_by: ctx.sender(),
+        pinned_at: ctx.timestamp,
     });
 
     Ok(())
 }
 
-/// Removes a user from the company. Admins can only remove Members, Field,
-/// and Pending; Owners can remove anyone except themselves.
+/// Unpins a message. Anyone who can post in the conversation can unpin.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller tries to remove themselves, is below Admin
-/// role, the colleague is not a member of the same company, or has an
-/// equal or higher role than the caller.
+/// Returns an error if the message is not found or not pinned, or its
+/// conversation is archived or the caller cannot post in it.
 #[spacetimedb::reducer]
-pub fn remove_colleague(
+pub fn unpin_chat_message(
     ctx: &ReducerContext,
-    colleague_identity: Identity,
+    kind: ChatKind,
+    message_id: u64,
 ) -> Result<(), String> {
-    if ctx.sender() == colleague_identity {
-        return Err("Cannot remove yourself".to_string());
-    }
+    let message = find_chat_message(ctx, kind, message_id)?;
+    require_not_archived(ctx, kind, message.conversation_id)?;
+    require_chat_poster(ctx, kind, message.conversation_id)?;
 
-    let (_caller, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+    let pin = ctx
+        .db
+        .pinned_message()
+        .pin_by_message()
+        .filter(&message_id)
+        .find(|p| p.kind == kind)
+        .ok_or("Message is not pinned")?;
+    ctx.db.pinned_message().id().delete(pin.id);
 
-    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
-        .ok_or("Not permitted")?;
+    Ok(())
+}
 
-    let colleague_membership = find_membership(ctx, colleague_identity, company_id)
-        .ok_or("Colleague is not in your company")?;
+// ---------------------------------------------------------------------------
+// Read Cursors
+// ---------------------------------------------------------------------------
 
-    // Hierarchy: cannot remove someone with equal or higher role
-    if role_level(colleague_membership.role) >= role_level(caller_membership.role) {
-        return Err("You can only remove members with a lower role than yours".to_string());
+/// Users who can read a conversation: Chat holders of either company of a
+/// connection, project participants holding Chat in the company they were
+/// assigned through, or channel members holding Chat in the channel's company
+/// (matching the chat views). For an archived conversation,
+/// its remaining readers (see `archive_audience`).
+fn conversation_audience(
+    db: &LocalReadOnly,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Vec<Identity> {
+    if let Some(archive) = find_chat_archive(db, kind, conversation_id) {
+        return archive_audience(db, archive.id);
     }
+    let mut audience: Vec<Identity> = match kind {
+        ChatKind::Connection => db
+            .company_connection()
+            .id()
+            .find(conversation_id)
+            .map(|c| {
+                [c.company_a, c.company_b]
+                    .iter()
+                    .flat_map(|cid| db.company_member().member_by_company().filter(cid))
+                    .filter(|m| has_permission(db, m, Permission::Chat))
+                    .map(|m| m.identity)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        ChatKind::Project => db
+            .project_participant()
+            .pp_by_project()
+            .filter(&conversation_id)
+            .filter(|p| {
+                db.company_member()
+                    .member_by_identity()
+                    .filter(&p.identity)
+                    .any(|m| {
+                        m.company_id == p.company_id && has_permission(db, &m, Permission::Chat)
+                    })
+            })
+            .map(|p| p.identity)
+            .collect(),
+        ChatKind::Channel => db
+            .company_channel()
+            .id()
+            .find(conversation_id)
+            .map(|channel| {
+                db.channel_member()
+                    .channel_member_by_channel()
+                    .filter(&conversation_id)
+                    .filter(|cm| {
+                        db.company_member()
+                            .member_by_identity()
+                            .filter(&cm.identity)
+                            .any(|m| {
+                                m.company_id == channel.company_id
+                                    && has_permission(db, &m, Permission::Chat)
+                            })
+                    })
+                    .map(|cm| cm.identity)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    audience.sort_unstable();
+    audience.dedup();
+    audience
+}
 
-    // Delete the membership
-    ctx.db.company_member().id().delete(colleague_membership.id);
-
-    // If their active company was this one, reassign
-    reassign_active_company(ctx, colleague_identity, company_id);
-
-    log::info!(
-        "AUDIT: User {} of Company {} removed colleague {}",
-        id_short(ctx.sender()),
-        company_id,
-        id_short(colleague_identity)
-    );
+/// Finds a live conversation's message count (see `ConversationStats`).
+fn find_conversation_stats(
+    db: &LocalReadOnly,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Option<ConversationStats> {
+    db.conversation_stats()
+        .stats_by_conversation()
+        .filter(&conversation_id)
+        .find(|s| s.kind == kind)
+}
 
-    // Notify the removed colleague
-    let company_name = ctx.db.company().id().find(company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_default();
-    notify(
-        ctx,
-        colleague_identity,
-        company_id,
-        NotificationType::MemberRemoved,
-        "Removed from company".to_string(),
-        format!("You were removed from {}", company_name),
-    );
+/// Senders of a conversation's visible messages after `after_id`. Only those
+/// messages are read, through the (conversation, id) indexes.
+fn visible_senders_after(
+    db: &LocalReadOnly,
+    kind: ChatKind,
+    conversation_id: u64,
+    after_id: u64,
+) -> Vec<Identity> {
+    let after = (conversation_id, after_id.saturating_add(1)..);
+    match kind {
+        ChatKind::Connection => db
+            .connection_chat()
+            .chat_by_connection_and_id()
+            .filter(after)
+            .filter(|m| !m.hidden && !m.deleted)
+            .map(|m| m.sender)
+            .collect(),
+        ChatKind::Project => db
+            .project_chat()
+            .pchat_by_project_and_id()
+            .filter(after)
+            .filter(|m| !m.hidden && !m.deleted)
+            .map(|m| m.sender)
+            .collect(),
+        ChatKind::Channel => db
+            .channel_chat()
+            .channel_chat_by_channel_and_id()
+            .filter(after)
+            .filter(|m| !m.hidden && !m.deleted)
+            .map(|m| m.sender)
+            .collect(),
+    }
+}
 
-    Ok(())
+/// The latest visible user message of a conversation before `before_id`,
+/// through the (conversation, id) indexes.
+fn latest_visible_message_before(
+    db: &LocalReadOnly,
+    kind: ChatKind,
+    conversation_id: u64,
+    before_id: u64,
+) -> Option<u64> {
+    let before = (conversation_id, ..before_id);
+    match kind {
+        ChatKind::Connection => db
+            .connection_chat()
+            .chat_by_connection_and_id()
+            .filter(before)
+            .filter(|m| !m.hidden && !m.deleted && m.message_kind == MessageKind::User)
+            .map(|m| m.id)
+            .max(),
+        ChatKind::Project => db
+            .project_chat()
+            .pchat_by_project_and_id()
+            .filter(before)
+            .filter(|m| !m.hidden && !m.deleted && m.message_kind == MessageKind::User)
+            .map(|m| m.id)
+            .max(),
+        ChatKind::Channel => db
+            .channel_chat()
+            .channel_chat_by_channel_and_id()
+            .filter(before)
+            .filter(|m| !m.hidden && !m.deleted && m.message_kind == MessageKind::User)
+            .map(|m| m.id)
+            .max(),
+    }
 }
 
-/// Voluntarily leave the active company. Owners must transfer ownership
-/// first — they cannot abandon a company.
-///
-/// # Errors
-///
-/// Returns an error if the caller has no account, has no active company,
-/// has no membership in that company, or is the Owner.
-#[spacetimedb::reducer]
-pub fn leave_company(ctx: &ReducerContext) -> Result<(), String> {
-    let account = ctx
-        .db
-        .user_account()
-        .identity()
-        .find(ctx.sender())
-        .ok_or("Account not found")?;
+/// Number of visible messages `sender` posted in a conversation, through the
+/// sender indexes.
+fn count_visible_from(
+    db: &LocalReadOnly,
+    kind: ChatKind,
+    conversation_id: u64,
+    sender: Identity,
+) -> u64 {
+    let count = match kind {
+        ChatKind::Connection => db
+            .connection_chat()
+            .chat_by_sender()
+            .filter(&sender)
+            .filter(|m| m.connection_id == conversation_id && !m.hidden && !m.deleted)
+            .count(),
+        ChatKind::Project => db
+            .project_chat()
+            .pchat_by_sender()
+            .filter(&sender)
+            .filter(|m| m.project_id == conversation_id && !m.hidden && !m.deleted)
+            .count(),
+        ChatKind::Channel => db
+            .channel_chat()
+            .channel_chat_by_sender()
+            .filter(&sender)
+            .filter(|m| m.channel_id == conversation_id && !m.hidden && !m.deleted)
+            .count(),
+    };
+    count as u64
+}
 
-    let company_id = account
-        .active_company_id
-        .ok_or("You do not belong to a company")?;
+/// `read_count` and `skipped_count` of `identity`'s cursor at `message_id`.
+/// A cursor that has read nothing yet skips everything posted by the user
+/// and the users they blocked, counted through the sender indexes; any other
+/// cursor only reads the messages after it.
+fn cursor_counts(
+    db: &LocalReadOnly,
+    kind: ChatKind,
+    conversation_id: u64,
+    identity: Identity,
+    message_id: u64,
+) -> (u64, u64) {
+    let message_count =
+        find_conversation_stats(db, kind, conversation_id).map_or(0, |s| s.message_count);
+    let blocked = blocked_by(db, identity);
+    if message_id == 0 {
+        let skipped = std::iter::once(identity)
+            .chain(blocked)
+            .map(|sender| count_visible_from(db, kind, conversation_id, sender))
+            .sum();
+        return (0, skipped);
+    }
+    let after = visible_senders_after(db, kind, conversation_id, message_id);
+    let skipped = after
+        .iter()
+        .filter(|s| **s == identity || blocked.contains(s))
+        .count() as u64;
+    (message_count.saturating_sub(after.len() as u64), skipped)
+}
 
-    let membership = find_membership(ctx, ctx.sender(), company_id)
-        .ok_or("You are not a member of this company")?;
+/// Finds a user's cursor in a conversation, if any.
+fn find_read_cursor(
+    ctx: &ReducerContext,
+    identity: Identity,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Option<ReadCursor> {
+    ctx.db
+        .read_cursor()
+        .cursor_by_identity()
+        .filter(&identity)
+        .find(|c| c.kind == kind && c.conversation_id == conversation_id)
+}
 
-    if membership.role == UserRole::Owner {
-        return Err("Transfer ownership before leaving the company".to_string());
+/// Creates a user's cursor in a conversation at `message_id`, or moves their
+/// cursor there, and recounts what is left unread.
+fn place_read_cursor(
+    ctx: &ReducerContext,
+    identity: Identity,
+    kind: ChatKind,
+    conversation_id: u64,
+    message_id: u64,
+    last_read_at: Option<Timestamp>,
+) {
+    let (read_count, skipped_count) = cursor_counts(
+        &ctx.as_read_only().db,
+        kind,
+        conversation_id,
+        identity,
+        message_id,
+    );
+    if let Some(cursor) = find_read_cursor(ctx, identity, kind, conversation_id) {
+        ctx.db.read_cursor().id().update(ReadCursor {
+            last_read_message_id: message_id,
+            last_read_at: last_read_at.or(cursor.last_read_at),
+            read_count,
+            skipped_count,
+            ..cursor
+        });
+    } else {
+        ctx.db.read_cursor().insert(ReadCursor {
+            id: 0,
+            identity,
+            kind,
+            conversation_id,
+            last_read_message_id: message_id,
+            last_read_at,
+            read_count,
+            skipped_count,
+        });
     }
+}
 
-    // Delete membership
-    ctx.db.company_member().id().delete(membership.id);
+/// Moves a user's cursor to `message_id` (creating it if needed) and recounts
+/// what is left unread.
+fn move_read_cursor(
+    ctx: &ReducerContext,
+    identity: Identity,
+    kind: ChatKind,
+    conversation_id: u64,
+    message_id: u64,
+) {
+    place_read_cursor(
+        ctx,
+        identity,
+        kind,
+        conversation_id,
+        message_id,
+        Some(ctx.timestamp),
+    );
+}
 
-    // Switch active company to next available, or None
-    reassign_active_company(ctx, ctx.sender(), company_id);
+/// Counts a new message in its conversation. The sender's cursor moves past
+/// it and their notifications about the conversation are marked read; users
+/// who blocked the sender skip it. Other readers' cursors are left alone:
+/// the message is unread for them through the conversation's count.
+fn count_new_message(ctx: &ReducerContext, kind: ChatKind, conversation_id: u64, message_id: u64) {
+    match find_conversation_stats(&ctx.as_read_only().db, kind, conversation_id) {
+        Some(stats) => {
+            ctx.db.conversation_stats().id().update(ConversationStats {
+                message_count: stats.message_count + 1,
+                last_message_id: message_id,
+                ..stats
+            });
+        }
+        None => {
+            ctx.db.conversation_stats().insert(ConversationStats {
+                id: 0,
+                kind,
+                conversation_id,
+                message_count: 1,
+                last_message_id: message_id,
+            });
+        }
+    }
 
-    Ok(())
-}
+    move_read_cursor(ctx, ctx.sender(), kind, conversation_id, message_id);
+    read_chat_notifications(ctx, kind, conversation_id, message_id);
 
-/// Switch the user's active company context. The user must have a membership
-/// in the target company.
-///
-/// # Errors
-///
-/// Returns an error if the caller has no account or is not a member of the
-/// target company.
-#[spacetimedb::reducer]
-pub fn switch_active_company(ctx: &ReducerContext, company_id: u64) -> Result<(), String> {
-    let account = ctx
+    let blockers: Vec<Identity> = ctx
         .db
-        .user_account()
-        .identity()
-        .find(ctx.sender())
-        .ok_or("Account not found")?;
+        .user_block()
+        .ublock_by_blocked()
+        .filter(&ctx.sender())
+        .map(|b| b.blocker)
+        .collect();
+    for blocker in blockers {
+        if let Some(cursor) = find_read_cursor(ctx, blocker, kind, conversation_id) {
+            ctx.db.read_cursor().id().update(ReadCursor {
+                skipped_count: cursor.skipped_count + 1,
+                ..cursor
+            });
+        } else {
+            place_read_cursor(ctx, blocker, kind, conversation_id, 0, None);
+        }
+    }
+}
 
-    ctx.db
-        .company()
-        .id()
-        .find(company_id)
-        .ok_or("Company not found")?;
+/// Updates the counts after a message was hidden, deleted or purged
+/// (`visible` false) or unhidden (`visible` true): the conversation's count,
+/// and the read or skipped count of each cursor the message was read or
+/// skipped by.
+fn count_message_visibility(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    message_id: u64,
+    sender: Identity,
+    visible: bool,
+) {
+    let db = &ctx.as_read_only().db;
+    let Some(stats) = find_conversation_stats(db, kind, conversation_id) else {
+        return;
+    };
+    let change = |count: u64| {
+        if visible {
+            count + 1
+        } else {
+            count.saturating_sub(1)
+        }
+    };
+    ctx.db.conversation_stats().id().update(ConversationStats {
+        message_count: change(stats.message_count),
+        ..stats
+    });
 
-    find_membership(ctx, ctx.sender(), company_id)
-        .ok_or("You are not a member of this company")?;
+    let cursors: Vec<ReadCursor> = ctx
+        .db
+        .read_cursor()
+        .cursor_by_conversation()
+        .filter(&conversation_id)
+        .filter(|c| c.kind == kind)
+        .collect();
+    for cursor in cursors {
+        if cursor.last_read_message_id >= message_id {
+            let read_count = change(cursor.read_count);
+            ctx.db.read_cursor().id().update(ReadCursor {
+                read_count,
+                ..cursor
+            });
+        } else if cursor.identity == sender || has_blocked(db, cursor.identity, sender) {
+            let skipped_count = change(cursor.skipped_count);
+            ctx.db.read_cursor().id().update(ReadCursor {
+                skipped_count,
+                ..cursor
+            });
+        }
+    }
+}
 
-    ctx.db.user_account().identity().update(UserAccount {
-        active_company_id: Some(company_id),
-        ..account
-    });
+/// Recounts a user's cursors after they blocked or unblocked `other`: every
+/// cursor where `other` has messages, creating missing ones so those messages
+/// are skipped.
+fn recount_user(ctx: &ReducerContext, identity: Identity, other: Identity) {
+    let db = &ctx.as_read_only().db;
+    let mut conversations: Vec<(ChatKind, u64)> = db
+        .connection_chat()
+        .chat_by_sender()
+        .filter(&other)
+        .map(|m| (ChatKind::Connection, m.connection_id))
+        .collect();
+    conversations.extend(
+        db.project_chat()
+            .pchat_by_sender()
+            .filter(&other)
+            .map(|m| (ChatKind::Project, m.project_id)),
+    );
+    conversations.extend(
+        db.channel_chat()
+            .channel_chat_by_sender()
+            .filter(&other)
+            .map(|m| (ChatKind::Channel, m.channel_id)),
+    );
+    conversations.sort_unstable_by_key(|(kind, id)| (*kind as u8, *id));
+    conversations.dedup();
 
-    Ok(())
+    for (kind, conversation_id) in conversations {
+        if find_conversation_stats(db, kind, conversation_id).is_none() {
+            continue;
+        }
+        let (message_id, last_read_at) = find_read_cursor(ctx, identity, kind, conversation_id)
+            .map_or((0, None), |c| (c.last_read_message_id, c.last_read_at));
+        place_read_cursor(
+            ctx,
+            identity,
+            kind,
+            conversation_id,
+            message_id,
+            last_read_at,
+        );
+    }
 }
 
-// ---------------------------------------------------------------------------
-// Phase 3 — Public Presence
-// ---------------------------------------------------------------------------
+/// Deletes all cursors on a conversation that is going away or being
+/// archived, along with its message count.
+fn delete_read_cursors(ctx: &ReducerContext, kind: ChatKind, conversation_id: u64) {
+    let cursor_ids: Vec<u64> = ctx
+        .db
+        .read_cursor()
+        .cursor_by_conversation()
+        .filter(&conversation_id)
+        .filter(|c| c.kind == kind)
+        .map(|c| c.id)
+        .collect();
+    for id in cursor_ids {
+        ctx.db.read_cursor().id().delete(id);
+    }
+    if let Some(stats) = find_conversation_stats(&ctx.as_read_only().db, kind, conversation_id) {
+        ctx.db.conversation_stats().id().delete(stats.id);
+    }
+}
 
-/// Updates the company's public profile. Requires at least Admin role.
+/// Marks a conversation as read up to `up_to_message_id`, or up to its latest
+/// message if `None`, along with the caller's chat notifications about it.
+/// Cursors never move backwards.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role, required fields are
-/// empty, or the new slug is already taken by another company.
+/// Returns an error if the caller cannot read the conversation.
 #[spacetimedb::reducer]
-#[allow(clippy::needless_pass_by_value)]
-pub fn update_company_profile(
+pub fn mark_conversation_read(
     ctx: &ReducerContext,
-    name: String,
-    slug: String,
-    location: String,
-    bio: String,
-    is_public: bool,
-    kvk_number: String,
+    kind: ChatKind,
+    conversation_id: u64,
+    up_to_message_id: Option<u64>,
 ) -> Result<(), String> {
-    let name = name.trim().to_string();
-    let slug = normalize_slug(&slug);
-    let location = location.trim().to_string();
-    let bio = bio.trim().to_string();
-    let kvk_number = kvk_number.trim().to_string();
-
-    validate_not_empty(&name, "Company name")?;
-    validate_not_empty(&slug, "Slug")?;
-    validate_not_empty(&location, "Location")?;
-    validate_length(&name, "Company name", MAX_COMPANY_NAME)?;
-    validate_length(&slug, "Slug", MAX_SLUG)?;
-    validate_length(&location, "Location", MAX_LOCATION)?;
-    validate_length(&bio, "Bio", MAX_BIO)?;
-    validate_length(&kvk_number, "KVK number", MAX_KVK_NUMBER)?;
-
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+    let db = &ctx.as_read_only().db;
+    if !conversation_audience(db, kind, conversation_id).contains(&ctx.sender()) {
+        return Err("Conversation not found".to_string());
+    }
 
-    let company = ctx
-        .db
-        .company()
-        .id()
-        .find(company_id)
-        .ok_or("Company not found")?;
+    let latest =
+        find_conversation_stats(db, kind, conversation_id).map_or(0, |s| s.last_message_id);
+    let up_to = up_to_message_id.map_or(latest, |id| id.min(latest));
 
-    // If slug changed, check uniqueness
-    if slug != company.slug && ctx.db.company().slug().find(&slug).is_some() {
-        return Err("Slug is already taken".to_string());
+    let already_past = find_read_cursor(ctx, ctx.sender(), kind, conversation_id)
+        .is_some_and(|c| c.last_read_message_id >= up_to && c.last_read_at.is_some());
+    if !already_past {
+        move_read_cursor(ctx, ctx.sender(), kind, conversation_id, up_to);
     }
-
-    ctx.db.company().id().update(Company {
-        name,
-        slug,
-        location,
-        bio,
-        is_public,
-        kvk_number,
-        ..company
-    });
+    read_chat_notifications(ctx, kind, conversation_id, up_to);
 
     Ok(())
 }
 
-/// Updates the company's capabilities. Requires at least Admin role.
+/// Turns sharing of the caller's read positions with other participants on or
+/// off. Only users who share their own can see others'.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role or capabilities are not
-/// found for the company.
+/// Returns an error if the caller has no account.
 #[spacetimedb::reducer]
-#[allow(clippy::fn_params_excessive_bools)] // SpacetimeDB reducer: each capability is an independent flag
-pub fn update_capabilities(
-    ctx: &ReducerContext,
-    can_install: bool,
-    has_cnc: bool,
-    has_large_format: bool,
-    has_bucket_truck: bool,
-) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
-
-    let cap = ctx
-        .db
-        .capability()
-        .company_id()
-        .find(company_id)
-        .ok_or("Capabilities not found")?;
+pub fn set_read_receipts(ctx: &ReducerContext, enabled: bool) -> Result<(), String> {
+    ctx.db
+        .user_account()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Account not found")?;
 
-    ctx.db.capability().company_id().update(Capability {
-        can_install,
-        has_cnc,
-        has_large_format,
-        has_bucket_truck,
-        ..cap
-    });
+    let sharing = ctx.db.read_receipt_sharing().identity().find(ctx.sender());
+    match (sharing, enabled) {
+        (None, true) => {
+            ctx.db.read_receipt_sharing().insert(ReadReceiptSharing {
+                identity: ctx.sender(),
+                enabled_at: ctx.timestamp,
+            });
+        }
+        (Some(_), false) => {
+            ctx.db
+                .read_receipt_sharing()
+                .identity()
+                .delete(ctx.sender());
+        }
+        (Some(_), true) | (None, false) => {}
+    }
 
     Ok(())
 }
 
 // ---------------------------------------------------------------------------
-// Phase 4 — Role Management
+// Chat Mentions
 // ---------------------------------------------------------------------------
 
-/// Admin or Owner changes a team member's role in their company.
-/// Cannot change your own role or promote someone to Owner (use
-/// `transfer_ownership` for that). Admins can only assign roles below
-/// their own level; Owners can assign any non-Owner role.
-///
-/// # Errors
-///
-/// Returns an error if the caller targets themselves, tries to assign Owner,
-/// is below Admin, the target has no membership in the same company,
-/// the target has an equal or higher role, or the caller tries to assign
-/// a role at or above their own level.
-#[spacetimedb::reducer]
-pub fn update_user_role(
-    ctx: &ReducerContext,
-    target_identity: Identity,
-    new_role: UserRole,
-) -> Result<(), String> {
-    if ctx.sender() == target_identity {
-        return Err("Cannot change your own role".to_string());
-    }
+/// Handle used to @mention a user: their nickname as a slug, so `John Doe` is
+/// mentioned as `@john-doe`.
+fn mention_handle(nickname: &str) -> String {
+    normalize_slug(nickname)
+}
 
-    if new_role == UserRole::Owner {
-        return Err("Use transfer_ownership to assign the Owner role".to_string());
+/// Records the mention handle of `identity`'s nickname in
+/// `user_mention_handle`.
+fn set_mention_handle(ctx: &ReducerContext, identity: Identity, nickname: &str) {
+    let row = UserMentionHandle {
+        identity,
+        handle: mention_handle(nickname),
+    };
+    if ctx
+        .db
+        .user_mention_handle()
+        .identity()
+        .find(identity)
+        .is_some()
+    {
+        ctx.db.user_mention_handle().identity().update(row);
+    } else {
+        ctx.db.user_mention_handle().insert(row);
     }
+}
 
-    let (_caller, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
-
-    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
-        .ok_or("Not permitted")?;
-
-    let target_membership = find_membership(ctx, target_identity, company_id)
-        .ok_or("Target is not in your company")?;
+/// Whether an account other than `except` already uses the mention handle of
+/// `nickname`. Nicknames are unique by handle so mentions are unambiguous.
+fn nickname_taken(ctx: &ReducerContext, nickname: &str, except: Identity) -> bool {
+    ctx.db
+        .user_mention_handle()
+        .handle_by_handle()
+        .filter(&mention_handle(nickname))
+        .any(|h| h.identity != except)
+}
 
-    // Cannot change role of someone at or above your level
-    if role_level(target_membership.role) >= role_level(caller_membership.role) {
-        return Err("Cannot change the role of someone at or above your level".to_string());
+/// `@handle` tokens in a message, lowercased and deduplicated. A mention
+/// starts at an `@` that does not follow a letter or digit (so e-mail
+/// addresses are skipped) and runs over letters, digits, `-`, `_` and `.`.
+fn parse_mentions(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut handles: Vec<String> = Vec::new();
+    for (i, c) in chars.iter().enumerate() {
+        if *c != '@' || (i > 0 && chars[i - 1].is_alphanumeric()) {
+            continue;
+        }
+        let handle: String = chars[i + 1..]
+            .iter()
+            .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            .collect();
+        let handle = handle.trim_end_matches(['.', '-', '_']).to_lowercase();
+        if !handle.is_empty() && !handles.contains(&handle) {
+            handles.push(handle);
+        }
     }
+    handles
+}
 
-    // Cannot promote someone to or above your own level (unless Owner)
-    if caller_membership.role != UserRole::Owner
-        && role_level(new_role) >= role_level(caller_membership.role)
-    {
-        return Err("Cannot assign a role at or above your own level".to_string());
+/// Users and companies mentioned in a message.
+#[derive(Default)]
+struct Mentions {
+    users: Vec<Identity>,
+    company_ids: Vec<u64>,
+}
+
+/// Resolves the @mentions in `text` against the conversation: `@nickname`
+/// against its readers (see `mention_handle`) and `@company-slug` against the
+/// companies taking part. Mentioning a company mentions its readers of the
+/// conversation. Accounts from before nicknames were unique may share a
+/// handle; every matching reader is mentioned then. The sender is never
+/// mentioned.
+fn resolve_mentions(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    text: &str,
+) -> Mentions {
+    let handles = parse_mentions(text);
+    if handles.is_empty() {
+        return Mentions::default();
     }
 
-    ctx.db.company_member().id().update(CompanyMember {
-        role: new_role,
-        ..target_membership
-    });
+    let db = &ctx.as_read_only().db;
+    let audience = conversation_audience(db, kind, conversation_id);
+    let participating: Vec<u64> = match kind {
+        ChatKind::Connection => ctx
+            .db
+            .company_connection()
+            .id()
+            .find(conversation_id)
+            .map(|c| vec![c.company_a, c.company_b])
+            .unwrap_or_default(),
+        ChatKind::Project => ctx
+            .db
+            .project_member()
+            .pm_by_project()
+            .filter(&conversation_id)
+            .filter(|m| m.status == ProjectMemberStatus::Accepted)
+            .map(|m| m.company_id)
+            .collect(),
+        ChatKind::Channel => ctx
+            .db
+            .company_channel()
+            .id()
+            .find(conversation_id)
+            .map(|c| vec![c.company_id])
+            .unwrap_or_default(),
+    };
 
-    log::info!(
-        "AUDIT: User {} of Company {} changed role of {} to {:?}",
-        id_short(ctx.sender()),
-        company_id,
-        id_short(target_identity),
-        new_role
-    );
+    let company_ids: Vec<u64> = participating
+        .into_iter()
+        .filter(|cid| {
+            ctx.db
+                .company()
+                .id()
+                .find(*cid)
+                .is_some_and(|c| handles.contains(&c.slug))
+        })
+        .collect();
 
-    // Notify the target that their role was changed
-    let company_name = ctx.db.company().id().find(company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_default();
-    notify(
-        ctx,
-        target_identity,
-        company_id,
-        NotificationType::RoleChanged,
-        "Role updated".to_string(),
-        format!("Your role in {} was changed to {:?}", company_name, new_role),
-    );
+    let mut users: Vec<Identity> = handles
+        .iter()
+        .flat_map(|handle| {
+            ctx.db
+                .user_mention_handle()
+                .handle_by_handle()
+                .filter(handle)
+        })
+        .map(|h| h.identity)
+        .filter(|id| audience.contains(id))
+        .collect();
+    for cid in &company_ids {
+        users.extend(
+            audience
+                .iter()
+                .copied()
+                .filter(|id| find_membership(ctx, *id, *cid).is_some()),
+        );
+    }
+    users.retain(|id| *id != ctx.sender());
+    users.sort_unstable();
+    users.dedup();
 
-    Ok(())
+    Mentions { users, company_ids }
 }
 
-/// Transfers company ownership to another team member. The caller (current
-/// Owner) is demoted to Admin and the target becomes the new Owner.
-/// Also updates `Company.owner_identity`.
-///
-/// # Errors
-///
-/// Returns an error if the caller targets themselves, is not the Owner,
-/// the target is not found, or the target is not in the same company.
-#[spacetimedb::reducer]
-pub fn transfer_ownership(
+/// Sends mention notifications for a message. A mentioned user who already
+/// got a notification about it (one of `notified`) has it upgraded to the
+/// mention type, if they want mentions, rather than receiving a second one. Returns the IDs of newly
+/// inserted notifications.
+fn notify_mentions(
     ctx: &ReducerContext,
-    new_owner_identity: Identity,
-) -> Result<(), String> {
-    if ctx.sender() == new_owner_identity {
-        return Err("You are already the owner".to_string());
+    kind: ChatKind,
+    conversation_id: u64,
+    users: &[Identity],
+    notified: &[u64],
+    title: String,
+    body: String,
+) -> Vec<u64> {
+    let mention_type = chat_notification_types(kind).mention;
+    let mut new_ids: Vec<u64> = Vec::new();
+    for user in users {
+        let existing = notified
+            .iter()
+            .filter_map(|id| ctx.db.notification().id().find(*id))
+            .find(|n| n.recipient_identity == *user);
+        // A notification that also stands for earlier messages stays a message
+        // notification; the mention gets its own
+        let existing = existing.filter(|n| n.message_count <= 1);
+        if let Some(notification) = existing {
+            if !wants_notification(
+                &ctx.as_read_only().db,
+                *user,
+                notification.company_id,
+                mention_type,
+            ) {
+                continue;
+            }
+            ctx.db.notification().id().update(Notification {
+                notification_type: mention_type,
+                title: title.clone(),
+                body: body.clone(),
+                ..notification
+            });
+        } else if let Some(company_id) = chat_company_for(ctx, kind, conversation_id, *user) {
+            new_ids.extend(notify_in_conversation(
+                ctx,
+                *user,
+                company_id,
+                mention_type,
+                kind,
+                conversation_id,
+                title.clone(),
+                body.clone(),
+            ));
+        }
     }
+    new_ids
+}
 
-    let (_caller, company_id) = require_role_at_least(ctx, UserRole::Owner)?;
+// ---------------------------------------------------------------------------
+// Attachments
+// ---------------------------------------------------------------------------
 
-    let caller_membership = find_membership(ctx, ctx.sender(), company_id)
-        .ok_or("Not permitted")?;
+/// Checks that the caller may post in a conversation, with the same rules as
+/// `send_connection_chat`, `send_project_chat` and `send_channel_chat`, and
+/// returns the company they post as.
+fn require_chat_poster(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Result<u64, String> {
+    let (_account, company_id) = require_permission(ctx, Permission::Chat)?;
+    match kind {
+        ChatKind::Connection => {
+            let conn = ctx
+                .db
+                .company_connection()
+                .id()
+                .find(conversation_id)
+                .ok_or("Connection not found")?;
+            if conn.status == ConnectionStatus::Blocked {
+                return Err("Cannot chat on a blocked connection".to_string());
+            }
+            if conn.company_a != company_id && conn.company_b != company_id {
+                return Err("Your company is not part of this connection".to_string());
+            }
+        }
+        ChatKind::Project => {
+            ctx.db
+                .project()
+                .id()
+                .find(conversation_id)
+                .ok_or("Project not found")?;
+            find_project_membership(
+                ctx,
+                conversation_id,
+                company_id,
+                ProjectMemberStatus::Accepted,
+            )
+            .ok_or("Your company is not a member of this project")?;
+            let participant =
+                find_project_participant(&ctx.as_read_only().db, conversation_id, ctx.sender())
+                    .filter(|p| p.company_id == company_id)
+                    .ok_or("You are not assigned to this project")?;
+            if participant.role == ProjectRole::Observer {
+                return Err("Observers cannot post in this project".to_string());
+            }
+        }
+        ChatKind::Channel => {
+            let channel = ctx
+                .db
+                .company_channel()
+                .id()
+                .find(conversation_id)
+                .filter(|c| c.company_id == company_id)
+                .ok_or("Channel not found")?;
+            find_channel_member(&ctx.as_read_only().db, conversation_id, ctx.sender())
+                .ok_or("You are not a member of this channel")?;
+            let member_count = ctx
+                .db
+                .channel_member()
+                .channel_member_by_channel()
+                .filter(&conversation_id)
+                .count();
+            if channel.is_direct && member_count < 2 {
+                return Err("The other person has left this conversation".to_string());
+            }
+        }
+    }
+    Ok(company_id)
+}
 
-    let target_membership = find_membership(ctx, new_owner_identity, company_id)
-        .ok_or("Target is not in your company")?;
+/// Validates a MIME type such as `image/png` or `application/vnd.ms-excel`.
+fn validate_mime_type(mime_type: &str) -> Result<(), String> {
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    };
+    match mime_type.split_once('/') {
+        Some((kind, subtype)) if valid_part(kind) && valid_part(subtype) => Ok(()),
+        _ => Err("Invalid MIME type".to_string()),
+    }
+}
 
-    // Update company owner reference — double-check caller is the recorded owner
-    let company = ctx
-        .db
-        .company()
-        .id()
+/// Formats a byte count for error messages, e.g. `25.0 MB`.
+fn format_bytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// The company's storage row, or a new one with the default quota.
+fn company_storage_for(ctx: &ReducerContext, company_id: u64) -> CompanyStorage {
+    ctx.db
+        .company_storage()
+        .company_id()
         .find(company_id)
-        .ok_or("Company not found")?;
+        .unwrap_or(CompanyStorage {
+            company_id,
+            used_bytes: 0,
+            quota_bytes: DEFAULT_STORAGE_QUOTA_BYTES,
+        })
+}
 
-    if company.owner_identity != ctx.sender() {
-        return Err("Only the owner can do this".to_string());
+fn save_company_storage(ctx: &ReducerContext, storage: CompanyStorage) {
+    if ctx
+        .db
+        .company_storage()
+        .company_id()
+        .find(storage.company_id)
+        .is_some()
+    {
+        ctx.db.company_storage().company_id().update(storage);
+    } else {
+        ctx.db.company_storage().insert(storage);
     }
+}
 
-    ctx.db.company().id().update(Company {
-        owner_identity: new_owner_identity,
-        ..company
-    });
-
-    // Demote current owner to Admin (on CompanyMember)
-    ctx.db.company_member().id().update(CompanyMember {
-        role: UserRole::Admin,
-        ..caller_membership
-    });
+/// Charges an upload of `bytes` to the company's quota.
+fn reserve_storage(ctx: &ReducerContext, company_id: u64, bytes: u64) -> Result<(), String> {
+    let storage = company_storage_for(ctx, company_id);
+    if storage.used_bytes + bytes > storage.quota_bytes {
+        return Err(format!(
+            "Storage quota exceeded ({} of {} used)",
+            format_bytes(storage.used_bytes),
+            format_bytes(storage.quota_bytes)
+        ));
+    }
+    save_company_storage(
+        ctx,
+        CompanyStorage {
+            used_bytes: storage.used_bytes + bytes,
+            ..storage
+        },
+    );
+    Ok(())
+}
 
-    // Promote target to Owner (on CompanyMember)
-    ctx.db.company_member().id().update(CompanyMember {
-        role: UserRole::Owner,
-        ..target_membership
-    });
+fn release_storage(ctx: &ReducerContext, company_id: u64, bytes: u64) {
+    if let Some(storage) = ctx.db.company_storage().company_id().find(company_id) {
+        ctx.db
+            .company_storage()
+            .company_id()
+            .update(CompanyStorage {
+                used_bytes: storage.used_bytes.saturating_sub(bytes),
+                ..storage
+            });
+    }
+}
 
-    log::info!(
-        "AUDIT: User {} of Company {} transferred ownership to {}",
-        id_short(ctx.sender()),
-        company_id,
-        id_short(new_owner_identity)
-    );
+/// Deletes an attachment and its content and frees its storage.
+fn purge_attachment(ctx: &ReducerContext, attachment: &Attachment) {
+    let chunk_ids: Vec<u64> = ctx
+        .db
+        .attachment_chunk()
+        .chunk_by_attachment()
+        .filter(&attachment.id)
+        .map(|c| c.id)
+        .collect();
+    for id in chunk_ids {
+        ctx.db.attachment_chunk().id().delete(id);
+    }
+    release_storage(ctx, attachment.company_id, attachment.size);
+    ctx.db.attachment().id().delete(attachment.id);
+}
 
-    Ok(())
+/// Deletes every attachment of a conversation.
+fn delete_conversation_attachments(ctx: &ReducerContext, kind: ChatKind, conversation_id: u64) {
+    let attachments: Vec<Attachment> = ctx
+        .db
+        .attachment()
+        .attachment_by_conversation()
+        .filter(&conversation_id)
+        .filter(|a| a.kind == kind)
+        .collect();
+    for attachment in &attachments {
+        purge_attachment(ctx, attachment);
+    }
 }
 
-// ---------------------------------------------------------------------------
-// Phase 5 — Inter-Company Connections
-// ---------------------------------------------------------------------------
+/// Discards the company's uploads that were not finished within
+/// `ATTACHMENT_UPLOAD_TTL_HOURS`, so abandoned uploads do not hold quota.
+fn discard_stale_uploads(ctx: &ReducerContext, company_id: u64) {
+    let cutoff =
+        ctx.timestamp.to_micros_since_unix_epoch() - ATTACHMENT_UPLOAD_TTL_HOURS * HOUR_MICROS;
+    let stale: Vec<Attachment> = ctx
+        .db
+        .attachment()
+        .attachment_by_company()
+        .filter(&company_id)
+        .filter(|a| {
+            a.status == AttachmentStatus::Uploading
+                && a.created_at.to_micros_since_unix_epoch() < cutoff
+        })
+        .collect();
+    for attachment in &stale {
+        purge_attachment(ctx, attachment);
+    }
+}
 
-/// Helper: determine which company the `requested_by` identity belongs to
-/// within this connection. Checks membership in both sides of the connection
-/// rather than relying on `active_company_id` (which may have changed since
-/// the request was made).
-///
-/// # Errors
-///
-/// Returns an error if the requesting user is not a member of either company.
-fn requesting_company_id(ctx: &ReducerContext, conn: &Connection) -> Result<u64, String> {
-    if find_membership(ctx, conn.requested_by, conn.company_a).is_some() {
-        return Ok(conn.company_a);
+/// Finds one of the caller's uploads that is still in progress.
+fn find_own_upload(ctx: &ReducerContext, attachment_id: u64) -> Result<Attachment, String> {
+    let attachment = ctx
+        .db
+        .attachment()
+        .id()
+        .find(attachment_id)
+        .filter(|a| a.uploader == ctx.sender())
+        .ok_or("Attachment not found")?;
+    if attachment.status != AttachmentStatus::Uploading {
+        return Err("Upload is already complete".to_string());
     }
-    if find_membership(ctx, conn.requested_by, conn.company_b).is_some() {
-        return Ok(conn.company_b);
+    Ok(attachment)
+}
+
+/// Validates the attachments of a new chat message: finished uploads of the
+/// caller to the same conversation that were not sent before. Returns the IDs
+/// without duplicates.
+fn check_chat_attachments(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    attachment_ids: Vec<u64>,
+) -> Result<Vec<u64>, String> {
+    let mut ids: Vec<u64> = Vec::new();
+    for id in attachment_ids {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
     }
-    Err("Requesting user is not a member of either connected company".to_string())
+    if ids.len() > MAX_ATTACHMENTS_PER_MESSAGE {
+        return Err(format!(
+            "Too many attachments (max {} per message)",
+            MAX_ATTACHMENTS_PER_MESSAGE
+        ));
+    }
+    for id in &ids {
+        let attachment = ctx
+            .db
+            .attachment()
+            .id()
+            .find(*id)
+            .filter(|a| a.uploader == ctx.sender())
+            .ok_or("Attachment not found")?;
+        if attachment.kind != kind || attachment.conversation_id != conversation_id {
+            return Err("Attachment was uploaded to another conversation".to_string());
+        }
+        if attachment.status != AttachmentStatus::Ready {
+            return Err(format!("Upload of {} is not complete", attachment.filename));
+        }
+        if attachment.message_id != 0 {
+            return Err(format!("{} has already been sent", attachment.filename));
+        }
+    }
+    Ok(ids)
 }
 
-/// Request a connection with another company. If the target has blocked us
-/// the call succeeds silently (ghosting — the block is never revealed).
+/// Records the message that attachments were sent with.
+fn link_attachments(ctx: &ReducerContext, attachment_ids: &[u64], message_id: u64) {
+    for id in attachment_ids {
+        if let Some(attachment) = ctx.db.attachment().id().find(*id) {
+            ctx.db.attachment().id().update(Attachment {
+                message_id,
+                ..attachment
+            });
+        }
+    }
+}
+
+/// Starts an upload of a file to a connection's, project's or channel's chat.
+/// The caller must be allowed to post there. The declared `size` is charged
+/// to the company's storage quota right away; the file is then sent in chunks
+/// with `upload_attachment_chunk` and finished with
+/// `complete_attachment_upload`. `checksum` is the BLAKE3 hash of the file as
+/// 64 hex characters. The new attachment appears in `visible_attachments`.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role, targets their own
-/// company, the target company does not exist, the message is too long,
-/// or a non-blocked connection already exists.
+/// Returns an error if the caller may not post in the conversation, the
+/// filename, MIME type or checksum is invalid, the file is empty or larger
+/// than `MAX_ATTACHMENT_BYTES`, or the company's storage quota is exceeded.
 #[spacetimedb::reducer]
 #[allow(clippy::needless_pass_by_value)]
-pub fn request_connection(
+pub fn begin_attachment_upload(
     ctx: &ReducerContext,
-    target_company_id: u64,
-    message: String,
+    kind: ChatKind,
+    conversation_id: u64,
+    filename: String,
+    mime_type: String,
+    size: u64,
+    checksum: String,
 ) -> Result<(), String> {
-    let message = message.trim().to_string();
-    validate_length(&message, "Message", MAX_MESSAGE)?;
-
-    let (_caller, my_company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+    let company_id = require_chat_poster(ctx, kind, conversation_id)?;
 
-    if my_company_id == target_company_id {
-        return Err("Cannot connect to your own company".to_string());
+    let filename = filename.trim().to_string();
+    validate_not_empty(&filename, "Filename")?;
+    validate_length(&filename, "Filename", MAX_FILENAME)?;
+    if filename.contains(['/', '\\']) {
+        return Err("Filename cannot contain path separators".to_string());
     }
-
-    ctx.db
-        .company()
-        .id()
-        .find(target_company_id)
-        .ok_or("Target company not found")?;
-
-    if let Some(conn) = find_connection(ctx, my_company_id, target_company_id) {
-        if conn.status == ConnectionStatus::Blocked {
-            // Ghosting: silently succeed so the block is never revealed
-            return Ok(());
-        }
-        return Err("A connection already exists between these companies".to_string());
+    let mime_type = mime_type.trim().to_lowercase();
+    validate_length(&mime_type, "MIME type", MAX_MIME_TYPE)?;
+    validate_mime_type(&mime_type)?;
+    let checksum = checksum.trim().to_lowercase();
+    if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Checksum must be a BLAKE3 hash in hex".to_string());
+    }
+    if size == 0 {
+        return Err("File is empty".to_string());
+    }
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "File is too large (max {})",
+            format_bytes(MAX_ATTACHMENT_BYTES)
+        ));
     }
 
-    let (lo, hi) = if my_company_id <= target_company_id {
-        (my_company_id, target_company_id)
-    } else {
-        (target_company_id, my_company_id)
-    };
+    discard_stale_uploads(ctx, company_id);
+    reserve_storage(ctx, company_id, size)?;
 
-    ctx.db.company_connection().insert(Connection {
+    ctx.db.attachment().insert(Attachment {
         id: 0,
-        company_a: lo,
-        company_b: hi,
-        status: ConnectionStatus::Pending,
-        requested_by: ctx.sender(),
-        blocking_company_id: None,
-        initial_message: message,
+        kind,
+        conversation_id,
+        company_id,
+        uploader: ctx.sender(),
+        filename,
+        mime_type,
+        size,
+        checksum,
+        status: AttachmentStatus::Uploading,
+        received_bytes: 0,
+        chunk_count: 0,
+        message_id: 0,
         created_at: ctx.timestamp,
+        completed_at: None,
     });
 
-    // Notify target company admins/owners of the incoming request
-    let my_company_name = ctx.db.company().id().find(my_company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_default();
-    notify_company_role(
-        ctx,
-        target_company_id,
-        UserRole::Admin,
-        None,
-        NotificationType::ConnectionRequest,
-        "Connection request".to_string(),
-        format!("{} wants to connect", my_company_name),
-    );
-
     Ok(())
 }
 
-/// Cancel a pending connection request. Only the requesting company can cancel.
+/// Appends the next chunk (at most `ATTACHMENT_CHUNK_BYTES`) to one of the
+/// caller's uploads. Chunks must arrive in order, starting at `seq` 0.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role, no pending connection
-/// exists, or the caller's company is not the requesting side.
+/// Returns an error if the upload is not found or already complete, `seq` is
+/// out of order, or the chunk is empty, too large or runs past the declared
+/// size.
 #[spacetimedb::reducer]
-pub fn cancel_request(
+#[allow(clippy::needless_pass_by_value)]
+pub fn upload_attachment_chunk(
     ctx: &ReducerContext,
-    target_company_id: u64,
+    attachment_id: u64,
+    seq: u32,
+    data: Vec<u8>,
 ) -> Result<(), String> {
-    let (_caller, my_company_id) = require_role_at_least(ctx, UserRole::Admin)?;
-
-    let conn = find_connection(ctx, my_company_id, target_company_id)
-        .ok_or("No connection exists")?;
+    let attachment = find_own_upload(ctx, attachment_id)?;
 
-    if conn.status != ConnectionStatus::Pending {
-        return Err("Connection is not pending".to_string());
+    if seq != attachment.chunk_count {
+        return Err(format!("Expected chunk {}", attachment.chunk_count));
     }
-
-    if requesting_company_id(ctx, &conn)? != my_company_id {
-        return Err("Only the requesting side can cancel a request".to_string());
+    if data.is_empty() {
+        return Err("Chunk cannot be empty".to_string());
+    }
+    if data.len() > ATTACHMENT_CHUNK_BYTES {
+        return Err(format!(
+            "Chunk is too large (max {} bytes)",
+            ATTACHMENT_CHUNK_BYTES
+        ));
+    }
+    let received_bytes = attachment.received_bytes + data.len() as u64;
+    if received_bytes > attachment.size {
+        return Err("Upload exceeds the declared file size".to_string());
     }
 
-    delete_connection_chat(ctx, conn.id);
-    ctx.db.company_connection().id().delete(conn.id);
+    ctx.db.attachment_chunk().insert(AttachmentChunk {
+        id: 0,
+        attachment_id,
+        seq,
+        data,
+    });
+    ctx.db.attachment().id().update(Attachment {
+        received_bytes,
+        chunk_count: seq + 1,
+        ..attachment
+    });
+
     Ok(())
 }
 
-/// Accept a pending connection request. Only the non-requesting company
-/// can accept.
+/// Finishes one of the caller's uploads after verifying its size and
+/// checksum. The attachment can then be sent with a chat message.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role, no pending connection
-/// exists, or the caller's company is the requesting side.
+/// Returns an error if the upload is not found or already complete, not all
+/// bytes have been received, or the content does not match the checksum.
 #[spacetimedb::reducer]
-pub fn accept_connection(
-    ctx: &ReducerContext,
-    target_company_id: u64,
-) -> Result<(), String> {
-    let (_caller, my_company_id) = require_role_at_least(ctx, UserRole::Admin)?;
-
-    let conn = find_connection(ctx, my_company_id, target_company_id)
-        .ok_or("No connection exists")?;
-
-    if conn.status != ConnectionStatus::Pending {
-        return Err("Connection is not pending".to_string());
+pub fn complete_attachment_upload(ctx: &ReducerContext, attachment_id: u64) -> Result<(), String> {
+    let attachment = find_own_upload(ctx, attachment_id)?;
+
+    if attachment.received_bytes != attachment.size {
+        return Err(format!(
+            "Upload is incomplete ({} of {} bytes received)",
+            attachment.received_bytes, attachment.size
+        ));
     }
 
-    let requesting_cid = requesting_company_id(ctx, &conn)?;
-
-    if requesting_cid == my_company_id {
-        return Err("You cannot accept your own connection request".to_string());
+    let mut chunks: Vec<AttachmentChunk> = ctx
+        .db
+        .attachment_chunk()
+        .chunk_by_attachment()
+        .filter(&attachment_id)
+        .collect();
+    chunks.sort_by_key(|c| c.seq);
+    let mut hasher = blake3::Hasher::new();
+    for chunk in &chunks {
+        hasher.update(&chunk.data);
+    }
+    if hasher.finalize().to_hex().as_str() != attachment.checksum {
+        return Err("Checksum mismatch: delete the attachment and upload it again".to_string());
     }
 
-    ctx.db.company_connection().id().update(Connection {
-        status: ConnectionStatus::Accepted,
-        ..conn
+    ctx.db.attachment().id().update(Attachment {
+        status: AttachmentStatus::Ready,
+        completed_at: Some(ctx.timestamp),
+        ..attachment
     });
 
-    // Notify the requesting company that their request was accepted
-    let my_company_name = ctx.db.company().id().find(my_company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_default();
-    notify_company_role(
-        ctx,
-        requesting_cid,
-        UserRole::Admin,
-        None,
-        NotificationType::ConnectionAccepted,
-        "Connection accepted".to_string(),
-        format!("{} accepted your connection request", my_company_name),
-    );
+    Ok(())
+}
+
+/// Deletes one of the caller's attachments that has not been sent yet
+/// (finished or not) and frees its storage. Sent attachments go with their
+/// message (see `delete_chat_message`).
+///
+/// # Errors
+///
+/// Returns an error if the attachment is not found or was already sent.
+#[spacetimedb::reducer]
+pub fn delete_attachment(ctx: &ReducerContext, attachment_id: u64) -> Result<(), String> {
+    let attachment = ctx
+        .db
+        .attachment()
+        .id()
+        .find(attachment_id)
+        .filter(|a| a.uploader == ctx.sender())
+        .ok_or("Attachment not found")?;
+    if attachment.message_id != 0 {
+        return Err("Attachment has been sent; delete the message instead".to_string());
+    }
+
+    purge_attachment(ctx, &attachment);
 
     Ok(())
 }
 
-/// Decline a pending connection request (soft reject — deletes the row).
-/// Only the non-requesting company can decline.
+/// Sets a company's attachment storage quota. Platform moderators only.
+/// Lowering it below the current usage only blocks new uploads.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role, no pending connection
-/// exists, or the caller's company is the requesting side.
+/// Returns an error if the caller is not a moderator or the company is not
+/// found.
 #[spacetimedb::reducer]
-pub fn decline_connection(
+pub fn set_storage_quota(
     ctx: &ReducerContext,
-    target_company_id: u64,
+    company_id: u64,
+    quota_bytes: u64,
 ) -> Result<(), String> {
-    let (_caller, my_company_id) = require_role_at_least(ctx, UserRole::Admin)?;
-
-    let conn = find_connection(ctx, my_company_id, target_company_id)
-        .ok_or("No connection exists")?;
-
-    if conn.status != ConnectionStatus::Pending {
-        return Err("Connection is not pending".to_string());
-    }
+    require_moderator(ctx)?;
+    find_live_company(&ctx.as_read_only().db, company_id).ok_or("Company not found")?;
 
-    if requesting_company_id(ctx, &conn)? == my_company_id {
-        return Err("You cannot decline your own connection request".to_string());
-    }
+    let storage = company_storage_for(ctx, company_id);
+    let old_value = format_bytes(storage.quota_bytes);
+    save_company_storage(
+        ctx,
+        CompanyStorage {
+            quota_bytes,
+            ..storage
+        },
+    );
 
-    // Notify the requesting company that their request was declined
-    let requesting_cid = requesting_company_id(ctx, &conn)?;
-    let my_company_name = ctx.db.company().id().find(my_company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_default();
-    notify_company_role(
+    record_platform_audit(
         ctx,
-        requesting_cid,
-        UserRole::Admin,
+        ctx.sender(),
+        AuditAction::StorageQuotaChanged,
         None,
-        NotificationType::ConnectionDeclined,
-        "Connection declined".to_string(),
-        format!("{} declined your connection request", my_company_name),
+        Some(company_id),
+        old_value,
+        format_bytes(quota_bytes),
     );
 
-    delete_connection_chat(ctx, conn.id);
-    ctx.db.company_connection().id().delete(conn.id);
     Ok(())
 }
 
-/// Block a company. Works from any state — updates an existing connection to
-/// Blocked, or creates a new Blocked row. If already blocked, silently
-/// succeeds without overwriting the original `blocked_by`.
-///
-/// # Errors
-///
-/// Returns an error if the caller is below Admin role or targets their own
-/// company.
-#[spacetimedb::reducer]
-pub fn block_company(
+/// Assembles an attachment's content for `identity`. Sent attachments can be
+/// read by everyone who can see their message; unsent ones only by the
+/// uploader.
+fn read_attachment(
     ctx: &ReducerContext,
-    target_company_id: u64,
-) -> Result<(), String> {
-    let (_caller, my_company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+    identity: Identity,
+    attachment_id: u64,
+) -> Result<Vec<u8>, String> {
+    let attachment = ctx
+        .db
+        .attachment()
+        .id()
+        .find(attachment_id)
+        .filter(|a| a.status == AttachmentStatus::Ready)
+        .ok_or("Attachment not found")?;
 
-    if my_company_id == target_company_id {
-        return Err("Cannot block your own company".to_string());
+    if attachment.uploader != identity {
+        if attachment.message_id == 0 {
+            return Err("Attachment not found".to_string());
+        }
+        let message = find_chat_message(ctx, attachment.kind, attachment.message_id)
+            .map_err(|_| "Attachment not found".to_string())?;
+        let db = &ctx.as_read_only().db;
+        let can_read = !message.hidden
+            && !has_blocked(db, identity, message.sender)
+            && conversation_audience(db, attachment.kind, attachment.conversation_id)
+                .contains(&identity);
+        if !can_read {
+            return Err("Attachment not found".to_string());
+        }
+    }
+
+    let mut chunks: Vec<AttachmentChunk> = ctx
+        .db
+        .attachment_chunk()
+        .chunk_by_attachment()
+        .filter(&attachment_id)
+        .collect();
+    chunks.sort_by_key(|c| c.seq);
+    Ok(chunks.into_iter().flat_map(|c| c.data).collect())
+}
+
+/// Returns the content of an attachment the caller can see (see
+/// `visible_attachments`).
+#[spacetimedb::procedure]
+pub fn download_attachment(
+    ctx: &mut ProcedureContext,
+    attachment_id: u64,
+) -> Result<Vec<u8>, String> {
+    let identity = ctx.sender();
+    ctx.with_tx(|tx| read_attachment(tx, identity, attachment_id))
+}
+
+// ---------------------------------------------------------------------------
+// Chat Retention and Archives
+// ---------------------------------------------------------------------------
+
+/// The archive of a deleted conversation, if any. Only connection and project
+/// conversations are archived; a deleted channel's messages go with it.
+fn find_chat_archive(
+    db: &LocalReadOnly,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Option<ChatArchive> {
+    db.chat_archive()
+        .archive_by_conversation()
+        .filter(&conversation_id)
+        .find(|a| a.kind == kind)
+}
+
+/// Whether a former reader still holds Chat in the company they read the
+/// archived conversation through.
+fn can_read_archive(db: &LocalReadOnly, reader: &ChatArchiveReader) -> bool {
+    db.company_member()
+        .member_by_identity()
+        .filter(&reader.identity)
+        .any(|m| m.company_id == reader.company_id && has_permission(db, &m, Permission::Chat))
+}
+
+/// Users who can still read an archived conversation.
+fn archive_audience(db: &LocalReadOnly, archive_id: u64) -> Vec<Identity> {
+    db.chat_archive_reader()
+        .archive_reader_by_archive()
+        .filter(&archive_id)
+        .filter(|r| can_read_archive(db, r))
+        .map(|r| r.identity)
+        .collect()
+}
+
+/// Archived conversations of `kind` that `identity` can still read.
+fn readable_archives(db: &LocalReadOnly, identity: Identity, kind: ChatKind) -> Vec<ChatArchive> {
+    db.chat_archive_reader()
+        .archive_reader_by_identity()
+        .filter(&identity)
+        .filter(|r| can_read_archive(db, r))
+        .filter_map(|r| db.chat_archive().id().find(r.archive_id))
+        .filter(|a| a.kind == kind)
+        .collect()
+}
+
+/// IDs and send times of a conversation's messages.
+fn conversation_messages(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+) -> Vec<(u64, Timestamp)> {
+    match kind {
+        ChatKind::Connection => ctx
+            .db
+            .connection_chat()
+            .chat_by_connection()
+            .filter(&conversation_id)
+            .map(|m| (m.id, m.created_at))
+            .collect(),
+        ChatKind::Project => ctx
+            .db
+            .project_chat()
+            .pchat_by_project()
+            .filter(&conversation_id)
+            .map(|m| (m.id, m.created_at))
+            .collect(),
+        ChatKind::Channel => ctx
+            .db
+            .channel_chat()
+            .channel_chat_by_channel()
+            .filter(&conversation_id)
+            .map(|m| (m.id, m.created_at))
+            .collect(),
+    }
+}
+
+/// Permanently deletes a chat message with its revisions, attachments, pins
+/// and notifications.
+fn purge_chat_message(ctx: &ReducerContext, kind: ChatKind, message_id: u64) {
+    let Ok(message) = find_chat_message(ctx, kind, message_id) else {
+        return;
+    };
+    // Deleted replies were already uncounted by `delete_chat_message`
+    if let Some(root_id) = message.parent_id.filter(|_| !message.deleted) {
+        update_reply_count(ctx, kind, root_id, false);
+    }
+    if !message.hidden && !message.deleted {
+        count_message_visibility(
+            ctx,
+            kind,
+            message.conversation_id,
+            message_id,
+            message.sender,
+            false,
+        );
+    }
+    for id in message.attachment_ids {
+        if let Some(attachment) = ctx.db.attachment().id().find(id) {
+            purge_attachment(ctx, &attachment);
+        }
+    }
+    withdraw_chat_notifications(ctx, kind, message_id);
+    delete_chat_revisions(ctx, kind, message_id);
+    delete_message_pins(ctx, kind, message_id);
+    unindex_chat_message(ctx, kind, message_id);
+    match kind {
+        ChatKind::Connection => {
+            ctx.db.connection_chat().id().delete(message_id);
+        }
+        ChatKind::Project => {
+            ctx.db.project_chat().id().delete(message_id);
+        }
+        ChatKind::Channel => {
+            ctx.db.channel_chat().id().delete(message_id);
+        }
+    }
+}
+
+/// Permanently deletes a conversation's messages and remaining uploads.
+fn delete_conversation_messages(ctx: &ReducerContext, kind: ChatKind, conversation_id: u64) {
+    for (id, _) in conversation_messages(ctx, kind, conversation_id) {
+        purge_chat_message(ctx, kind, id);
+    }
+    delete_conversation_attachments(ctx, kind, conversation_id);
+}
+
+/// Keeps a conversation read-only for its current readers before its
+/// connection or project is deleted. Each reader keeps access while they hold
+/// Chat in the company they read it through. A conversation without messages
+/// or readers is deleted outright; read cursors, mutes and unsent uploads are
+/// discarded either way.
+fn archive_conversation(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    title: String,
+    company_ids: Vec<u64>,
+) {
+    let db = &ctx.as_read_only().db;
+    let readers: Vec<(Identity, u64)> = conversation_audience(db, kind, conversation_id)
+        .into_iter()
+        .filter_map(|identity| {
+            chat_company_for(ctx, kind, conversation_id, identity).map(|cid| (identity, cid))
+        })
+        .collect();
+
+    delete_read_cursors(ctx, kind, conversation_id);
+    delete_conversation_mutes(ctx, kind, conversation_id);
+    let unsent: Vec<Attachment> = ctx
+        .db
+        .attachment()
+        .attachment_by_conversation()
+        .filter(&conversation_id)
+        .filter(|a| a.kind == kind && a.message_id == 0)
+        .collect();
+    for attachment in &unsent {
+        purge_attachment(ctx, attachment);
     }
 
-    ctx.db
-        .company()
-        .id()
-        .find(target_company_id)
-        .ok_or("Target company not found")?;
-
-    if let Some(conn) = find_connection(ctx, my_company_id, target_company_id) {
-        if conn.status == ConnectionStatus::Blocked {
-            // Already blocked — keep original blocker, silently succeed
-            return Ok(());
-        }
-        ctx.db.company_connection().id().update(Connection {
-            status: ConnectionStatus::Blocked,
-            blocking_company_id: Some(my_company_id),
-            ..conn
-        });
-    } else {
-        let (lo, hi) = if my_company_id <= target_company_id {
-            (my_company_id, target_company_id)
-        } else {
-            (target_company_id, my_company_id)
-        };
+    if readers.is_empty() || conversation_messages(ctx, kind, conversation_id).is_empty() {
+        delete_conversation_messages(ctx, kind, conversation_id);
+        return;
+    }
 
-        ctx.db.company_connection().insert(Connection {
+    let archive = ctx.db.chat_archive().insert(ChatArchive {
+        id: 0,
+        kind,
+        conversation_id,
+        title,
+        company_ids,
+        archived_at: ctx.timestamp,
+    });
+    for (identity, company_id) in readers {
+        ctx.db.chat_archive_reader().insert(ChatArchiveReader {
             id: 0,
-            company_a: lo,
-            company_b: hi,
-            status: ConnectionStatus::Blocked,
-            requested_by: ctx.sender(),
-            blocking_company_id: Some(my_company_id),
-            initial_message: String::new(),
-            created_at: ctx.timestamp,
+            archive_id: archive.id,
+            identity,
+            company_id,
         });
     }
+}
 
-    log::info!(
-        "AUDIT: User {} of Company {} blocked Company {}",
-        id_short(ctx.sender()),
-        my_company_id,
-        target_company_id
+/// Archives a connection's chat and deletes the notifications referring to
+/// the connection before its row is deleted.
+fn archive_connection_chat(ctx: &ReducerContext, conn: &Connection) {
+    delete_connection_notifications(ctx, conn.id);
+    let company_name = |cid: u64| {
+        ctx.db
+            .company()
+            .id()
+            .find(cid)
+            .map(|c| c.name)
+            .unwrap_or_default()
+    };
+    let title = format!(
+        "{} — {}",
+        company_name(conn.company_a),
+        company_name(conn.company_b)
+    );
+    archive_conversation(
+        ctx,
+        ChatKind::Connection,
+        conn.id,
+        title,
+        vec![conn.company_a, conn.company_b],
     );
+}
 
-    Ok(())
+/// Deletes an archived conversation with everything in it.
+fn delete_chat_archive(ctx: &ReducerContext, archive: &ChatArchive) {
+    delete_conversation_messages(ctx, archive.kind, archive.conversation_id);
+    let reader_ids: Vec<u64> = ctx
+        .db
+        .chat_archive_reader()
+        .archive_reader_by_archive()
+        .filter(&archive.id)
+        .map(|r| r.id)
+        .collect();
+    for id in reader_ids {
+        ctx.db.chat_archive_reader().id().delete(id);
+    }
+    ctx.db.chat_archive().id().delete(archive.id);
 }
 
-/// Unblock a company. Only the company that performed the block can unblock.
-///
-/// # Errors
-///
-/// Returns an error if the caller is below Admin role, no blocked connection
-/// exists, or the caller's company is not the one that performed the block.
-#[spacetimedb::reducer]
-pub fn unblock_company(
+/// Fails if the conversation has been archived and is read-only.
+fn require_not_archived(
     ctx: &ReducerContext,
-    target_company_id: u64,
+    kind: ChatKind,
+    conversation_id: u64,
 ) -> Result<(), String> {
-    let (_caller, my_company_id) = require_role_at_least(ctx, UserRole::Admin)?;
-
-    let conn = find_connection(ctx, my_company_id, target_company_id)
-        .ok_or("No connection exists")?;
-
-    if conn.status != ConnectionStatus::Blocked {
-        return Err("Connection is not blocked".to_string());
+    if find_chat_archive(&ctx.as_read_only().db, kind, conversation_id).is_some() {
+        return Err("This conversation is archived".to_string());
     }
+    Ok(())
+}
 
-    // Only the company that performed the block can unblock
-    if let Some(blocker_company) = conn.blocking_company_id {
-        if blocker_company != my_company_id {
-            return Err("Only the company that blocked can unblock".to_string());
+/// Messages sent before the returned time (microseconds since the epoch) are
+/// past the retention of every existing company in `company_ids`. `None`
+/// while any of them keeps chat history indefinitely.
+fn retention_cutoff(ctx: &ReducerContext, company_ids: &[u64]) -> Option<i64> {
+    let mut longest_days: Option<u32> = None;
+    for cid in company_ids {
+        if ctx.db.company().id().find(*cid).is_none() {
+            continue;
         }
+        let days = ctx
+            .db
+            .chat_retention()
+            .company_id()
+            .find(*cid)?
+            .retention_days;
+        longest_days = Some(longest_days.map_or(days, |d| d.max(days)));
     }
+    longest_days
+        .map(|days| ctx.timestamp.to_micros_since_unix_epoch() - i64::from(days) * DAY_MICROS)
+}
 
-    ctx.db.company_connection().id().delete(conn.id);
-
-    log::info!(
-        "AUDIT: User {} of Company {} unblocked Company {}",
-        id_short(ctx.sender()),
-        my_company_id,
-        target_company_id
-    );
+/// Deletes a conversation's messages that are past its retention. Returns the
+/// number of messages deleted.
+fn prune_conversation(
+    ctx: &ReducerContext,
+    kind: ChatKind,
+    conversation_id: u64,
+    company_ids: &[u64],
+) -> usize {
+    let Some(cutoff) = retention_cutoff(ctx, company_ids) else {
+        return 0;
+    };
+    let expired: Vec<u64> = conversation_messages(ctx, kind, conversation_id)
+        .into_iter()
+        .filter(|(_, created_at)| created_at.to_micros_since_unix_epoch() < cutoff)
+        .map(|(id, _)| id)
+        .collect();
+    for id in &expired {
+        purge_chat_message(ctx, kind, *id);
+    }
+    expired.len()
+}
 
-    Ok(())
+/// Schedules the daily retention sweep unless it is already scheduled.
+fn ensure_retention_sweep(ctx: &ReducerContext) {
+    if ctx.db.chat_retention_sweep().iter().next().is_none() {
+        ctx.db.chat_retention_sweep().insert(ChatRetentionSweep {
+            scheduled_id: 0,
+            scheduled_at: ScheduleAt::Interval(
+                Duration::from_secs(CHAT_RETENTION_SWEEP_HOURS * 60 * 60).into(),
+            ),
+        });
+    }
 }
 
-/// Disconnect from a company. Deletes the Accepted connection row.
+/// Sets how many days the caller's company keeps chat history; `None` keeps
+/// it indefinitely. Requires EditProfile. Conversations shared with other
+/// companies are only pruned once the retention of every one of them has
+/// passed, so a short retention cannot erase a partner's records.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is below Admin role, no connection exists,
-/// or the connection is not in Accepted status.
+/// Returns an error if the caller lacks EditProfile or `retention_days` is
+/// below `MIN_CHAT_RETENTION_DAYS`.
 #[spacetimedb::reducer]
-pub fn disconnect_company(
-    ctx: &ReducerContext,
-    target_company_id: u64,
-) -> Result<(), String> {
-    let (_caller, my_company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+pub fn set_chat_retention(ctx: &ReducerContext, retention_days: Option<u32>) -> Result<(), String> {
+    let (_account, company_id) = require_permission(ctx, Permission::EditProfile)?;
 
-    let conn = find_connection(ctx, my_company_id, target_company_id)
-        .ok_or("No connection exists")?;
+    let previous = ctx.db.chat_retention().company_id().find(company_id);
+    let old_value = previous
+        .as_ref()
+        .map(|r| r.retention_days.to_string())
+        .unwrap_or_default();
 
-    if conn.status != ConnectionStatus::Accepted {
-        return Err("Connection is not active".to_string());
+    match retention_days {
+        Some(days) => {
+            if days < MIN_CHAT_RETENTION_DAYS {
+                return Err(format!(
+                    "Retention must be at least {} days",
+                    MIN_CHAT_RETENTION_DAYS
+                ));
+            }
+            let retention = ChatRetention {
+                company_id,
+                retention_days: days,
+                updated_by: ctx.sender(),
+                updated_at: ctx.timestamp,
+            };
+            if previous.is_some() {
+                ctx.db.chat_retention().company_id().update(retention);
+            } else {
+                ctx.db.chat_retention().insert(retention);
+            }
+            ensure_retention_sweep(ctx);
+        }
+        None => {
+            ctx.db.chat_retention().company_id().delete(company_id);
+        }
     }
 
-    delete_connection_chat(ctx, conn.id);
-    ctx.db.company_connection().id().delete(conn.id);
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::ChatRetentionChanged,
+        None,
+        Some(company_id),
+        None,
+        old_value,
+        retention_days.map(|d| d.to_string()).unwrap_or_default(),
+    );
+
     Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Company Deletion (cascade-aware)
-// ---------------------------------------------------------------------------
-
-/// Permanently deletes a company and all associated data. Only the Owner can
-/// do this. Cascades to: invite codes, capabilities, connections (+ chat),
-/// and unlinks all members.
+/// Scheduled daily: deletes chat messages past their conversation's retention
+/// (see `retention_cutoff`) in live and archived conversations. Archives left
+/// without messages, or whose companies no longer exist, are deleted.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller is not the Owner or the company is not found.
+/// Returns an error if called by a client instead of the scheduler.
 #[spacetimedb::reducer]
-pub fn delete_company(ctx: &ReducerContext) -> Result<(), String> {
-    let (_caller, company_id) = require_role_at_least(ctx, UserRole::Owner)?;
+#[allow(clippy::needless_pass_by_value)]
+pub fn sweep_chat_retention(ctx: &ReducerContext, _job: ChatRetentionSweep) -> Result<(), String> {
+    if ctx.sender() != ctx.identity() {
+        return Err("Not permitted".to_string());
+    }
+    run_schema_migrations(ctx);
 
-    let company = ctx
+    let mut pruned = 0;
+    let connections: Vec<(u64, Vec<u64>)> = ctx
         .db
-        .company()
-        .id()
-        .find(company_id)
-        .ok_or("Company not found")?;
+        .company_connection()
+        .iter()
+        .map(|c| (c.id, vec![c.company_a, c.company_b]))
+        .collect();
+    for (conn_id, company_ids) in connections {
+        let count = prune_conversation(ctx, ChatKind::Connection, conn_id, &company_ids);
+        pruned += count;
+    }
 
-    if company.owner_identity != ctx.sender() {
-        return Err("Only the owner can do this".to_string());
+    let project_ids: Vec<u64> = ctx.db.project().iter().map(|p| p.id).collect();
+    for project_id in project_ids {
+        let company_ids: Vec<u64> = ctx
+            .db
+            .project_member()
+            .pm_by_project()
+            .filter(&project_id)
+            .filter(|m| m.status == ProjectMemberStatus::Accepted)
+            .map(|m| m.company_id)
+            .collect();
+        let count = prune_conversation(ctx, ChatKind::Project, project_id, &company_ids);
+        pruned += count;
     }
 
-    // 1. Delete all invite codes for this company
-    let invite_codes: Vec<String> = ctx
+    let channels: Vec<(u64, u64)> = ctx
         .db
-        .invite_code()
-        .invite_by_company()
-        .filter(&company_id)
-        .map(|ic| ic.code)
+        .company_channel()
+        .iter()
+        .map(|c| (c.id, c.company_id))
         .collect();
-    for code in invite_codes {
-        ctx.db.invite_code().code().delete(&code);
+    for (channel_id, company_id) in channels {
+        let count = prune_conversation(ctx, ChatKind::Channel, channel_id, &[company_id]);
+        pruned += count;
     }
 
-    // 2. Delete all memberships and reassign active companies
-    let members: Vec<(u64, Identity)> = ctx
-        .db
-        .company_member()
-        .member_by_company()
-        .filter(&company_id)
-        .map(|m| (m.id, m.identity))
-        .collect();
-    for (member_id, member_identity) in &members {
-        ctx.db.company_member().id().delete(*member_id);
-        reassign_active_company(ctx, *member_identity, company_id);
+    let mut archives_deleted = 0;
+    let archives: Vec<ChatArchive> = ctx.db.chat_archive().iter().collect();
+    for archive in &archives {
+        let companies_left = archive
+            .company_ids
+            .iter()
+            .any(|cid| ctx.db.company().id().find(*cid).is_some());
+        if companies_left {
+            pruned += prune_conversation(
+                ctx,
+                archive.kind,
+                archive.conversation_id,
+                &archive.company_ids,
+            );
+        }
+        if !companies_left
+            || conversation_messages(ctx, archive.kind, archive.conversation_id).is_empty()
+        {
+            delete_chat_archive(ctx, archive);
+            archives_deleted += 1;
+        }
     }
 
-    // 3. Delete capability row
-    ctx.db.capability().company_id().delete(company_id);
+    log::info!(
+        "RETENTION: pruned {} chat messages, deleted {} archived conversations",
+        pruned,
+        archives_deleted
+    );
 
-    // 4. Delete all connections and their chat messages
-    let conn_ids: Vec<u64> = ctx
-        .db
-        .company_connection()
-        .conn_by_company_a()
-        .filter(&company_id)
-        .map(|c| c.id)
-        .collect();
-    let conn_ids_b: Vec<u64> = ctx
-        .db
-        .company_connection()
-        .conn_by_company_b()
-        .filter(&company_id)
-        .map(|c| c.id)
-        .collect();
-    for conn_id in conn_ids.iter().chain(conn_ids_b.iter()) {
-        delete_connection_chat(ctx, *conn_id);
-        ctx.db.company_connection().id().delete(*conn_id);
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Chat Search
+// ---------------------------------------------------------------------------
+
+/// Lowercase words of a message or query with how often each occurs. Words
+/// are runs of letters and digits, so `3m` and `lightbox` in "3m lightbox!"
+/// are separate terms; single letters are skipped.
+fn search_terms(text: &str) -> Vec<(String, u32)> {
+    let mut terms: Vec<(String, u32)> = Vec::new();
+    for word in text.to_lowercase().split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty()
+            || (word.chars().count() < 2 && !word.chars().all(|c| c.is_ascii_digit()))
+        {
+            continue;
+        }
+        let term: String = word.chars().take(MAX_SEARCH_TERM).collect();
+        if let Some(entry) = terms.iter_mut().find(|(t, _)| *t == term) {
+            entry.1 += 1;
+        } else {
+            terms.push((term, 1));
+        }
     }
+    terms
+}
 
-    // 5. Delete all notifications for this company
-    let notif_ids: Vec<u64> = ctx
+/// Removes a message from the search index.
+fn unindex_chat_message(ctx: &ReducerContext, kind: ChatKind, message_id: u64) {
+    let ids: Vec<u64> = ctx
         .db
-        .notification()
-        .iter()
-        .filter(|n| n.company_id == company_id)
-        .map(|n| n.id)
+        .chat_search_term()
+        .search_by_message()
+        .filter(&message_id)
+        .filter(|t| t.kind == kind)
+        .map(|t| t.id)
         .collect();
-    for notif_id in notif_ids {
-        ctx.db.notification().id().delete(notif_id);
+    for id in ids {
+        ctx.db.chat_search_term().id().delete(id);
     }
+}
 
-    // 6. Cascade-delete projects owned by this company
-    let owned_project_ids: Vec<u64> = ctx
-        .db
-        .project()
+/// (Re)indexes a message's text for `search_chat`.
+fn index_chat_message(ctx: &ReducerContext, kind: ChatKind, message_id: u64, text: &str) {
+    unindex_chat_message(ctx, kind, message_id);
+    for (term, occurrences) in search_terms(text) {
+        ctx.db.chat_search_term().insert(ChatSearchTerm {
+            id: 0,
+            term,
+            kind,
+            message_id,
+            occurrences,
+        });
+    }
+}
+
+/// Excerpt of `text` starting a little before the first of `terms` it
+/// contains. Lowercasing can change the length of a character (`İ` becomes
+/// two), so the match is mapped back to its character in `text`.
+fn search_snippet(text: &str, terms: &[String]) -> String {
+    let mut lower = String::new();
+    let mut origin: Vec<usize> = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        for l in c.to_lowercase() {
+            lower.push(l);
+            origin.extend(std::iter::repeat_n(i, l.len_utf8()));
+        }
+    }
+    let first_match = terms
         .iter()
-        .filter(|p| p.owner_company_id == company_id)
-        .map(|p| p.id)
-        .collect();
-    for pid in owned_project_ids {
-        delete_project_cascade(ctx, pid);
+        .filter_map(|t| lower.find(t.as_str()))
+        .min()
+        .and_then(|byte| origin.get(byte).copied())
+        .unwrap_or(0);
+    let start = first_match.saturating_sub(SEARCH_SNIPPET_LEAD);
+    let snippet = truncate_preview(
+        &text.chars().skip(start).collect::<String>(),
+        SEARCH_SNIPPET_LEN,
+    );
+    if start > 0 {
+        format!("...{snippet}")
+    } else {
+        snippet
     }
+}
 
-    // 7. Remove this company from projects it was a member of (not owner)
-    //    Collect affected project IDs before deleting memberships, so we can
-    //    check for orphaned projects afterward (scoped, not full table scan).
-    let affected_project_ids: Vec<u64> = ctx
-        .db
-        .project_member()
-        .pm_by_company()
-        .filter(&company_id)
-        .map(|m| m.project_id)
-        .collect();
-    let pm_ids: Vec<u64> = ctx
-        .db
-        .project_member()
-        .pm_by_company()
-        .filter(&company_id)
-        .map(|m| m.id)
-        .collect();
-    for pm_id in pm_ids {
-        ctx.db.project_member().id().delete(pm_id);
+/// A chat message found by `search_chat`.
+#[derive(SpacetimeType)]
+pub struct ChatSearchHit {
+    pub kind: ChatKind,
+    pub conversation_id: u64,
+    pub message_id: u64,
+    pub sender: Identity,
+    pub created_at: Timestamp,
+    pub snippet: String,
+    /// Total occurrences of the query terms; higher ranks first.
+    pub score: u32,
+}
+
+/// Optional restrictions for `search_chat`.
+#[derive(SpacetimeType)]
+pub struct ChatSearchFilter {
+    pub kind: Option<ChatKind>,
+    pub conversation_id: Option<u64>,
+    pub sender: Option<Identity>,
+    pub sent_after: Option<Timestamp>,
+    pub sent_before: Option<Timestamp>,
+}
+
+/// Runs a search for `identity` (see `search_chat`).
+fn search_chat_messages(
+    ctx: &ReducerContext,
+    identity: Identity,
+    query: &str,
+    filter: &ChatSearchFilter,
+    limit: u32,
+) -> Result<Vec<ChatSearchHit>, String> {
+    let query = query.trim();
+    validate_length(query, "Search query", MAX_SEARCH_QUERY)?;
+    let terms: Vec<String> = search_terms(query).into_iter().map(|(t, _)| t).collect();
+    if terms.is_empty() {
+        return Err("Search query is too short".to_string());
     }
 
-    // 8. Auto-delete any affected projects left with 0 Accepted members
-    for pid in affected_project_ids {
-        // Project may have already been cascade-deleted in step 6
-        if ctx.db.project().id().find(pid).is_none() {
+    let db = &ctx.as_read_only().db;
+    let blocked = blocked_by(db, identity);
+    let mut hits: Vec<ChatSearchHit> = Vec::new();
+
+    for kind in [ChatKind::Connection, ChatKind::Project, ChatKind::Channel] {
+        if filter.kind.is_some_and(|k| k != kind) {
             continue;
         }
-        let has_accepted = ctx
-            .db
-            .project_member()
-            .pm_by_project()
-            .filter(&pid)
-            .any(|m| m.status == ProjectMemberStatus::Accepted);
-        if !has_accepted {
-            delete_project_cascade(ctx, pid);
-        }
-    }
-
-    // 9. Delete the company row
-    ctx.db.company().id().delete(company_id);
+        let visible = visible_conversation_ids(db, identity, kind);
 
-    log::info!(
-        "AUDIT: User {} deleted Company {} (name: {}, {} members unlinked)",
-        id_short(ctx.sender()),
-        company_id,
-        company.name,
-        members.len()
-    );
+        // Messages containing every term, with their total occurrences
+        let mut scores: Vec<(u64, u32)> = ctx
+            .db
+            .chat_search_term()
+            .search_by_term()
+            .filter(&terms[0])
+            .filter(|t| t.kind == kind)
+            .map(|t| (t.message_id, t.occurrences))
+            .collect();
+        for term in &terms[1..] {
+            let matches: Vec<(u64, u32)> = ctx
+                .db
+                .chat_search_term()
+                .search_by_term()
+                .filter(term)
+                .filter(|t| t.kind == kind)
+                .map(|t| (t.message_id, t.occurrences))
+                .collect();
+            scores.retain_mut(|(id, score)| match matches.iter().find(|(m, _)| m == id) {
+                Some((_, occurrences)) => {
+                    *score += occurrences;
+                    true
+                }
+                None => false,
+            });
+        }
 
-    Ok(())
+        for (message_id, score) in scores {
+            let Ok(message) = find_chat_message(ctx, kind, message_id) else {
+                continue;
+            };
+            let matches_filter = filter
+                .conversation_id
+                .is_none_or(|c| c == message.conversation_id)
+                && filter.sender.is_none_or(|s| s == message.sender)
+                && filter.sent_after.is_none_or(|t| message.created_at >= t)
+                && filter.sent_before.is_none_or(|t| message.created_at < t);
+            let can_see = !message.hidden
+                && !message.deleted
+                && !blocked.contains(&message.sender)
+                && visible.binary_search(&message.conversation_id).is_ok();
+            if matches_filter && can_see {
+                hits.push(ChatSearchHit {
+                    kind,
+                    conversation_id: message.conversation_id,
+                    message_id,
+                    sender: message.sender,
+                    created_at: message.created_at,
+                    snippet: search_snippet(&message.text, &terms),
+                    score,
+                });
+            }
+        }
+    }
+
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then(b.created_at.cmp(&a.created_at)));
+    let limit = if limit == 0 {
+        DEFAULT_SEARCH_RESULTS
+    } else {
+        limit.min(MAX_SEARCH_RESULTS)
+    };
+    hits.truncate(limit as usize);
+    Ok(hits)
 }
 
-// ---------------------------------------------------------------------------
-// Phase 6 — Connection Chat
-// ---------------------------------------------------------------------------
+/// Searches the chat messages the caller can see (the same messages as
+/// `my_connection_chat`, `my_project_chat` and `my_channel_chat`) for messages containing every
+/// word of `query`. Results are ranked by how often the words occur, newest
+/// first on ties, and carry a snippet around the first match. `limit` caps
+/// the results (0 for the default of `DEFAULT_SEARCH_RESULTS`).
+#[spacetimedb::procedure]
+pub fn search_chat(
+    ctx: &mut ProcedureContext,
+    query: String,
+    filter: ChatSearchFilter,
+    limit: u32,
+) -> Result<Vec<ChatSearchHit>, String> {
+    let identity = ctx.sender();
+    ctx.with_tx(|tx| search_chat_messages(tx, identity, &query, &filter, limit))
+}
 
-/// Send a chat message within a connection. Only allowed when the connection
-/// is Pending or Accepted, and the caller belongs to one of the two companies.
+/// Rebuilds the search index of one conversation, e.g. after a failed write
+/// left it out of step with the messages: visible user messages are indexed
+/// again and the rest removed from the index. Platform moderators only.
 ///
 /// # Errors
 ///
-/// Returns an error if the caller has no account or company, the connection
-/// is not found, the connection is Blocked, the caller's company is not
-/// part of the connection, or the message is empty or too long.
+/// Returns an error if the caller is not a moderator.
 #[spacetimedb::reducer]
-#[allow(clippy::needless_pass_by_value)]
-pub fn send_connection_chat(
+pub fn rebuild_chat_search_index(
     ctx: &ReducerContext,
-    connection_id: u64,
-    text: String,
+    kind: ChatKind,
+    conversation_id: u64,
 ) -> Result<(), String> {
-    let text = text.trim().to_string();
-    if text.is_empty() {
-        return Err("Message cannot be empty".to_string());
-    }
-    validate_length(&text, "Message", MAX_MESSAGE)?;
-
-    // Pending users cannot chat — they must be activated first
-    let (_account, my_company_id) = require_role_at_least(ctx, UserRole::Field)?;
-
-    let conn = ctx
-        .db
-        .company_connection()
-        .id()
-        .find(connection_id)
-        .ok_or("Connection not found")?;
-
-    if conn.status == ConnectionStatus::Blocked {
-        return Err("Cannot chat on a blocked connection".to_string());
-    }
+    require_moderator(ctx)?;
 
-    if conn.company_a != my_company_id && conn.company_b != my_company_id {
-        return Err("Your company is not part of this connection".to_string());
+    // (id, text) per message; no text for messages that are not searchable
+    let messages: Vec<(u64, Option<String>)> = match kind {
+        ChatKind::Connection => ctx
+            .db
+            .connection_chat()
+            .chat_by_connection()
+            .filter(&conversation_id)
+            .map(|m| {
+                (
+                    m.id,
+                    (!m.deleted && m.message_kind == MessageKind::User).then_some(m.text),
+                )
+            })
+            .collect(),
+        ChatKind::Project => ctx
+            .db
+            .project_chat()
+            .pchat_by_project()
+            .filter(&conversation_id)
+            .map(|m| {
+                (
+                    m.id,
+                    (!m.deleted && m.message_kind == MessageKind::User).then_some(m.text),
+                )
+            })
+            .collect(),
+        ChatKind::Channel => ctx
+            .db
+            .channel_chat()
+            .channel_chat_by_channel()
+            .filter(&conversation_id)
+            .map(|m| {
+                (
+                    m.id,
+                    (!m.deleted && m.message_kind == MessageKind::User).then_some(m.text),
+                )
+            })
+            .collect(),
+    };
+    let mut indexed = 0;
+    for (id, text) in messages {
+        match text {
+            Some(text) => {
+                index_chat_message(ctx, kind, id, &text);
+                indexed += 1;
+            }
+            None => unindex_chat_message(ctx, kind, id),
+        }
     }
 
-    ctx.db.connection_chat().insert(ConnectionChat {
-        id: 0,
-        connection_id,
-        sender: ctx.sender(),
-        text: text.clone(),
-        created_at: ctx.timestamp,
-    });
-
-    // Notify the other company about the new message
-    let other_company_id = if conn.company_a == my_company_id {
-        conn.company_b
-    } else {
-        conn.company_a
-    };
-    let sender_name = _account.nickname.clone();
-    let preview = truncate_preview(&text, 50);
-    notify_company_role(
+    record_platform_audit(
         ctx,
-        other_company_id,
-        UserRole::Field,
+        ctx.sender(),
+        AuditAction::ChatSearchIndexRebuilt,
         None,
-        NotificationType::ChatMessage,
-        "New message".to_string(),
-        format!("{}: {}", sender_name, preview),
+        None,
+        String::new(),
+        format!("{kind:?} conversation {conversation_id}: {indexed} messages"),
     );
 
     Ok(())
 }
 
 // ---------------------------------------------------------------------------
-// Phase 7 — Notifications
+// Platform Moderation
 // ---------------------------------------------------------------------------
 
-/// Mark a single notification as read.
-#[spacetimedb::reducer]
-pub fn mark_notification_read(ctx: &ReducerContext, notification_id: u64) -> Result<(), String> {
-    let notif = ctx
-        .db
-        .notification()
-        .id()
-        .find(notification_id)
-        .ok_or("Notification not found")?;
+/// Verifies that the caller is a platform moderator.
+fn require_moderator(ctx: &ReducerContext) -> Result<(), String> {
+    ctx.db
+        .platform_moderator()
+        .identity()
+        .find(ctx.sender())
+        .ok_or("Only platform moderators can do this")?;
+    Ok(())
+}
 
-    if notif.recipient_identity != ctx.sender() {
-        return Err("Not your notification".to_string());
+/// Checks that `reporter` can see the reported content and returns an excerpt
+/// of it for the report.
+fn report_excerpt(
+    ctx: &ReducerContext,
+    reporter: Identity,
+    target: ReportTarget,
+) -> Result<String, String> {
+    match target {
+        ReportTarget::ConnectionMessage(message_id) => {
+            let msg = ctx
+                .db
+                .connection_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            let conn = ctx
+                .db
+                .company_connection()
+                .id()
+                .find(msg.connection_id)
+                .ok_or("Message not found")?;
+            let is_party = find_membership(ctx, reporter, conn.company_a).is_some()
+                || find_membership(ctx, reporter, conn.company_b).is_some();
+            if !is_party {
+                return Err("Message not found".to_string());
+            }
+            Ok(truncate_preview(&msg.text, 200))
+        }
+        ReportTarget::ProjectMessage(message_id) => {
+            let msg = ctx
+                .db
+                .project_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            find_project_participant(&ctx.as_read_only().db, msg.project_id, reporter)
+                .ok_or("Message not found")?;
+            Ok(truncate_preview(&msg.text, 200))
+        }
+        ReportTarget::Company(company_id) => {
+            let company =
+                find_live_company(&ctx.as_read_only().db, company_id).ok_or("Company not found")?;
+            Ok(format!("{} ({})", company.name, company.slug))
+        }
+        ReportTarget::User(identity) => {
+            if identity == reporter {
+                return Err("Cannot report yourself".to_string());
+            }
+            let account = ctx
+                .db
+                .user_account()
+                .identity()
+                .find(identity)
+                .ok_or("User not found")?;
+            Ok(format!("{} ({})", account.nickname, account.full_name))
+        }
+        ReportTarget::ChannelMessage(message_id) => {
+            let msg = ctx
+                .db
+                .channel_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            find_channel_member(&ctx.as_read_only().db, msg.channel_id, reporter)
+                .ok_or("Message not found")?;
+            Ok(truncate_preview(&msg.text, 200))
+        }
     }
+}
 
-    ctx.db.notification().id().update(Notification {
-        is_read: true,
-        ..notif
-    });
-
-    Ok(())
+/// Describes what a report points at for notification texts, e.g. "a
+/// project chat message" or the reported company's name.
+fn describe_report_target(db: &LocalReadOnly, target: ReportTarget) -> String {
+    match target {
+        ReportTarget::ConnectionMessage(_) => "a connection chat message".to_string(),
+        ReportTarget::ProjectMessage(_) => "a project chat message".to_string(),
+        ReportTarget::ChannelMessage(_) => "a channel message".to_string(),
+        ReportTarget::Company(company_id) => db.company().id().find(company_id).map_or_else(
+            || "a company".to_string(),
+            |c| format!("company {}", c.name),
+        ),
+        ReportTarget::User(identity) => db
+            .user_account()
+            .identity()
+            .find(identity)
+            .map_or_else(|| "a user".to_string(), |a| format!("user {}", a.nickname)),
+    }
 }
 
-/// Mark all unread notifications as read for the caller within a specific company.
+/// Reports a chat message, company or user to the platform moderators.
+///
+/// # Errors
+///
+/// Returns an error if the caller has no account, the reason is empty or too
+/// long, the target does not exist or is not visible to the caller, or the
+/// caller already has an open report on the same target.
 #[spacetimedb::reducer]
-pub fn mark_all_notifications_read(ctx: &ReducerContext, company_id: u64) -> Result<(), String> {
-    let _account = ctx
+#[allow(clippy::needless_pass_by_value)]
+pub fn report(ctx: &ReducerContext, target: ReportTarget, reason: String) -> Result<(), String> {
+    let account = ctx
         .db
         .user_account()
         .identity()
         .find(ctx.sender())
         .ok_or("Account not found")?;
 
-    let to_update: Vec<Notification> = ctx
+    let reason = reason.trim().to_string();
+    validate_not_empty(&reason, "Reason")?;
+    validate_length(&reason, "Reason", MAX_REPORT_TEXT)?;
+
+    let excerpt = report_excerpt(ctx, ctx.sender(), target)?;
+
+    let already_open = ctx
         .db
-        .notification()
-        .notif_by_recipient()
+        .moderation_report()
+        .report_by_reporter()
         .filter(&ctx.sender())
-        .filter(|n| n.company_id == company_id && !n.is_read)
-        .collect();
-
-    for notif in to_update {
-        ctx.db.notification().id().update(Notification {
-            is_read: true,
-            ..notif
-        });
+        .any(|r| r.target == target && r.status == ReportStatus::Open);
+    if already_open {
+        return Err("You have already reported this".to_string());
     }
 
-    Ok(())
-}
-
-/// Delete all read notifications for the caller within a specific company.
-#[spacetimedb::reducer]
-pub fn clear_notifications(ctx: &ReducerContext, company_id: u64) -> Result<(), String> {
-    let _account = ctx
-        .db
-        .user_account()
-        .identity()
-        .find(ctx.sender())
-        .ok_or("Account not found")?;
+    ctx.db.moderation_report().insert(ModerationReport {
+        id: 0,
+        reporter: ctx.sender(),
+        target,
+        reason: reason.clone(),
+        excerpt,
+        status: ReportStatus::Open,
+        created_at: ctx.timestamp,
+        reviewed_by: None,
+        decision: String::new(),
+        reviewed_at: None,
+    });
 
-    let to_delete: Vec<u64> = ctx
+    let moderators: Vec<Identity> = ctx
         .db
-        .notification()
-        .notif_by_recipient()
-        .filter(&ctx.sender())
-        .filter(|n| n.company_id == company_id && n.is_read)
-        .map(|n| n.id)
+        .platform_moderator()
+        .iter()
+        .map(|m| m.identity)
         .collect();
-
-    for id in to_delete {
-        ctx.db.notification().id().delete(id);
+    let preview = truncate_preview(&reason, 80);
+    let described = describe_report_target(&ctx.as_read_only().db, target);
+    for moderator in moderators {
+        notify(
+            ctx,
+            moderator,
+            0,
+            NotificationType::ReportSubmitted,
+            "New report".to_string(),
+            format!("{} reported {}: {}", account.nickname, described, preview),
+            NotificationRefs {
+                scope: NotificationScope::Platform,
+                ..NotificationRefs::default()
+            },
+        );
     }
 
     Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Phase 8 — Projects (Multi-Company Rooms)
-// ---------------------------------------------------------------------------
-
-/// Create a new project room. The caller's active company becomes the owner
-/// and is automatically added as an Accepted member.
+/// Closes an open report as Dismissed or ActionTaken, recording the decision
+/// and notifying the reporter. Hiding messages and suspending companies are
+/// separate reducers.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator, the report is not found
+/// or already reviewed, `status` is Open, or the decision is too long.
 #[spacetimedb::reducer]
-pub fn create_project(
+#[allow(clippy::needless_pass_by_value)]
+pub fn review_report(
     ctx: &ReducerContext,
-    name: String,
-    description: String,
+    report_id: u64,
+    status: ReportStatus,
+    decision: String,
 ) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+    require_moderator(ctx)?;
 
-    let name = name.trim().to_string();
-    let description = description.trim().to_string();
-    validate_not_empty(&name, "Project name")?;
-    validate_length(&name, "Project name", MAX_PROJECT_NAME)?;
-    validate_length(&description, "Description", MAX_PROJECT_DESCRIPTION)?;
+    if status == ReportStatus::Open {
+        return Err("A review must dismiss the report or record the action taken".to_string());
+    }
 
-    let project = ctx.db.project().insert(Project {
-        id: 0,
-        owner_company_id: company_id,
-        name: name.clone(),
-        description,
-        created_by: ctx.sender(),
-        created_at: ctx.timestamp,
-    });
+    let decision = decision.trim().to_string();
+    validate_length(&decision, "Decision", MAX_REPORT_TEXT)?;
 
-    // Auto-add creator's company as Accepted member
-    ctx.db.project_member().insert(ProjectMember {
-        id: 0,
-        project_id: project.id,
-        company_id,
-        status: ProjectMemberStatus::Accepted,
-        invited_by: ctx.sender(),
-        joined_at: ctx.timestamp,
+    let report = ctx
+        .db
+        .moderation_report()
+        .id()
+        .find(report_id)
+        .ok_or("Report not found")?;
+    if report.status != ReportStatus::Open {
+        return Err("Report has already been reviewed".to_string());
+    }
+
+    let reporter = report.reporter;
+    let target = report.target;
+    ctx.db.moderation_report().id().update(ModerationReport {
+        status,
+        reviewed_by: Some(ctx.sender()),
+        decision: decision.clone(),
+        reviewed_at: Some(ctx.timestamp),
+        ..report
     });
 
-    log::info!(
-        "AUDIT: User {} created Project {} '{}' (owner company {})",
-        id_short(ctx.sender()),
-        project.id,
-        name,
-        company_id
-    );
+    if reporter != Identity::ZERO {
+        let outcome = match status {
+            ReportStatus::Dismissed => "no action was taken",
+            ReportStatus::Open | ReportStatus::ActionTaken => "action was taken",
+        };
+        let described = describe_report_target(&ctx.as_read_only().db, target);
+        let body = if decision.is_empty() {
+            format!("Your report on {} was reviewed: {}.", described, outcome)
+        } else {
+            format!(
+                "Your report on {} was reviewed: {}. {}",
+                described, outcome, decision
+            )
+        };
+        notify(
+            ctx,
+            reporter,
+            0,
+            NotificationType::ReportReviewed,
+            "Report reviewed".to_string(),
+            body,
+            NotificationRefs {
+                hide_actor: true,
+                scope: NotificationScope::Platform,
+                ..NotificationRefs::default()
+            },
+        );
+    }
 
     Ok(())
 }
 
-/// Invite another company to a project. Only admins+ of the project owner
-/// company can invite. Cleans up old Left/Kicked rows before inserting.
+/// Hides (or unhides) a chat message. Hidden messages disappear from the chat
+/// views for everyone, and the notifications raised for them are withdrawn.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator, the target is not a chat
+/// message, or the message is not found.
 #[spacetimedb::reducer]
-pub fn invite_to_project(
+pub fn set_message_hidden(
     ctx: &ReducerContext,
-    project_id: u64,
-    target_company_id: u64,
+    target: ReportTarget,
+    hidden: bool,
 ) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
-
-    let project = ctx
-        .db
-        .project()
-        .id()
-        .find(project_id)
-        .ok_or("Project not found")?;
-
-    if project.owner_company_id != company_id {
-        return Err("Only the owner company can invite".to_string());
-    }
-
-    // Target company must exist
-    let _target = ctx
-        .db
-        .company()
-        .id()
-        .find(target_company_id)
-        .ok_or("Target company not found")?;
-
-    if target_company_id == company_id {
-        return Err("Cannot invite your own company".to_string());
-    }
+    require_moderator(ctx)?;
 
-    // Require an accepted connection between the two companies
-    let conn = find_connection(ctx, company_id, target_company_id);
-    match conn {
-        Some(c) if c.status == ConnectionStatus::Accepted => { /* ok */ }
-        _ => return Err("You must have an accepted connection with this company first".to_string()),
-    }
-
-    // Check for existing membership
-    let existing: Option<ProjectMember> = ctx
-        .db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .find(|m| m.company_id == target_company_id);
-
-    if let Some(existing_member) = existing {
-        match existing_member.status {
-            ProjectMemberStatus::Accepted => {
-                return Err("Company is already a member of this project".to_string());
+    let sender = match target {
+        ReportTarget::ConnectionMessage(message_id) => {
+            let msg = ctx
+                .db
+                .connection_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            if msg.hidden != hidden && !msg.deleted {
+                count_message_visibility(
+                    ctx,
+                    ChatKind::Connection,
+                    msg.connection_id,
+                    message_id,
+                    msg.sender,
+                    !hidden,
+                );
             }
-            ProjectMemberStatus::Invited => {
-                return Err("Company has already been invited".to_string());
+            if hidden {
+                withdraw_chat_notifications(ctx, ChatKind::Connection, message_id);
             }
-            ProjectMemberStatus::Left | ProjectMemberStatus::Kicked => {
-                // Clean up old row so we can re-invite
-                ctx.db.project_member().id().delete(existing_member.id);
+            let sender = msg.sender;
+            ctx.db
+                .connection_chat()
+                .id()
+                .update(ConnectionChat { hidden, ..msg });
+            sender
+        }
+        ReportTarget::ProjectMessage(message_id) => {
+            let msg = ctx
+                .db
+                .project_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            if msg.hidden != hidden && !msg.deleted {
+                count_message_visibility(
+                    ctx,
+                    ChatKind::Project,
+                    msg.project_id,
+                    message_id,
+                    msg.sender,
+                    !hidden,
+                );
+            }
+            if hidden {
+                withdraw_chat_notifications(ctx, ChatKind::Project, message_id);
+            }
+            let sender = msg.sender;
+            ctx.db
+                .project_chat()
+                .id()
+                .update(ProjectChat { hidden, ..msg });
+            sender
+        }
+        ReportTarget::ChannelMessage(message_id) => {
+            let msg = ctx
+                .db
+                .channel_chat()
+                .id()
+                .find(message_id)
+                .ok_or("Message not found")?;
+            if msg.hidden != hidden && !msg.deleted {
+                count_message_visibility(
+                    ctx,
+                    ChatKind::Channel,
+                    msg.channel_id,
+                    message_id,
+                    msg.sender,
+                    !hidden,
+                );
+            }
+            if hidden {
+                withdraw_chat_notifications(ctx, ChatKind::Channel, message_id);
             }
+            let sender = msg.sender;
+            ctx.db
+                .channel_chat()
+                .id()
+                .update(ChannelChat { hidden, ..msg });
+            sender
         }
-    }
-
-    ctx.db.project_member().insert(ProjectMember {
-        id: 0,
-        project_id,
-        company_id: target_company_id,
-        status: ProjectMemberStatus::Invited,
-        invited_by: ctx.sender(),
-        joined_at: ctx.timestamp,
-    });
+        ReportTarget::Company(_) | ReportTarget::User(_) => {
+            return Err("Only chat messages can be hidden".to_string());
+        }
+    };
 
-    // Notify target company admins
-    notify_company_role(
+    let action = if hidden {
+        AuditAction::MessageHidden
+    } else {
+        AuditAction::MessageUnhidden
+    };
+    record_platform_audit(
         ctx,
-        target_company_id,
-        UserRole::Admin,
+        ctx.sender(),
+        action,
+        Some(sender),
         None,
-        NotificationType::ProjectInvite,
-        format!("Project invitation: {}", project.name),
-        format!(
-            "Your company has been invited to join project '{}'",
-            project.name
-        ),
-    );
-
-    log::info!(
-        "AUDIT: User {} invited Company {} to Project {} '{}'",
-        id_short(ctx.sender()),
-        target_company_id,
-        project_id,
-        project.name
+        describe_report_target(&ctx.as_read_only().db, target),
+        String::new(),
     );
 
     Ok(())
 }
 
-/// Accept a pending project invitation. Caller must be admin+ of the invited company.
+/// Suspends a company. Its members can no longer act as it (see
+/// `require_active_membership`) until a moderator lifts the suspension.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator, the company is not
+/// found or already suspended, or the reason is empty or too long.
 #[spacetimedb::reducer]
-pub fn accept_project_invite(ctx: &ReducerContext, project_id: u64) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+#[allow(clippy::needless_pass_by_value)]
+pub fn suspend_company(
+    ctx: &ReducerContext,
+    company_id: u64,
+    reason: String,
+) -> Result<(), String> {
+    require_moderator(ctx)?;
 
-    let project = ctx
-        .db
-        .project()
-        .id()
-        .find(project_id)
-        .ok_or("Project not found")?;
+    let reason = reason.trim().to_string();
+    validate_not_empty(&reason, "Reason")?;
+    validate_length(&reason, "Reason", MAX_REPORT_TEXT)?;
 
-    let membership = find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Invited)
-        .ok_or("No pending invitation found")?;
+    let company =
+        find_live_company(&ctx.as_read_only().db, company_id).ok_or("Company not found")?;
+    if ctx
+        .db
+        .company_suspension()
+        .company_id()
+        .find(company_id)
+        .is_some()
+    {
+        return Err("Company is already suspended".to_string());
+    }
 
-    ctx.db.project_member().id().update(ProjectMember {
-        status: ProjectMemberStatus::Accepted,
-        joined_at: ctx.timestamp,
-        ..membership
+    ctx.db.company_suspension().insert(CompanySuspension {
+        company_id,
+        reason: reason.clone(),
+        suspended_by: ctx.sender(),
+        suspended_at: ctx.timestamp,
     });
 
-    let company_name = ctx
-        .db
-        .company()
-        .id()
-        .find(company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_else(|| "Unknown".to_string());
+    notify(
+        ctx,
+        company.owner_identity,
+        company_id,
+        NotificationType::CompanySuspension,
+        format!("{} has been suspended", company.name),
+        reason.clone(),
+        NotificationRefs {
+            hide_actor: true,
+            ..NotificationRefs::default()
+        },
+    );
 
-    // Notify all other Accepted companies' admins
-    let other_accepted: Vec<u64> = ctx
+    record_audit(
+        ctx,
+        company_id,
+        AuditAction::CompanySuspended,
+        None,
+        Some(company_id),
+        None,
+        String::new(),
+        reason,
+    );
+
+    Ok(())
+}
+
+/// Lifts a company's suspension.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator or the company is not
+/// suspended.
+#[spacetimedb::reducer]
+pub fn unsuspend_company(ctx: &ReducerContext, company_id: u64) -> Result<(), String> {
+    require_moderator(ctx)?;
+
+    let suspension = ctx
         .db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .filter(|m| m.status == ProjectMemberStatus::Accepted && m.company_id != company_id)
-        .map(|m| m.company_id)
-        .collect();
+        .company_suspension()
+        .company_id()
+        .find(company_id)
+        .ok_or("Company is not suspended")?;
+    ctx.db.company_suspension().company_id().delete(company_id);
 
-    for cid in other_accepted {
-        notify_company_role(
+    if let Some(company) = ctx.db.company().id().find(company_id) {
+        notify(
             ctx,
-            cid,
-            UserRole::Admin,
-            None,
-            NotificationType::ProjectAccepted,
-            format!("{} joined project", company_name),
-            format!(
-                "{} accepted the invitation to project '{}'",
-                company_name, project.name
-            ),
+            company.owner_identity,
+            company_id,
+            NotificationType::CompanySuspension,
+            format!("{} is no longer suspended", company.name),
+            "A platform moderator lifted the suspension.".to_string(),
+            NotificationRefs {
+                hide_actor: true,
+                ..NotificationRefs::default()
+            },
         );
     }
 
-    log::info!(
-        "AUDIT: Company {} accepted invite to Project {} '{}'",
+    record_audit(
+        ctx,
         company_id,
-        project_id,
-        project.name
+        AuditAction::CompanyUnsuspended,
+        None,
+        Some(company_id),
+        None,
+        suspension.reason,
+        String::new(),
     );
 
     Ok(())
 }
 
-/// Decline a pending project invitation. Deletes the Invited row.
+/// Summarises rate limits for the platform audit trail.
+fn describe_rate_limits(config: &RateLimitConfig) -> String {
+    format!(
+        "chat {}/{} per minute, requests {}/h user, {}/day company",
+        config.chat_messages_per_minute_user,
+        config.chat_messages_per_minute_company,
+        config.connection_requests_per_hour_user,
+        config.connection_requests_per_day_company
+    )
+}
+
+/// Updates the platform-wide rate limits (see `check_rate_limit`).
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator or any limit is zero.
 #[spacetimedb::reducer]
-pub fn decline_project_invite(ctx: &ReducerContext, project_id: u64) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+pub fn set_rate_limits(
+    ctx: &ReducerContext,
+    chat_messages_per_minute_user: u32,
+    chat_messages_per_minute_company: u32,
+    connection_requests_per_hour_user: u32,
+    connection_requests_per_day_company: u32,
+) -> Result<(), String> {
+    require_moderator(ctx)?;
+
+    let limits = [
+        chat_messages_per_minute_user,
+        chat_messages_per_minute_company,
+        connection_requests_per_hour_user,
+        connection_requests_per_day_company,
+    ];
+    if limits.contains(&0) {
+        return Err("Rate limits must be at least 1".to_string());
+    }
 
-    let project = ctx
-        .db
-        .project()
-        .id()
-        .find(project_id)
-        .ok_or("Project not found")?;
+    let old_value = describe_rate_limits(&rate_limit_config(ctx));
+    let config = RateLimitConfig {
+        id: 0,
+        chat_messages_per_minute_user,
+        chat_messages_per_minute_company,
+        connection_requests_per_hour_user,
+        connection_requests_per_day_company,
+    };
+    let new_value = describe_rate_limits(&config);
+    if ctx.db.rate_limit_config().id().find(0).is_some() {
+        ctx.db.rate_limit_config().id().update(config);
+    } else {
+        ctx.db.rate_limit_config().insert(config);
+    }
 
-    let membership = find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Invited)
-        .ok_or("No pending invitation found")?;
+    record_platform_audit(
+        ctx,
+        ctx.sender(),
+        AuditAction::RateLimitsChanged,
+        None,
+        None,
+        old_value,
+        new_value,
+    );
 
-    ctx.db.project_member().id().delete(membership.id);
+    Ok(())
+}
 
-    let company_name = ctx
-        .db
-        .company()
-        .id()
-        .find(company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_else(|| "Unknown".to_string());
+/// Sets how many days a deleted company stays restorable before it is purged
+/// (see `delete_company`). Companies already pending deletion keep their date.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator or the value is outside
+/// 1..=`MAX_COMPANY_DELETION_GRACE_DAYS`.
+#[spacetimedb::reducer]
+pub fn set_company_deletion_grace_days(ctx: &ReducerContext, days: u32) -> Result<(), String> {
+    require_moderator(ctx)?;
+
+    if days == 0 || days > MAX_COMPANY_DELETION_GRACE_DAYS {
+        return Err(format!(
+            "Grace period must be between 1 and {MAX_COMPANY_DELETION_GRACE_DAYS} days"
+        ));
+    }
 
-    // Notify owner company admins
-    notify_company_role(
+    let previous = platform_config(ctx);
+    let old_value = previous.company_deletion_grace_days.to_string();
+    save_platform_config(
         ctx,
-        project.owner_company_id,
-        UserRole::Admin,
-        None,
-        NotificationType::ProjectDeclined,
-        format!("{} declined project invite", company_name),
-        format!(
-            "{} declined the invitation to project '{}'",
-            company_name, project.name
-        ),
+        PlatformConfig {
+            company_deletion_grace_days: days,
+            ..previous
+        },
     );
 
-    log::info!(
-        "AUDIT: Company {} declined invite to Project {} '{}'",
-        company_id,
-        project_id,
-        project.name
+    record_platform_audit(
+        ctx,
+        ctx.sender(),
+        AuditAction::CompanyDeletionGraceChanged,
+        None,
+        None,
+        old_value,
+        days.to_string(),
     );
 
     Ok(())
 }
 
-/// Send a chat message in a project room. Caller must be Field+ in an Accepted
-/// member company. Fan-out notifications to all other Accepted companies.
+/// Sets the platform owner, who becomes a moderator whenever the platform has
+/// none (see `PlatformConfig`).
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator or `identity` is zero.
 #[spacetimedb::reducer]
-pub fn send_project_chat(
-    ctx: &ReducerContext,
-    project_id: u64,
-    text: String,
-) -> Result<(), String> {
-    let (account, company_id) = require_role_at_least(ctx, UserRole::Field)?;
+pub fn set_platform_owner(ctx: &ReducerContext, identity: Identity) -> Result<(), String> {
+    require_moderator(ctx)?;
 
-    let project = ctx
-        .db
-        .project()
-        .id()
-        .find(project_id)
-        .ok_or("Project not found")?;
+    if identity == Identity::ZERO {
+        return Err("Invalid identity".to_string());
+    }
 
-    // Caller's company must be an Accepted member
-    find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Accepted)
-        .ok_or("Your company is not a member of this project")?;
+    let previous = platform_config(ctx);
+    let old_value = if previous.owner_identity == Identity::ZERO {
+        String::new()
+    } else {
+        previous.owner_identity.to_hex().to_string()
+    };
+    save_platform_config(
+        ctx,
+        PlatformConfig {
+            owner_identity: identity,
+            ..previous
+        },
+    );
 
-    let text = text.trim().to_string();
-    validate_not_empty(&text, "Message")?;
-    validate_length(&text, "Message", MAX_MESSAGE)?;
+    record_platform_audit(
+        ctx,
+        ctx.sender(),
+        AuditAction::PlatformOwnerChanged,
+        Some(identity),
+        None,
+        old_value,
+        identity.to_hex().to_string(),
+    );
 
-    ctx.db.project_chat().insert(ProjectChat {
-        id: 0,
-        project_id,
-        sender: ctx.sender(),
-        text: text.clone(),
-        created_at: ctx.timestamp,
-    });
+    Ok(())
+}
 
-    let sender_name = account.nickname.clone();
-    let company_name = ctx
-        .db
-        .company()
-        .id()
-        .find(company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_else(|| "Unknown".to_string());
-    let preview = truncate_preview(&text, 80);
+/// Appoints another user as platform moderator.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator, the user has no account,
+/// or is already a moderator.
+#[spacetimedb::reducer]
+pub fn add_platform_moderator(ctx: &ReducerContext, identity: Identity) -> Result<(), String> {
+    require_moderator(ctx)?;
 
-    // Notify all other Accepted companies (Field+)
-    let other_accepted: Vec<u64> = ctx
+    ctx.db
+        .user_account()
+        .identity()
+        .find(identity)
+        .ok_or("User not found")?;
+    if ctx
         .db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .filter(|m| m.status == ProjectMemberStatus::Accepted && m.company_id != company_id)
-        .map(|m| m.company_id)
-        .collect();
-
-    for cid in other_accepted {
-        notify_company_role(
-            ctx,
-            cid,
-            UserRole::Field,
-            None,
-            NotificationType::ProjectChat,
-            format!("{} — {}", project.name, company_name),
-            format!("[{}] {}: {}", company_name, sender_name, preview),
-        );
+        .platform_moderator()
+        .identity()
+        .find(identity)
+        .is_some()
+    {
+        return Err("User is already a moderator".to_string());
     }
 
-    log::info!(
-        "AUDIT: User {} sent chat in Project {} (company {})",
-        id_short(ctx.sender()),
-        project_id,
-        company_id
+    ctx.db.platform_moderator().insert(PlatformModerator {
+        identity,
+        added_by: ctx.sender(),
+        added_at: ctx.timestamp,
+    });
+
+    record_platform_audit(
+        ctx,
+        ctx.sender(),
+        AuditAction::ModeratorAppointed,
+        Some(identity),
+        None,
+        String::new(),
+        String::new(),
     );
 
     Ok(())
 }
 
-/// Leave a project. The owner company cannot leave (must delete the project).
-/// If no Accepted members remain after leaving, the project is auto-deleted.
+/// Revokes another user's moderator status. Moderators cannot revoke their
+/// own, so at least one always remains.
+///
+/// # Errors
+///
+/// Returns an error if the caller is not a moderator, targets themselves, or
+/// the user is not a moderator.
 #[spacetimedb::reducer]
-pub fn leave_project(ctx: &ReducerContext, project_id: u64) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+pub fn remove_platform_moderator(ctx: &ReducerContext, identity: Identity) -> Result<(), String> {
+    require_moderator(ctx)?;
 
-    let project = ctx
-        .db
-        .project()
-        .id()
-        .find(project_id)
-        .ok_or("Project not found")?;
+    if identity == ctx.sender() {
+        return Err("Cannot remove yourself".to_string());
+    }
+
+    ctx.db
+        .platform_moderator()
+        .identity()
+        .find(identity)
+        .ok_or("User is not a moderator")?;
+    ctx.db.platform_moderator().identity().delete(identity);
+
+    record_platform_audit(
+        ctx,
+        ctx.sender(),
+        AuditAction::ModeratorRemoved,
+        Some(identity),
+        None,
+        String::new(),
+        String::new(),
+    );
 
-    if project.owner_company_id == company_id {
-        return Err("Owner company cannot leave. Delete the project instead.".to_string());
-    }
+    Ok(())
+}
 
-    let membership = find_project_membership(ctx, project_id, company_id, ProjectMemberStatus::Accepted)
-        .ok_or("Your company is not a member of this project")?;
+// ---------------------------------------------------------------------------
+// Privacy — Personal Data Export
+// ---------------------------------------------------------------------------
 
-    ctx.db.project_member().id().update(ProjectMember {
-        status: ProjectMemberStatus::Left,
-        ..membership
+/// Formats a timestamp for the export document.
+fn export_timestamp(ts: Timestamp) -> String {
+    ts.to_rfc3339().unwrap_or_default()
+}
+
+/// Assembles everything stored about `identity` into one JSON document.
+fn build_data_export(ctx: &ReducerContext, identity: Identity) -> serde_json::Value {
+    let account = ctx.db.user_account().identity().find(identity).map(|a| {
+        json!({
+            "full_name": a.full_name,
+            "nickname": a.nickname,
+            "email": ctx.db.user_contact().identity().find(identity).map(|c| c.email),
+            "active_company_id": a.active_company_id,
+            "created_at": export_timestamp(a.created_at),
+        })
     });
 
-    let company_name = ctx
+    let memberships: Vec<serde_json::Value> = ctx
+        .db
+        .company_member()
+        .member_by_identity()
+        .filter(&identity)
+        .map(|m| {
+            json!({
+                "company_id": m.company_id,
+                "company_name": ctx.db.company().id().find(m.company_id).map(|c| c.name),
+                "role": format!("{:?}", m.role),
+                "joined_at": export_timestamp(m.joined_at),
+            })
+        })
+        .collect();
+
+    let companies_owned: Vec<serde_json::Value> = ctx
         .db
         .company()
-        .id()
-        .find(company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_else(|| "Unknown".to_string());
+        .iter()
+        .filter(|c| c.owner_identity == identity)
+        .map(|c| {
+            json!({
+                "id": c.id,
+                "name": c.name,
+                "slug": c.slug,
+                "location": c.location,
+                "bio": c.bio,
+                "is_public": c.is_public,
+                "kvk_number": c.kvk_number,
+            })
+        })
+        .collect();
 
-    // Notify other Accepted companies' admins
-    let other_accepted: Vec<u64> = ctx
+    // Earlier versions of a message, including the text of a deleted one
+    let revisions = |kind: ChatKind, message_id: u64| -> Vec<serde_json::Value> {
+        ctx.db
+            .chat_revision()
+            .revision_by_message()
+            .filter(&message_id)
+            .filter(|r| r.kind == kind)
+            .map(|r| {
+                json!({
+                    "text": r.text,
+                    "revised_by": r.revised_by.to_hex().to_string(),
+                    "revised_at": export_timestamp(r.revised_at),
+                })
+            })
+            .collect()
+    };
+
+    let connection_messages: Vec<serde_json::Value> = ctx
         .db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .filter(|m| m.status == ProjectMemberStatus::Accepted && m.company_id != company_id)
-        .map(|m| m.company_id)
+        .connection_chat()
+        .chat_by_sender()
+        .filter(&identity)
+        .map(|m| {
+            json!({
+                "id": m.id,
+                "connection_id": m.connection_id,
+                "text": m.text,
+                "created_at": export_timestamp(m.created_at),
+                "revisions": revisions(ChatKind::Connection, m.id),
+            })
+        })
         .collect();
 
-    for cid in &other_accepted {
-        notify_company_role(
-            ctx,
-            *cid,
-            UserRole::Admin,
-            None,
-            NotificationType::ProjectLeft,
-            format!("{} left project", company_name),
-            format!(
-                "{} left project '{}'",
-                company_name, project.name
-            ),
-        );
-    }
+    let project_messages: Vec<serde_json::Value> = ctx
+        .db
+        .project_chat()
+        .pchat_by_sender()
+        .filter(&identity)
+        .map(|m| {
+            json!({
+                "id": m.id,
+                "project_id": m.project_id,
+                "text": m.text,
+                "created_at": export_timestamp(m.created_at),
+                "revisions": revisions(ChatKind::Project, m.id),
+            })
+        })
+        .collect();
 
-    log::info!(
-        "AUDIT: User {} (Company {}) left Project {} '{}'",
-        id_short(ctx.sender()),
-        company_id,
-        project_id,
-        project.name
-    );
+    let channel_messages: Vec<serde_json::Value> = ctx
+        .db
+        .channel_chat()
+        .channel_chat_by_sender()
+        .filter(&identity)
+        .map(|m| {
+            json!({
+                "id": m.id,
+                "channel_id": m.channel_id,
+                "text": m.text,
+                "created_at": export_timestamp(m.created_at),
+                "revisions": revisions(ChatKind::Channel, m.id),
+            })
+        })
+        .collect();
 
-    // If no Accepted members remain at all, auto-delete the project
-    let remaining_accepted = ctx
+    let attachments_uploaded: Vec<serde_json::Value> = ctx
         .db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .any(|m| m.status == ProjectMemberStatus::Accepted);
-    if !remaining_accepted {
-        delete_project_cascade(ctx, project_id);
-        log::info!(
-            "AUDIT: Project {} '{}' auto-deleted (no accepted members remain)",
-            project_id,
-            project.name
-        );
-    }
+        .attachment()
+        .attachment_by_uploader()
+        .filter(&identity)
+        .map(|a| {
+            json!({
+                "id": a.id,
+                "conversation": format!("{:?}", a.kind),
+                "conversation_id": a.conversation_id,
+                "filename": a.filename,
+                "mime_type": a.mime_type,
+                "size": a.size,
+                "checksum": a.checksum,
+                "message_id": a.message_id,
+                "created_at": export_timestamp(a.created_at),
+            })
+        })
+        .collect();
 
-    Ok(())
-}
+    let project_participations: Vec<serde_json::Value> = ctx
+        .db
+        .project_participant()
+        .pp_by_identity()
+        .filter(&identity)
+        .map(|p| {
+            json!({
+                "project_id": p.project_id,
+                "company_id": p.company_id,
+                "role": format!("{:?}", p.role),
+                "assigned_at": export_timestamp(p.assigned_at),
+            })
+        })
+        .collect();
 
-/// Kick a company from a project. Only the owner company's admins can kick.
-#[spacetimedb::reducer]
-pub fn kick_from_project(
-    ctx: &ReducerContext,
-    project_id: u64,
-    target_company_id: u64,
-) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+    let users_blocked: Vec<serde_json::Value> = ctx
+        .db
+        .user_block()
+        .ublock_by_blocker()
+        .filter(&identity)
+        .map(|b| {
+            json!({
+                "identity": b.blocked.to_hex().to_string(),
+                "created_at": export_timestamp(b.created_at),
+            })
+        })
+        .collect();
 
-    let project = ctx
+    let reports_filed: Vec<serde_json::Value> = ctx
         .db
-        .project()
-        .id()
-        .find(project_id)
-        .ok_or("Project not found")?;
+        .moderation_report()
+        .report_by_reporter()
+        .filter(&identity)
+        .map(|r| {
+            json!({
+                "id": r.id,
+                "target": format!("{:?}", r.target),
+                "reason": r.reason,
+                "status": format!("{:?}", r.status),
+                "decision": r.decision,
+                "created_at": export_timestamp(r.created_at),
+            })
+        })
+        .collect();
 
-    if project.owner_company_id != company_id {
-        return Err("Only the owner company can kick members".to_string());
-    }
+    let notifications: Vec<serde_json::Value> = ctx
+        .db
+        .notification()
+        .notif_by_recipient()
+        .filter(&identity)
+        .map(|n| {
+            json!({
+                "id": n.id,
+                "company_id": n.company_id,
+                "type": format!("{:?}", n.notification_type),
+                "title": n.title,
+                "body": n.body,
+                "is_read": n.is_read,
+                "created_at": export_timestamp(n.created_at),
+                "project_id": n.project_id,
+                "connection_id": n.connection_id,
+                "channel_id": n.channel_id,
+                "message_id": n.message_id,
+                "target_company_id": n.target_company_id,
+            })
+        })
+        .collect();
 
-    if target_company_id == company_id {
-        return Err("Cannot kick your own company".to_string());
-    }
+    let notification_preferences: Vec<serde_json::Value> = ctx
+        .db
+        .notification_preference()
+        .pref_by_identity()
+        .filter(&identity)
+        .map(|p| {
+            json!({
+                "company_id": p.company_id,
+                "type": format!("{:?}", p.notification_type),
+                "level": format!("{:?}", p.level),
+            })
+        })
+        .collect();
 
-    let membership = find_project_membership(ctx, project_id, target_company_id, ProjectMemberStatus::Accepted)
-        .ok_or("Target company is not an active member")?;
+    let conversation_mutes: Vec<serde_json::Value> = ctx
+        .db
+        .conversation_mute()
+        .mute_by_identity()
+        .filter(&identity)
+        .map(|m| {
+            json!({
+                "conversation": format!("{:?}", m.kind),
+                "conversation_id": m.conversation_id,
+                "muted_until": m.muted_until.map(export_timestamp),
+            })
+        })
+        .collect();
 
-    ctx.db.project_member().id().update(ProjectMember {
-        status: ProjectMemberStatus::Kicked,
-        ..membership
-    });
+    let invite_codes_created: Vec<serde_json::Value> = ctx
+        .db
+        .invite_code()
+        .iter()
+        .filter(|ic| ic.created_by == identity)
+        .map(|ic| {
+            json!({
+                "code": ic.code,
+                "company_id": ic.company_id,
+                "uses_remaining": ic.uses_remaining,
+                "role": format!("{:?}", ic.role),
+                "expires_at": ic.expires_at.map(export_timestamp),
+            })
+        })
+        .collect();
 
-    let target_name = ctx
+    let invite_codes_used: Vec<serde_json::Value> = ctx
         .db
-        .company()
-        .id()
-        .find(target_company_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_else(|| "Unknown".to_string());
+        .used_invite_code()
+        .used_by_identity()
+        .filter(&identity)
+        .map(|u| json!({ "code": u.code, "company_id": u.company_id }))
+        .collect();
 
-    // Notify kicked company's admins
-    notify_company_role(
-        ctx,
-        target_company_id,
-        UserRole::Admin,
-        None,
-        NotificationType::ProjectKicked,
-        format!("Removed from project '{}'", project.name),
-        format!(
-            "Your company has been removed from project '{}'",
-            project.name
-        ),
+    let mut audit_events: Vec<AuditEvent> = ctx
+        .db
+        .audit_event()
+        .audit_by_actor()
+        .filter(&identity)
+        .collect();
+    audit_events.extend(
+        ctx.db
+            .audit_event()
+            .audit_by_target_user()
+            .filter(&identity)
+            .filter(|e| e.actor != identity),
     );
+    audit_events.sort_by_key(|e| e.id);
+    let audit_events: Vec<serde_json::Value> = audit_events
+        .into_iter()
+        .map(|e| {
+            json!({
+                "company_id": e.company_id,
+                "actor": e.actor.to_hex().to_string(),
+                "action": format!("{:?}", e.action),
+                "target_identity": e.target_identity.map(|t| t.to_hex().to_string()),
+                "target_company_id": e.target_company_id,
+                "target_project_id": e.target_project_id,
+                "old_value": e.old_value,
+                "new_value": e.new_value,
+                "created_at": export_timestamp(e.created_at),
+            })
+        })
+        .collect();
 
-    // Notify other remaining Accepted companies' admins
-    let other_accepted: Vec<u64> = ctx
+    let personal_invitations: Vec<serde_json::Value> = ctx
         .db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .filter(|m| {
-            m.status == ProjectMemberStatus::Accepted
-                && m.company_id != company_id
-                && m.company_id != target_company_id
+        .user_contact()
+        .identity()
+        .find(identity)
+        .map(|c| {
+            ctx.db
+                .personal_invitation()
+                .pinv_by_email()
+                .filter(&c.email.to_lowercase())
+                .map(|i| {
+                    json!({
+                        "company_id": i.company_id,
+                        "email": i.email,
+                        "role": format!("{:?}", i.role),
+                        "created_at": export_timestamp(i.created_at),
+                    })
+                })
+                .collect()
         })
-        .map(|m| m.company_id)
-        .collect();
+        .unwrap_or_default();
 
-    for cid in other_accepted {
-        notify_company_role(
-            ctx,
-            cid,
-            UserRole::Admin,
-            None,
-            NotificationType::ProjectKicked,
-            format!("{} removed from project", target_name),
-            format!(
-                "{} was removed from project '{}'",
-                target_name, project.name
+    json!({
+        "identity": identity.to_hex().to_string(),
+        "exported_at": export_timestamp(ctx.timestamp),
+        "account": account,
+        "memberships": memberships,
+        "companies_owned": companies_owned,
+        "connection_messages": connection_messages,
+        "project_messages": project_messages,
+        "channel_messages": channel_messages,
+        "attachments_uploaded": attachments_uploaded,
+        "project_participations": project_participations,
+        "users_blocked": users_blocked,
+        "reports_filed": reports_filed,
+        "notifications": notifications,
+        "notification_preferences": notification_preferences,
+        "conversation_mutes": conversation_mutes,
+        "invite_codes_created": invite_codes_created,
+        "invite_codes_used": invite_codes_used,
+        "audit_events": audit_events,
+        "personal_invitations": personal_invitations,
+    })
+}
+
+/// Returns a JSON document with everything the platform stores about the
+/// calling identity (GDPR access request). Clients offer it as a download.
+#[spacetimedb::procedure]
+pub fn export_my_data(ctx: &mut ProcedureContext) -> String {
+    let identity = ctx.sender();
+    ctx.with_tx(|tx| build_data_export(tx, identity).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // -----------------------------------------------------------------------
+    // Personal invitations
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn verified_email_claim_requires_email_verified() {
+        assert_eq!(
+            verified_email_claim(r#"{"sub":"a","email":"Jan@Example.com","email_verified":true}"#),
+            Some("jan@example.com".to_string())
+        );
+        assert_eq!(
+            verified_email_claim(r#"{"sub":"a","email":"jan@example.com","email_verified":false}"#),
+            None
+        );
+        assert_eq!(
+            verified_email_claim(r#"{"sub":"a","email":"jan@example.com"}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn verified_email_claim_rejects_malformed_claims() {
+        assert_eq!(
+            verified_email_claim(r#"{"sub":"a","email_verified":true}"#),
+            None
+        );
+        assert_eq!(
+            verified_email_claim(r#"{"sub":"a","email":42,"email_verified":true}"#),
+            None
+        );
+        assert_eq!(
+            verified_email_claim(
+                r#"{"sub":"a","email":"jan@example.com","email_verified":"true"}"#
             ),
+            None
         );
+        assert_eq!(verified_email_claim("not json"), None);
     }
 
-    log::info!(
-        "AUDIT: User {} kicked Company {} from Project {} '{}'",
-        id_short(ctx.sender()),
-        target_company_id,
-        project_id,
-        project.name
-    );
+    // -----------------------------------------------------------------------
+    // Chat mentions
+    // -----------------------------------------------------------------------
 
-    Ok(())
-}
+    #[test]
+    fn parse_mentions_skips_email_addresses() {
+        assert_eq!(
+            parse_mentions("mail jan@example.com or ask @piet"),
+            vec!["piet"]
+        );
+        assert!(parse_mentions("jan@example.com").is_empty());
+    }
 
-/// Delete a project entirely. Only the owner company's admins can delete.
-/// Cascade-deletes all members and chat messages.
-#[spacetimedb::reducer]
-pub fn delete_project(ctx: &ReducerContext, project_id: u64) -> Result<(), String> {
-    let (_account, company_id) = require_role_at_least(ctx, UserRole::Admin)?;
+    #[test]
+    fn parse_mentions_trims_trailing_punctuation() {
+        assert_eq!(
+            parse_mentions("Thanks @John-Doe. Also @jan_, @piet- and @acme.nl."),
+            vec!["john-doe", "jan", "piet", "acme.nl"]
+        );
+    }
 
-    let project = ctx
-        .db
-        .project()
-        .id()
-        .find(project_id)
-        .ok_or("Project not found")?;
+    #[test]
+    fn parse_mentions_deduplicates_case_insensitively() {
+        assert_eq!(
+            parse_mentions("@jan @Jan (@JAN) @piet @jan"),
+            vec!["jan", "piet"]
+        );
+    }
 
-    if project.owner_company_id != company_id {
-        return Err("Only the owner company can delete the project".to_string());
+    #[test]
+    fn parse_mentions_ignores_bare_at_signs() {
+        assert!(parse_mentions("@").is_empty());
+        assert!(parse_mentions("meet me @ noon").is_empty());
+        assert_eq!(parse_mentions("cc @jan @"), vec!["jan"]);
+        assert!(parse_mentions("@... @--").is_empty());
     }
 
-    let project_name = project.name.clone();
+    // -----------------------------------------------------------------------
+    // Chat search
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn search_terms_counts_lowercased_words() {
+        assert_eq!(
+            search_terms("Invoice 7, invoice-2024; INVOICE a b"),
+            vec![
+                ("invoice".to_string(), 3),
+                ("7".to_string(), 1),
+                ("2024".to_string(), 1),
+            ]
+        );
+        assert!(search_terms("a - b !").is_empty());
+    }
 
-    // Notify all other Accepted members before deleting
-    let other_accepted: Vec<u64> = ctx
-        .db
-        .project_member()
-        .pm_by_project()
-        .filter(&project_id)
-        .filter(|m| m.status == ProjectMemberStatus::Accepted && m.company_id != company_id)
-        .map(|m| m.company_id)
-        .collect();
+    #[test]
+    fn search_terms_truncates_long_words() {
+        let terms = search_terms(&"x".repeat(MAX_SEARCH_TERM + 10));
+        assert_eq!(terms, vec![("x".repeat(MAX_SEARCH_TERM), 1)]);
+    }
 
-    for cid in other_accepted {
-        notify_company_role(
-            ctx,
-            cid,
-            UserRole::Admin,
-            None,
-            NotificationType::ProjectLeft,
-            format!("Project '{}' deleted", project_name),
+    #[test]
+    fn search_snippet_starts_before_the_first_match() {
+        let text = format!("{}Needle in the haystack", "a".repeat(50));
+        assert_eq!(
+            search_snippet(&text, &["needle".to_string()]),
             format!(
-                "Project '{}' has been deleted by the owner",
-                project_name
-            ),
+                "...{}Needle in the haystack",
+                "a".repeat(SEARCH_SNIPPET_LEAD)
+            )
+        );
+        assert_eq!(
+            search_snippet("short needle", &["needle".to_string()]),
+            "short needle"
+        );
+        assert_eq!(
+            search_snippet("no match", &["needle".to_string()]),
+            "no match"
         );
     }
 
-    delete_project_cascade(ctx, project_id);
-
-    log::info!(
-        "AUDIT: User {} deleted Project {} '{}'",
-        id_short(ctx.sender()),
-        project_id,
-        project_name
-    );
-
-    Ok(())
+    #[test]
+    fn search_snippet_counts_characters_of_the_original_text() {
+        // `İ` lowercases to two characters
+        let text = format!("{}Needle", "İ".repeat(50));
+        assert_eq!(
+            search_snippet(&text, &["needle".to_string()]),
+            format!("...{}Needle", "İ".repeat(SEARCH_SNIPPET_LEAD))
+        );
+    }
 }